@@ -6,4 +6,5 @@ mod binance_usd_futures;
 mod factory;
 mod gateio_usd_futures;
 
+pub use base::exchange::Exchange;
 pub use factory::ExchangeFactory;
@@ -1,5 +1,9 @@
 mod client;
+mod clock;
 pub mod errors;
 pub mod exchange;
+mod rate_limit;
 
 pub use client::USER_AGENT;
+pub use clock::{ServerClock, keep_clock_synced, local_ms};
+pub use rate_limit::{Limit, Priority, RateLimiter, Usage};
@@ -0,0 +1,98 @@
+use crate::models::{ClockConfig, Log, LogLevel};
+use std::sync::atomic::{AtomicI64, Ordering};
+use std::sync::mpsc::Sender;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tokio::time::sleep;
+
+/// Offset of the exchange clock from the local clock, applied to the timestamps of signed requests.
+pub struct ServerClock {
+    offset_ms: AtomicI64,
+}
+
+impl ServerClock {
+    pub const fn new() -> Self {
+        Self {
+            offset_ms: AtomicI64::new(0),
+        }
+    }
+
+    /// Exchange time in milliseconds.
+    pub fn now_ms(&self) -> u64 {
+        (local_ms() as i64 + self.offset_ms()) as u64
+    }
+
+    pub fn offset_ms(&self) -> i64 {
+        self.offset_ms.load(Ordering::Relaxed)
+    }
+
+    /// Sets the offset from the server time received in response to a request sent at `sent_ms`,
+    /// assuming the response took half of the round trip. Returns the offset.
+    pub fn sync(&self, server_ms: u64, sent_ms: u64, received_ms: u64) -> i64 {
+        let local_ms = sent_ms + received_ms.saturating_sub(sent_ms) / 2;
+        let offset_ms = server_ms as i64 - local_ms as i64;
+        self.offset_ms.store(offset_ms, Ordering::Relaxed);
+        offset_ms
+    }
+}
+
+/// Syncs the clock now and then every `sync_interval_secs`, warns when the local clock drifts too far.
+pub async fn keep_clock_synced<E: std::fmt::Debug>(
+    sync: impl AsyncFn() -> Result<i64, E>,
+    logs_sender: &Sender<Log>,
+    config: &ClockConfig,
+) {
+    loop {
+        match sync().await {
+            Ok(offset_ms) if offset_ms.unsigned_abs() > config.drift_warning_ms => {
+                let direction = if offset_ms > 0 { "behind" } else { "ahead of" };
+                logs_sender
+                    .send(Log::new(
+                        LogLevel::Warning("CLOCK".to_string(), Some(5)),
+                        format!(
+                            "Local clock is {}ms {} the exchange clock, signed requests use the exchange time",
+                            offset_ms.unsigned_abs(),
+                            direction
+                        ),
+                        None,
+                    ))
+                    .ok();
+            }
+            Ok(_) => {}
+            Err(e) => {
+                logs_sender
+                    .send(Log::new(
+                        LogLevel::Warning("CLOCK".to_string(), None),
+                        format!("Failed to sync clock: {:?}", e),
+                        None,
+                    ))
+                    .ok();
+            }
+        }
+        sleep(Duration::from_secs(config.sync_interval_secs.max(1))).await;
+    }
+}
+
+pub fn local_ms() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .expect("Time went backwards")
+        .as_millis() as u64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ServerClock, local_ms};
+
+    #[test]
+    fn test_sync() {
+        let clock = ServerClock::new();
+        assert_eq!(clock.offset_ms(), 0);
+
+        // the server is 1.5s ahead, the request took 200ms
+        assert_eq!(clock.sync(11_600, 10_000, 10_200), 1_500);
+        let now = clock.now_ms();
+        assert!(now >= local_ms() + 1_400 && now <= local_ms() + 1_500);
+
+        assert_eq!(clock.sync(9_100, 10_000, 10_200), -1_000);
+    }
+}
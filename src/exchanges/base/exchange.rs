@@ -1,11 +1,33 @@
-use crate::models::{Interval, NewOrder, Order, SharedState, Symbol};
+use crate::models::{
+    Capabilities, Interval, NewOrder, Order, RateLimitStatus, SharedState, Symbol, Timestamp,
+};
+use rust_decimal::Decimal;
+use std::time::Duration;
+
+// pause before a lost stream is reopened
+pub const RECONNECT_DELAY: Duration = Duration::from_secs(5);
+
+/// The candle interval in the format of the Binance and Gate.io kline endpoints.
+pub fn kline_interval(interval: Interval) -> &'static str {
+    match interval {
+        Interval::M1 => "1m",
+        Interval::M5 => "5m",
+        Interval::M15 => "15m",
+        Interval::H1 => "1h",
+    }
+}
 
 pub trait Exchange: Send + Sync {
     /// Returns the exchange name that is being displayed in the window header.
     fn name(&self) -> &str;
 
+    /// Returns the supported features, the UI hides or disables the others.
+    fn capabilities(&self) -> Capabilities;
+
     /// Should return Symbol and SharedState, and then keep them updated.
     /// Use channels to log errors and important events.
+    /// Errors carry the component that reported them (`Log::with_component`),
+    /// the component reports `LogLevel::Recovered` with the same message once it works again.
     fn start(
         &mut self,
         interval: Interval,
@@ -14,9 +36,24 @@ pub trait Exchange: Send + Sync {
     /// Gracefully stops all exchange activities and free resources.
     fn stop(&mut self) -> ();
 
+    /// Returns symbols available for trading, used by the symbol switcher.
+    fn symbols(&self) -> Result<Vec<String>, Box<dyn std::error::Error>>;
+
     /// Sets `shared_candles_state.interval` and populate it with historical data.
     fn set_interval(&self, interval: Interval) -> ();
 
+    /// Loads candles opened before `until` and prepends them to `shared_candles_state`.
+    /// This method should return immediately and load the candles in a new thread.
+    fn load_candles(&self, interval: Interval, until: Timestamp);
+
+    /// Returns Symbol and SharedState with the candles opened at or before `at`, without starting streams.
+    /// Used to render a chart image in the headless mode.
+    fn snapshot(
+        &self,
+        interval: Interval,
+        at: Timestamp,
+    ) -> Result<(Symbol, SharedState), Box<dyn std::error::Error>>;
+
     /// Submits an order.
     /// This method should return immediately, spawn a new thread to submit the order,
     /// then communicate updates or errors using channels.
@@ -24,4 +61,19 @@ pub trait Exchange: Send + Sync {
 
     /// Cancels an existing order. Similar to `place_order`, this method should return immediately.
     fn cancel_order(&self, order: Order) -> ();
+
+    /// Cancels the open `orders` at once, with a single summary log.
+    /// Exchanges without a cancel-all endpoint cancel them one by one.
+    fn cancel_all_orders(&self, orders: Vec<Order>) {
+        for order in orders {
+            self.cancel_order(order);
+        }
+    }
+
+    /// Moves an open limit order to `price`. Similar to `place_order`, this method should return immediately.
+    fn modify_order(&self, order: Order, price: Decimal) -> ();
+
+    /// Returns the REST request budget, order and cancel requests should be preferred over market data polls
+    /// and all requests should be paused while the exchange rate limits them.
+    fn rate_limit(&self) -> RateLimitStatus;
 }
@@ -0,0 +1,276 @@
+use super::clock::local_ms;
+use crate::models::{RateLimitStatus, Timestamp};
+use reqwest::StatusCode;
+use reqwest::header::HeaderMap;
+use std::sync::Mutex;
+use std::time::Duration;
+use tokio::time::sleep;
+
+// market data requests wait above this share of a limit, the rest is kept for orders and cancels
+const MARKET_DATA_SHARE_PERCENT: u32 = 80;
+// pause after a 429/418 response without a Retry-After header
+const DEFAULT_BACKOFF: Duration = Duration::from_secs(60);
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Priority {
+    Order,
+    MarketData,
+}
+
+/// How the exchange reports the usage of a limit in the response headers.
+#[derive(Debug, Clone, Copy)]
+pub enum Usage {
+    // used weight or count in the current window (Binance `X-MBX-USED-WEIGHT-1M`)
+    Used(&'static str),
+    // requests left in the current window and the reset time in seconds (Gate.io `X-Gate-RateLimit-*`)
+    Remaining {
+        remaining: &'static str,
+        reset_timestamp: &'static str,
+    },
+}
+
+/// A request limit of the exchange, the window starts at multiples of its length.
+#[derive(Debug, Clone, Copy)]
+pub struct Limit {
+    pub usage: Usage,
+    pub limit: u32,
+    pub window: Duration,
+    // counted only for orders, e.g. `X-MBX-ORDER-COUNT-10S`
+    pub orders_only: bool,
+}
+
+#[derive(Debug)]
+struct Counter {
+    limit: Limit,
+    used: u32,
+    reset_at: u64,
+}
+
+impl Counter {
+    fn refresh(&mut self, now: u64) {
+        if now >= self.reset_at {
+            let window = self.limit.window.as_millis().max(1) as u64;
+            self.used = 0;
+            self.reset_at = (now / window + 1) * window;
+        }
+    }
+
+    fn available(&self, priority: Priority) -> bool {
+        match priority {
+            Priority::Order => self.used < self.limit.limit,
+            Priority::MarketData => {
+                self.limit.orders_only
+                    || self.used * 100 < self.limit.limit * MARKET_DATA_SHARE_PERCENT
+            }
+        }
+    }
+}
+
+#[derive(Debug)]
+struct State {
+    counters: Vec<Counter>,
+    backoff_until: Option<u64>,
+}
+
+/// Client-side throttling of the REST requests of an exchange client.
+/// Usage is counted locally and corrected from the response headers,
+/// a 429/418 response pauses all requests until the exchange allows them again.
+#[derive(Debug)]
+pub struct RateLimiter {
+    state: Mutex<State>,
+}
+
+impl RateLimiter {
+    pub fn new(limits: &[Limit]) -> Self {
+        Self {
+            state: Mutex::new(State {
+                counters: limits
+                    .iter()
+                    .map(|limit| Counter {
+                        limit: *limit,
+                        used: 0,
+                        reset_at: 0,
+                    })
+                    .collect(),
+                backoff_until: None,
+            }),
+        }
+    }
+
+    /// Waits until a market data request is allowed.
+    /// Orders are not delayed: returns the time until they are allowed again instead.
+    pub async fn acquire(&self, priority: Priority) -> Result<(), Duration> {
+        loop {
+            match self.try_acquire(priority, local_ms()) {
+                Ok(()) => return Ok(()),
+                Err(wait) if priority == Priority::Order => return Err(wait),
+                Err(wait) => sleep(wait).await,
+            }
+        }
+    }
+
+    fn try_acquire(&self, priority: Priority, now: u64) -> Result<(), Duration> {
+        let mut state = self.state.lock().unwrap();
+        if let Some(until) = state.backoff_until {
+            if now < until {
+                return Err(Duration::from_millis(until - now));
+            }
+            state.backoff_until = None;
+        }
+
+        let counters = &mut state.counters;
+        counters.iter_mut().for_each(|counter| counter.refresh(now));
+        if let Some(counter) = counters
+            .iter()
+            .filter(|counter| !counter.available(priority))
+            .max_by_key(|counter| counter.reset_at)
+        {
+            return Err(Duration::from_millis(counter.reset_at - now));
+        }
+
+        for counter in counters.iter_mut() {
+            if priority == Priority::Order || !counter.limit.orders_only {
+                counter.used += 1;
+            }
+        }
+        Ok(())
+    }
+
+    /// Takes the usage reported by the exchange, and backs off on 429 (rate limited) and 418 (banned).
+    pub fn update(&self, status: StatusCode, headers: &HeaderMap) {
+        self.update_at(status, headers, local_ms());
+    }
+
+    fn update_at(&self, status: StatusCode, headers: &HeaderMap, now: u64) {
+        let header = |name: &str| {
+            headers
+                .get(name)
+                .and_then(|value| value.to_str().ok())
+                .and_then(|value| value.parse::<u64>().ok())
+        };
+
+        let mut state = self.state.lock().unwrap();
+        for counter in state.counters.iter_mut() {
+            counter.refresh(now);
+            match counter.limit.usage {
+                Usage::Used(name) => {
+                    if let Some(used) = header(name) {
+                        counter.used = used as u32;
+                    }
+                }
+                Usage::Remaining {
+                    remaining,
+                    reset_timestamp,
+                } => {
+                    if let Some(remaining) = header(remaining) {
+                        counter.used = counter.limit.limit.saturating_sub(remaining as u32);
+                    }
+                    if let Some(reset) = header(reset_timestamp) {
+                        counter.reset_at = reset * 1000;
+                    }
+                }
+            }
+        }
+
+        if status == StatusCode::TOO_MANY_REQUESTS || status == StatusCode::IM_A_TEAPOT {
+            let backoff = header("Retry-After")
+                .map(Duration::from_secs)
+                .unwrap_or(DEFAULT_BACKOFF);
+            state.backoff_until = Some(now + backoff.as_millis() as u64);
+        }
+    }
+
+    pub fn status(&self) -> RateLimitStatus {
+        let now = local_ms();
+        let mut state = self.state.lock().unwrap();
+        state
+            .counters
+            .iter_mut()
+            .for_each(|counter| counter.refresh(now));
+        RateLimitStatus {
+            used_percent: state
+                .counters
+                .iter()
+                .map(|counter| counter.used * 100 / counter.limit.limit.max(1))
+                .max()
+                .unwrap_or(0),
+            throttled: state
+                .counters
+                .iter()
+                .any(|counter| !counter.available(Priority::MarketData)),
+            backoff_until: state
+                .backoff_until
+                .filter(|until| *until > now)
+                .map(Timestamp::from_milliseconds),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Limit, Priority, RateLimiter, Usage};
+    use reqwest::StatusCode;
+    use reqwest::header::{HeaderMap, HeaderValue};
+    use std::time::Duration;
+
+    fn limiter() -> RateLimiter {
+        RateLimiter::new(&[
+            Limit {
+                usage: Usage::Used("x-mbx-used-weight-1m"),
+                limit: 100,
+                window: Duration::from_secs(60),
+                orders_only: false,
+            },
+            Limit {
+                usage: Usage::Used("x-mbx-order-count-10s"),
+                limit: 10,
+                window: Duration::from_secs(10),
+                orders_only: true,
+            },
+        ])
+    }
+
+    #[test]
+    fn test_orders_have_priority() {
+        let limiter = limiter();
+        let now = 60_000;
+        let mut headers = HeaderMap::new();
+        headers.insert("x-mbx-used-weight-1m", HeaderValue::from_static("85"));
+        limiter.update_at(StatusCode::OK, &headers, now);
+
+        // market data waits until the minute is over, orders still go through
+        assert_eq!(
+            limiter.try_acquire(Priority::MarketData, now + 1_000),
+            Err(Duration::from_secs(59))
+        );
+        assert_eq!(limiter.try_acquire(Priority::Order, now + 1_000), Ok(()));
+
+        // the order count is only limited for orders
+        headers.insert("x-mbx-order-count-10s", HeaderValue::from_static("10"));
+        headers.insert("x-mbx-used-weight-1m", HeaderValue::from_static("10"));
+        limiter.update_at(StatusCode::OK, &headers, now + 2_000);
+        assert_eq!(
+            limiter.try_acquire(Priority::Order, now + 2_000),
+            Err(Duration::from_secs(8))
+        );
+        assert_eq!(
+            limiter.try_acquire(Priority::MarketData, now + 2_000),
+            Ok(())
+        );
+        assert_eq!(limiter.try_acquire(Priority::Order, now + 10_000), Ok(()));
+    }
+
+    #[test]
+    fn test_backoff() {
+        let limiter = limiter();
+        let mut headers = HeaderMap::new();
+        headers.insert("Retry-After", HeaderValue::from_static("30"));
+        limiter.update_at(StatusCode::TOO_MANY_REQUESTS, &headers, 1_000);
+
+        assert_eq!(
+            limiter.try_acquire(Priority::Order, 11_000),
+            Err(Duration::from_secs(20))
+        );
+        assert_eq!(limiter.try_acquire(Priority::MarketData, 31_000), Ok(()));
+    }
+}
@@ -1,2 +1,3 @@
 pub mod auth;
 pub mod errors;
+pub mod stream_hub;
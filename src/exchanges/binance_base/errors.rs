@@ -9,6 +9,7 @@ pub enum BinanceError {
     ParseError(String),
     WebSocketError(String),
     InvalidParameter(String),
+    RateLimitError(String),
 }
 
 impl fmt::Display for BinanceError {
@@ -20,6 +21,7 @@ impl fmt::Display for BinanceError {
             BinanceError::ParseError(msg) => write!(f, "Parse error: {}", msg),
             BinanceError::WebSocketError(msg) => write!(f, "WebSocket error: {}", msg),
             BinanceError::InvalidParameter(msg) => write!(f, "Invalid parameter: {}", msg),
+            BinanceError::RateLimitError(msg) => write!(f, "Rate limit: {}", msg),
         }
     }
 }
@@ -0,0 +1,193 @@
+use futures_util::{SinkExt, StreamExt};
+use std::sync::Mutex;
+use std::thread;
+use std::time::Duration;
+use tokio::runtime;
+use tokio::sync::mpsc::{UnboundedReceiver, UnboundedSender, unbounded_channel};
+use tokio::time::interval;
+use tokio_tungstenite::{connect_async, tungstenite::protocol::Message};
+
+// raw combined stream message, or the error that closed the connection
+pub type HubMessage = Result<String, String>;
+
+// how often streams of dropped receivers are unsubscribed
+const UNSUBSCRIBE_INTERVAL: Duration = Duration::from_secs(1);
+
+enum HubCommand {
+    Subscribe(Vec<String>, UnboundedSender<HubMessage>),
+}
+
+/// Shares one combined stream connection (`/stream`) between all symbols of an exchange.
+/// Streams are added with the `SUBSCRIBE` method and removed shortly after nobody listens to them.
+/// The connection is opened on the first subscription and reopened after it was lost.
+pub struct StreamHub {
+    url: &'static str,
+    commands: Mutex<Option<UnboundedSender<HubCommand>>>,
+}
+
+impl StreamHub {
+    pub const fn new(url: &'static str) -> Self {
+        Self {
+            url,
+            commands: Mutex::new(None),
+        }
+    }
+
+    /// Returns messages of the given streams, e.g. `btcusdt@aggTrade`.
+    /// The receiver is closed when the connection is lost, drop it to unsubscribe.
+    pub fn subscribe(&self, streams: Vec<String>) -> UnboundedReceiver<HubMessage> {
+        let (tx, rx) = unbounded_channel();
+        let mut commands = self.commands.lock().unwrap();
+        if commands.as_ref().is_none_or(|c| c.is_closed()) {
+            *commands = Some(self.spawn());
+        }
+        if let Some(commands) = commands.as_ref() {
+            let _ = commands.send(HubCommand::Subscribe(streams, tx));
+        }
+        rx
+    }
+
+    fn spawn(&self) -> UnboundedSender<HubCommand> {
+        let (tx, rx) = unbounded_channel();
+        let url = self.url;
+        thread::spawn(move || {
+            let rt = runtime::Builder::new_current_thread()
+                .enable_all()
+                .build()
+                .expect("Failed to build tokio runtime for the stream hub");
+            rt.block_on(run(url, rx));
+        });
+        tx
+    }
+}
+
+async fn run(url: &str, mut commands: UnboundedReceiver<HubCommand>) {
+    let mut subscribers: Vec<(Vec<String>, UnboundedSender<HubMessage>)> = Vec::new();
+
+    let (mut write, mut read) = match connect_async(url).await {
+        Ok((ws_stream, _)) => ws_stream.split(),
+        Err(e) => {
+            // fail subscriptions that are already queued
+            commands.close();
+            while let Some(HubCommand::Subscribe(_, tx)) = commands.recv().await {
+                let _ = tx.send(Err(e.to_string()));
+            }
+            return;
+        }
+    };
+
+    let mut request_id = 0;
+    let mut cleanup = interval(UNSUBSCRIBE_INTERVAL);
+    let error = loop {
+        tokio::select! {
+            command = commands.recv() => match command {
+                Some(HubCommand::Subscribe(streams, tx)) => {
+                    request_id += 1;
+                    let request = serde_json::json!({
+                        "method": "SUBSCRIBE",
+                        "params": streams,
+                        "id": request_id,
+                    });
+                    if let Err(e) = write.send(Message::Text(request.to_string().into())).await {
+                        let _ = tx.send(Err(e.to_string()));
+                        break e.to_string();
+                    }
+                    subscribers.push((streams, tx));
+                }
+                None => break "Stream hub stopped".to_string(),
+            },
+            msg = read.next() => match msg {
+                Some(Ok(Message::Text(text))) => {
+                    let stream = serde_json::from_str::<serde_json::Value>(&text)
+                        .ok()
+                        .and_then(|v| v.get("stream").and_then(|s| s.as_str()).map(str::to_string));
+                    let Some(stream) = stream else {
+                        // subscription responses
+                        continue;
+                    };
+                    for (streams, tx) in subscribers.iter() {
+                        if streams.contains(&stream) {
+                            let _ = tx.send(Ok(text.to_string()));
+                        }
+                    }
+                }
+                Some(Ok(Message::Close(_))) | None => break "WebSocket closed".to_string(),
+                Some(Err(e)) => break e.to_string(),
+                _ => {}
+            },
+            // streams of closed panes, also when nothing else is received
+            _ = cleanup.tick() => {
+                let unused = unused_streams(&mut subscribers);
+                if !unused.is_empty() {
+                    request_id += 1;
+                    let request = serde_json::json!({
+                        "method": "UNSUBSCRIBE",
+                        "params": unused,
+                        "id": request_id,
+                    });
+                    if let Err(e) = write.send(Message::Text(request.to_string().into())).await {
+                        break e.to_string();
+                    }
+                }
+            }
+        }
+    };
+
+    for (_, tx) in subscribers {
+        let _ = tx.send(Err(error.clone()));
+    }
+}
+
+/// Removes closed subscribers, returns their streams no one else listens to.
+fn unused_streams(
+    subscribers: &mut Vec<(Vec<String>, UnboundedSender<HubMessage>)>,
+) -> Vec<String> {
+    let (closed, open): (Vec<_>, Vec<_>) =
+        subscribers.drain(..).partition(|(_, tx)| tx.is_closed());
+    *subscribers = open;
+
+    let mut unused: Vec<String> = closed
+        .into_iter()
+        .flat_map(|(streams, _)| streams)
+        .collect();
+    unused.retain(|stream| {
+        !subscribers
+            .iter()
+            .any(|(streams, _)| streams.contains(stream))
+    });
+    unused.sort();
+    unused.dedup();
+    unused
+}
+
+#[cfg(test)]
+mod tests {
+    use super::unused_streams;
+    use tokio::sync::mpsc::unbounded_channel;
+
+    #[test]
+    fn test_unused_streams() {
+        let (tx_1, rx_1) = unbounded_channel();
+        let (tx_2, _rx_2) = unbounded_channel();
+        let mut subscribers = vec![
+            (
+                vec![
+                    "btcusdt@aggTrade".to_string(),
+                    "btcusdt@kline_1m".to_string(),
+                ],
+                tx_1,
+            ),
+            (vec!["btcusdt@aggTrade".to_string()], tx_2),
+        ];
+
+        assert!(unused_streams(&mut subscribers).is_empty());
+        assert_eq!(subscribers.len(), 2);
+
+        drop(rx_1);
+        assert_eq!(
+            unused_streams(&mut subscribers),
+            vec!["btcusdt@kline_1m".to_string()]
+        );
+        assert_eq!(subscribers.len(), 1);
+    }
+}
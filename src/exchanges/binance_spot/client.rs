@@ -291,13 +291,42 @@ impl BinanceClient {
         self.runtime.block_on(self.get_symbol())
     }
 
+    /// Returns all symbols that are currently trading.
+    pub async fn get_symbols(&self) -> Result<Vec<String>> {
+        let exchange_info: ExchangeInfo = self.get_public("/api/v3/exchangeInfo", None).await?;
+        Ok(exchange_info
+            .symbols
+            .into_iter()
+            .filter(|sym| sym.status == "TRADING")
+            .map(|sym| sym.symbol)
+            .collect())
+    }
+
+    pub fn get_symbols_sync(&self) -> Result<Vec<String>> {
+        self.runtime.block_on(self.get_symbols())
+    }
+
     pub async fn get_candles(&self, interval: &str, limit: usize) -> Result<Vec<Candle>> {
+        self.get_candles_until(interval, limit, None).await
+    }
+
+    /// Returns candles opened before `end_time`, or the latest ones if it's not set.
+    pub async fn get_candles_until(
+        &self,
+        interval: &str,
+        limit: usize,
+        end_time: Option<Timestamp>,
+    ) -> Result<Vec<Candle>> {
         let limit_str = limit.to_string();
-        let params: Vec<(&str, &str)> = vec![
+        let end_time_str = end_time.map(|t| (t.milliseconds() - 1).to_string());
+        let mut params: Vec<(&str, &str)> = vec![
             ("symbol", self.symbol.as_str()),
             ("interval", interval),
             ("limit", limit_str.as_str()),
         ];
+        if let Some(end_time_str) = &end_time_str {
+            params.push(("endTime", end_time_str.as_str()));
+        }
         let data: Vec<serde_json::Value> = self.get_public("/api/v3/klines", Some(&params)).await?;
 
         let candles: Vec<Candle> = data
@@ -319,6 +348,16 @@ impl BinanceClient {
         self.runtime.block_on(self.get_candles(interval, limit))
     }
 
+    pub fn get_candles_until_sync(
+        &self,
+        interval: &str,
+        limit: usize,
+        end_time: Option<Timestamp>,
+    ) -> Result<Vec<Candle>> {
+        self.runtime
+            .block_on(self.get_candles_until(interval, limit, end_time))
+    }
+
     pub async fn get_depth(&self, limit: usize) -> Result<DepthSnapshot> {
         let limit_str = limit.to_string();
         let params: Vec<(&str, &str)> = vec![
@@ -347,6 +386,7 @@ pub struct ExchangeInfo {
 #[serde(rename_all = "camelCase")]
 pub struct SymbolInfo {
     pub symbol: String,
+    pub status: String,
     pub filters: Vec<Filter>,
 }
 
@@ -1,10 +1,12 @@
 use super::client::BinanceClient;
 use super::market_stream::start_market_stream;
-use crate::exchanges::base::exchange::Exchange;
+use crate::exchanges::base::exchange::{Exchange, RECONNECT_DELAY, kline_interval};
 use crate::models::{
-    CandlesState, Interval, Log, LogLevel, NewOrder, OpenInterestState, Order, OrderBookState,
-    OrderFlowState, SharedCandlesState, SharedState, Symbol,
+    CandlesState, Capabilities, Interval, Log, LogLevel, NewOrder, NotificationEvent,
+    OpenInterestState, Order, OrderBookState, OrderFlowState, RateLimitStatus, SharedCandlesState,
+    SharedState, Sound, Symbol, Timestamp,
 };
+use rust_decimal::Decimal;
 use std::sync::{Arc, RwLock, mpsc::Sender};
 use std::thread;
 use std::time::Duration;
@@ -29,6 +31,10 @@ impl Exchange for BinanceUSSpotExchange {
         self.name
     }
 
+    fn capabilities(&self) -> Capabilities {
+        Capabilities::DATA_ONLY
+    }
+
     fn start(
         &mut self,
         interval: Interval,
@@ -64,24 +70,38 @@ impl Exchange for BinanceUSSpotExchange {
                 .expect("Failed to build tokio runtime for streams");
 
             rt.block_on(async move {
+                let component = format!("{} market stream", symbol_clone);
                 tokio::select! {
-                    res = start_market_stream(
-                        &client_clone,
-                        &symbol_clone,
-                        500,
-                        candles_clone,
-                        order_book_clone,
-                        order_flow_clone,
-                    ) => {
-                        if let Err(e) = res {
-                            logs_sender_clone.send(
-                                Log::new(
-                                    LogLevel::Error("CONN".to_string()),
-                                    format!("{:?}", e),
-                                    None,
-                                )).ok();
+                    _ = async {
+                        loop {
+                            let res = start_market_stream(
+                                &client_clone,
+                                &symbol_clone,
+                                500,
+                                candles_clone.clone(),
+                                order_book_clone.clone(),
+                                order_flow_clone.clone(),
+                                || {
+                                    logs_sender_clone.send(
+                                        Log::new(
+                                            LogLevel::Recovered("CONN".to_string()),
+                                            "Market stream connected".to_string(),
+                                            None,
+                                        ).with_component(&component)).ok();
+                                },
+                            ).await;
+                            if let Err(e) = res {
+                                logs_sender_clone.send(
+                                    Log::new(
+                                        LogLevel::Error("CONN".to_string()),
+                                        format!("{:?}", e),
+                                        Some(Sound::Disconnected),
+                                    ).with_event(NotificationEvent::ConnectionLost)
+                                    .with_component(&component)).ok();
+                            }
+                            sleep(RECONNECT_DELAY).await;
                         }
-                    }
+                    } => {}
 
                     _ = shutdown_rx => {
                         logs_sender_clone.send(
@@ -111,7 +131,7 @@ impl Exchange for BinanceUSSpotExchange {
         ))
     }
 
-    fn stop(&mut self) -> () {
+    fn stop(&mut self) {
         if let Some(stop_tx) = self.stop_tx.take() {
             let _ = stop_tx.send(());
         }
@@ -122,17 +142,14 @@ impl Exchange for BinanceUSSpotExchange {
         self.handle = None;
     }
 
-    fn set_interval(&self, interval: Interval) -> () {
-        let interval_str = match interval {
-            Interval::M1 => "1m",
-            Interval::M5 => "5m",
-            Interval::M15 => "15m",
-            Interval::H1 => "1h",
-        };
+    fn symbols(&self) -> Result<Vec<String>, Box<dyn std::error::Error>> {
+        Ok(self.client.get_symbols_sync()?)
+    }
 
+    fn set_interval(&self, interval: Interval) {
         let candles = self
             .client
-            .get_candles_sync(&interval_str, self.candles_limit)
+            .get_candles_sync(kline_interval(interval), self.candles_limit)
             .unwrap();
 
         if let Some(shared_candles_state) = self.shared_candles_state.as_ref() {
@@ -144,7 +161,57 @@ impl Exchange for BinanceUSSpotExchange {
         }
     }
 
-    fn place_order(&self, _new_order: NewOrder) -> () {
+    fn load_candles(&self, interval: Interval, until: Timestamp) {
+        let Some(shared_candles_state) = self.shared_candles_state.clone() else {
+            return;
+        };
+        let client = self.client.clone();
+        let limit = self.candles_limit;
+        let logs_sender_clone = self.logs_sender.clone();
+        thread::spawn(move || {
+            match client.get_candles_until_sync(kline_interval(interval), limit, Some(until)) {
+                Ok(candles) => {
+                    let mut buffer = shared_candles_state.write().unwrap();
+                    // the interval could be changed while loading
+                    if buffer.interval == interval {
+                        buffer.prepend(candles);
+                    }
+                }
+                Err(e) => {
+                    let _ = logs_sender_clone.send(Log::new(
+                        LogLevel::Warning("WARN".to_string(), None),
+                        format!("Failed to load candles: {:?}", e),
+                        None,
+                    ));
+                }
+            }
+        });
+    }
+
+    fn snapshot(
+        &self,
+        interval: Interval,
+        at: Timestamp,
+    ) -> Result<(Symbol, SharedState), Box<dyn std::error::Error>> {
+        let symbol = self.client.get_symbol_sync()?;
+        let candles = self.client.get_candles_until_sync(
+            kline_interval(interval),
+            self.candles_limit,
+            Some(Timestamp::from_milliseconds(at.milliseconds() + 1)),
+        )?;
+
+        let shared_state = SharedState::new(self.candles_limit, interval);
+        {
+            let mut buffer = shared_state.candles.write().unwrap();
+            for candle in candles {
+                buffer.push(candle);
+            }
+        }
+
+        Ok((symbol, shared_state))
+    }
+
+    fn place_order(&self, _new_order: NewOrder) {
         let _ = self.logs_sender.send(Log::new(
             LogLevel::Warning("NA".to_string(), None),
             format!("Trading is not supported on {}", self.name()).to_string(),
@@ -152,13 +219,25 @@ impl Exchange for BinanceUSSpotExchange {
         ));
     }
 
-    fn cancel_order(&self, _order: Order) -> () {
+    fn cancel_order(&self, _order: Order) {
         let _ = self.logs_sender.send(Log::new(
             LogLevel::Warning("NA".to_string(), None),
             format!("Trading is not supported on {}", self.name()).to_string(),
             None,
         ));
     }
+
+    fn modify_order(&self, _order: Order, _price: Decimal) {
+        let _ = self.logs_sender.send(Log::new(
+            LogLevel::Warning("NA".to_string(), None),
+            format!("Trading is not supported on {}", self.name()).to_string(),
+            None,
+        ));
+    }
+
+    fn rate_limit(&self) -> RateLimitStatus {
+        RateLimitStatus::default()
+    }
 }
 
 impl BinanceUSSpotExchange {
@@ -1,13 +1,15 @@
 use super::client::BinanceClient;
+use crate::exchanges::binance_base::stream_hub::StreamHub;
 use crate::models::{
-    Candle, CandlesState, Interval, SharedCandlesState, SharedOrderBookState, SharedOrderFlowState,
-    Timestamp,
+    Candle, CandlesState, Interval, OrderSide, SharedCandlesState, SharedOrderBookState,
+    SharedOrderFlowState, Timestamp, Trade,
 };
-use futures_util::stream::StreamExt;
 use rust_decimal::Decimal;
 use serde::Deserialize;
 use std::str::FromStr;
-use tokio_tungstenite::{connect_async, tungstenite::protocol::Message};
+
+// one connection for all symbols of the exchange
+static STREAMS: StreamHub = StreamHub::new("wss://stream.binance.us:9443/stream");
 
 pub async fn start_market_stream(
     client: &BinanceClient,
@@ -16,15 +18,15 @@ pub async fn start_market_stream(
     shared_candles_state: SharedCandlesState,
     shared_dom_state: SharedOrderBookState,
     shared_order_flow_state: SharedOrderFlowState,
+    // called on the first message, after a reconnect it reports the recovery
+    on_connected: impl Fn(),
 ) -> Result<(), Box<dyn std::error::Error>> {
-    let ws_url = format!(
-        "wss://stream.binance.us:9443/stream?streams={}@kline_1m/{}@depth@100ms/{}@aggTrade",
-        symbol.to_lowercase(),
-        symbol.to_lowercase(),
-        symbol.to_lowercase(),
-    );
-    let (ws_stream, _) = connect_async(ws_url).await?;
-    let (_write, mut read) = ws_stream.split();
+    let symbol_lower = symbol.to_lowercase();
+    let mut read = STREAMS.subscribe(vec![
+        format!("{}@kline_1m", symbol_lower),
+        format!("{}@depth@100ms", symbol_lower),
+        format!("{}@aggTrade", symbol_lower),
+    ]);
 
     let mut candles_state_1m = CandlesState::new(60, Interval::M1);
     for c in client
@@ -59,9 +61,14 @@ pub async fn start_market_stream(
         buffer.init_snapshot(bids, asks);
     }
 
-    while let Some(msg) = read.next().await {
+    let mut connected = false;
+    while let Some(msg) = read.recv().await {
         match msg {
-            Ok(Message::Text(text)) => {
+            Ok(text) => {
+                if !connected {
+                    connected = true;
+                    on_connected();
+                }
                 if let Some(data) = extract_inner(&text) {
                     if let Ok(event) = serde_json::from_value::<DepthUpdateEvent>(data.clone()) {
                         let mut buffer = shared_dom_state.write().unwrap();
@@ -83,6 +90,9 @@ pub async fn start_market_stream(
                             }
                         }
                         buffer.updated = Timestamp::from_milliseconds(event.event_time);
+                        buffer
+                            .stats
+                            .record(Timestamp::from_milliseconds(event.event_time));
                         buffer.online = true;
                     } else if let Ok(event) = serde_json::from_value::<AggTradeEvent>(data.clone())
                     {
@@ -91,13 +101,21 @@ pub async fn start_market_stream(
                             Decimal::from_str(&event.quantity),
                         ) {
                             let mut buffer = shared_order_flow_state.write().unwrap();
-                            // If maker == true, buyer is the market maker -> trade was seller-initiated (sell)
-                            if event.maker {
-                                buffer.sell(price, qty);
-                            } else {
-                                buffer.buy(price, qty);
-                            }
+                            buffer.add_trade(Trade {
+                                time: Timestamp::from_milliseconds(event.event_time),
+                                price,
+                                quantity: qty,
+                                // If maker == true, buyer is the market maker -> trade was seller-initiated (sell)
+                                side: if event.maker {
+                                    OrderSide::Sell
+                                } else {
+                                    OrderSide::Buy
+                                },
+                            });
                             buffer.updated = Timestamp::from_milliseconds(event.event_time);
+                            buffer
+                                .stats
+                                .record(Timestamp::from_milliseconds(event.event_time));
                             buffer.online = true;
                         }
                     } else if let Ok(event) = serde_json::from_value::<KlineEvent>(data.clone()) {
@@ -115,24 +133,19 @@ pub async fn start_market_stream(
                         if let Some(candle) = candles_state_1m.to_candle(&buffer.interval) {
                             buffer.push(candle);
                             buffer.updated = Timestamp::from_milliseconds(event.event_time);
+                            buffer
+                                .stats
+                                .record(Timestamp::from_milliseconds(event.event_time));
                             buffer.online = true;
                         };
                     }
                 }
             }
-            Ok(Message::Close(_)) => {
-                let mut buffer = shared_candles_state.write().unwrap();
-                buffer.online = false;
-                println!("WebSocket closed");
-                break;
-            }
             Err(e) => {
                 let mut buffer = shared_candles_state.write().unwrap();
                 buffer.online = false;
-                eprintln!("Error: {}", e);
-                break;
+                return Err(e.into());
             }
-            _ => {}
         }
     }
 
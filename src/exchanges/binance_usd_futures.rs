@@ -2,6 +2,7 @@ mod client;
 mod exchange;
 mod market_stream;
 mod open_interest_stream;
+mod order_api;
 mod orders_stream;
 
 pub use exchange::BinanceUSDFuturesExchange;
@@ -1,34 +1,69 @@
-use crate::exchanges::base::USER_AGENT;
-use crate::exchanges::binance_base::auth::{build_signed_query, get_timestamp};
+use crate::exchanges::base::{Limit, Priority, RateLimiter, ServerClock, USER_AGENT, Usage};
+use crate::exchanges::binance_base::auth::{build_signed_query, get_timestamp, sign};
 use crate::exchanges::binance_base::errors::{BinanceError, Result};
 use crate::models::{
-    Candle, NewOrder, Order, OrderSide, OrderStatus, OrderType, Symbol, Timestamp,
+    Candle, NewOrder, Order, OrderSide, OrderStatus, OrderType, RateLimitStatus, Symbol, Timestamp,
 };
-use reqwest::{Client, Response};
+use reqwest::{Client, RequestBuilder, Response};
 use rust_decimal::Decimal;
 use serde::Deserialize;
 use serde::de::DeserializeOwned;
+use serde_json::{Map, Value};
 use std::str::FromStr;
+use std::time::Duration;
 use tokio::runtime::Runtime;
 
 const BASE_URL: &str = "https://fapi.binance.com";
 
+// https://developers.binance.com/docs/derivatives/usds-margined-futures/general-info#limits
+const LIMITS: [Limit; 3] = [
+    Limit {
+        usage: Usage::Used("x-mbx-used-weight-1m"),
+        limit: 2400,
+        window: Duration::from_secs(60),
+        orders_only: false,
+    },
+    Limit {
+        usage: Usage::Used("x-mbx-order-count-10s"),
+        limit: 300,
+        window: Duration::from_secs(10),
+        orders_only: true,
+    },
+    Limit {
+        usage: Usage::Used("x-mbx-order-count-1m"),
+        limit: 1200,
+        window: Duration::from_secs(60),
+        orders_only: true,
+    },
+];
+
 pub struct BinanceClient {
     client: Client,
     symbol: String,
     access_key: Option<String>,
     secret_key: Option<String>,
     runtime: Runtime,
+    clock: ServerClock,
+    recv_window_ms: u64,
+    rate_limiter: RateLimiter,
 }
 
 impl BinanceClient {
-    pub fn new(symbol: String, access_key: Option<String>, secret_key: Option<String>) -> Self {
+    pub fn new(
+        symbol: String,
+        access_key: Option<String>,
+        secret_key: Option<String>,
+        recv_window_ms: u64,
+    ) -> Self {
         Self {
             client: Client::builder().user_agent(USER_AGENT).build().unwrap(),
             symbol,
             access_key,
             secret_key,
             runtime: Runtime::new().expect("Failed to create BinanceClient Tokio runtime"),
+            clock: ServerClock::new(),
+            recv_window_ms,
+            rate_limiter: RateLimiter::new(&LIMITS),
         }
     }
 
@@ -59,6 +94,24 @@ impl BinanceClient {
         }
     }
 
+    async fn send<T: DeserializeOwned>(
+        &self,
+        request: RequestBuilder,
+        priority: Priority,
+    ) -> Result<T> {
+        self.rate_limiter.acquire(priority).await.map_err(|wait| {
+            BinanceError::RateLimitError(format!("Requests are paused for {}s", wait.as_secs() + 1))
+        })?;
+        let response = request.send().await?;
+        self.rate_limiter
+            .update(response.status(), response.headers());
+        self.handle_response(response).await
+    }
+
+    pub fn rate_limit(&self) -> RateLimitStatus {
+        self.rate_limiter.status()
+    }
+
     async fn get_public<T: DeserializeOwned>(
         &self,
         endpoint: &str,
@@ -75,8 +128,7 @@ impl BinanceClient {
             url.push_str(&format!("?{}", query));
         }
 
-        let response = self.client.get(&url).send().await?;
-        self.handle_response(response).await
+        self.send(self.client.get(&url), Priority::MarketData).await
     }
 
     async fn get_signed<T: DeserializeOwned>(
@@ -93,8 +145,9 @@ impl BinanceClient {
             .as_ref()
             .ok_or_else(|| BinanceError::AuthError("Secret key not set".to_string()))?;
 
-        let timestamp = get_timestamp().to_string();
+        let timestamp = self.clock.now_ms().to_string();
         let mut all_params = params.unwrap_or_default();
+        all_params.push(("recvWindow", self.recv_window_ms.to_string()));
         all_params.push(("timestamp", timestamp));
 
         let params_ref: Vec<(&str, &str)> =
@@ -103,14 +156,11 @@ impl BinanceClient {
         let query = build_signed_query(&params_ref, secret_key);
         let url = format!("{}{}?{}", BASE_URL, endpoint, query);
 
-        let response = self
-            .client
-            .get(&url)
-            .header("X-MBX-APIKEY", access_key)
-            .send()
-            .await?;
-
-        self.handle_response(response).await
+        self.send(
+            self.client.get(&url).header("X-MBX-APIKEY", access_key),
+            Priority::Order,
+        )
+        .await
     }
 
     async fn post_signed<T: DeserializeOwned>(
@@ -127,8 +177,9 @@ impl BinanceClient {
             .as_ref()
             .ok_or_else(|| BinanceError::AuthError("Secret key not set".to_string()))?;
 
-        let timestamp = get_timestamp().to_string();
+        let timestamp = self.clock.now_ms().to_string();
         let mut all_params = params;
+        all_params.push(("recvWindow", self.recv_window_ms.to_string()));
         all_params.push(("timestamp", timestamp));
 
         let params_ref: Vec<(&str, &str)> =
@@ -137,14 +188,11 @@ impl BinanceClient {
         let query = build_signed_query(&params_ref, secret_key);
         let url = format!("{}{}?{}", BASE_URL, endpoint, query);
 
-        let response = self
-            .client
-            .post(&url)
-            .header("X-MBX-APIKEY", access_key)
-            .send()
-            .await?;
-
-        self.handle_response(response).await
+        self.send(
+            self.client.post(&url).header("X-MBX-APIKEY", access_key),
+            Priority::Order,
+        )
+        .await
     }
 
     async fn put_signed<T: DeserializeOwned>(
@@ -161,8 +209,9 @@ impl BinanceClient {
             .as_ref()
             .ok_or_else(|| BinanceError::AuthError("Secret key not set".to_string()))?;
 
-        let timestamp = get_timestamp().to_string();
+        let timestamp = self.clock.now_ms().to_string();
         let mut all_params = params;
+        all_params.push(("recvWindow", self.recv_window_ms.to_string()));
         all_params.push(("timestamp", timestamp));
 
         let params_ref: Vec<(&str, &str)> =
@@ -171,14 +220,11 @@ impl BinanceClient {
         let query = build_signed_query(&params_ref, secret_key);
         let url = format!("{}{}?{}", BASE_URL, endpoint, query);
 
-        let response = self
-            .client
-            .put(&url)
-            .header("X-MBX-APIKEY", access_key)
-            .send()
-            .await?;
-
-        self.handle_response(response).await
+        self.send(
+            self.client.put(&url).header("X-MBX-APIKEY", access_key),
+            Priority::Order,
+        )
+        .await
     }
 
     async fn delete_signed<T: DeserializeOwned>(
@@ -195,8 +241,9 @@ impl BinanceClient {
             .as_ref()
             .ok_or_else(|| BinanceError::AuthError("Secret key not set".to_string()))?;
 
-        let timestamp = get_timestamp().to_string();
+        let timestamp = self.clock.now_ms().to_string();
         let mut all_params = params;
+        all_params.push(("recvWindow", self.recv_window_ms.to_string()));
         all_params.push(("timestamp", timestamp));
 
         let params_ref: Vec<(&str, &str)> =
@@ -205,18 +252,24 @@ impl BinanceClient {
         let query = build_signed_query(&params_ref, secret_key);
         let url = format!("{}{}?{}", BASE_URL, endpoint, query);
 
-        let response = self
-            .client
-            .delete(&url)
-            .header("X-MBX-APIKEY", access_key)
-            .send()
-            .await?;
-
-        self.handle_response(response).await
+        self.send(
+            self.client.delete(&url).header("X-MBX-APIKEY", access_key),
+            Priority::Order,
+        )
+        .await
     }
 
     // === Public API endpoints ===
 
+    /// Syncs the clock used for signed requests with the exchange, returns the offset of the exchange clock.
+    pub async fn sync_clock(&self) -> Result<i64> {
+        let sent_ms = get_timestamp();
+        let server_time: ServerTime = self.get_public("/fapi/v1/time", None).await?;
+        Ok(self
+            .clock
+            .sync(server_time.server_time, sent_ms, get_timestamp()))
+    }
+
     pub async fn get_symbol(&self) -> Result<Symbol> {
         let exchange_info: ExchangeInfo = self.get_public("/fapi/v1/exchangeInfo", None).await?;
         for sym in exchange_info.symbols {
@@ -253,13 +306,42 @@ impl BinanceClient {
         self.runtime.block_on(self.get_symbol())
     }
 
+    /// Returns all symbols that are currently trading.
+    pub async fn get_symbols(&self) -> Result<Vec<String>> {
+        let exchange_info: ExchangeInfo = self.get_public("/fapi/v1/exchangeInfo", None).await?;
+        Ok(exchange_info
+            .symbols
+            .into_iter()
+            .filter(|sym| sym.status == "TRADING")
+            .map(|sym| sym.symbol)
+            .collect())
+    }
+
+    pub fn get_symbols_sync(&self) -> Result<Vec<String>> {
+        self.runtime.block_on(self.get_symbols())
+    }
+
     pub async fn get_candles(&self, interval: &str, limit: usize) -> Result<Vec<Candle>> {
+        self.get_candles_until(interval, limit, None).await
+    }
+
+    /// Returns candles opened before `end_time`, or the latest ones if it's not set.
+    pub async fn get_candles_until(
+        &self,
+        interval: &str,
+        limit: usize,
+        end_time: Option<Timestamp>,
+    ) -> Result<Vec<Candle>> {
         let limit_str = limit.to_string();
-        let params: Vec<(&str, &str)> = vec![
+        let end_time_str = end_time.map(|t| (t.milliseconds() - 1).to_string());
+        let mut params: Vec<(&str, &str)> = vec![
             ("symbol", self.symbol.as_str()),
             ("interval", interval),
             ("limit", limit_str.as_str()),
         ];
+        if let Some(end_time_str) = &end_time_str {
+            params.push(("endTime", end_time_str.as_str()));
+        }
         let data: Vec<serde_json::Value> =
             self.get_public("/fapi/v1/klines", Some(&params)).await?;
 
@@ -282,6 +364,16 @@ impl BinanceClient {
         self.runtime.block_on(self.get_candles(interval, limit))
     }
 
+    pub fn get_candles_until_sync(
+        &self,
+        interval: &str,
+        limit: usize,
+        end_time: Option<Timestamp>,
+    ) -> Result<Vec<Candle>> {
+        self.runtime
+            .block_on(self.get_candles_until(interval, limit, end_time))
+    }
+
     pub async fn get_depth(&self, limit: usize) -> Result<DepthSnapshot> {
         let limit_str = limit.to_string();
         let params: Vec<(&str, &str)> = vec![
@@ -339,51 +431,15 @@ impl BinanceClient {
             ("orderId", order_id.to_string()),
         ];
         let resp: BinanceOrder = self.get_signed("/fapi/v1/order", Some(params)).await?;
-
-        let order_type = match resp.order_type.as_str() {
-            "MARKET" => OrderType::Market,
-            "LIMIT" => OrderType::Limit,
-            "STOP_MARKET" => OrderType::Stop,
-            _ => return Err(BinanceError::ParseError("Unknown order type".to_string())),
-        };
-
-        let order_side = match resp.order_side.as_str() {
-            "BUY" => OrderSide::Buy,
-            "SELL" => OrderSide::Sell,
-            _ => return Err(BinanceError::ParseError("Unknown order side".to_string())),
-        };
-
-        let order_status = match resp.status.as_str() {
-            "NEW" => OrderStatus::Pending,
-            "PARTIALLY_FILLED" => OrderStatus::Pending,
-            _ => OrderStatus::Filled,
-        };
-
-        let price = match &order_type {
-            OrderType::Stop => resp.stop_price,
-            _ => resp.price,
-        };
-
-        Ok(Order {
-            id: resp.order_id.to_string(),
-            order_type,
-            order_side,
-            order_status,
-            quantity: resp.orig_qty,
-            executed_quantity: resp.executed_qty,
-            price,
-            average_price: resp.avg_price,
-            commission: resp.commission(),
-            timestamp: Timestamp::from_milliseconds(resp.update_time),
-            is_update: false,
-        })
+        resp.into_order()
     }
 
     pub fn get_order_sync(&self, order_id: &str) -> Result<Order> {
         self.runtime.block_on(self.get_order(order_id))
     }
 
-    pub async fn place_order(&self, order: NewOrder) -> Result<Order> {
+    /// Parameters of a new MARKET or LIMIT order, shared by REST and the WebSocket API.
+    pub fn new_order_params(&self, order: &NewOrder) -> Vec<(&'static str, String)> {
         let order_side = match order.order_side {
             OrderSide::Buy => "BUY",
             OrderSide::Sell => "SELL",
@@ -406,74 +462,118 @@ impl BinanceClient {
             params.push(("price", order.price.unwrap().to_string()));
             params.push(("timeInForce", "GTC".to_string()));
         }
+        params
+    }
 
+    pub async fn place_order(&self, order: NewOrder) -> Result<Order> {
+        let params = self.new_order_params(&order);
         let resp: BinanceOrder = self.post_signed("/fapi/v1/order", params).await?;
-
-        let order_status = match resp.status.as_str() {
-            "NEW" => OrderStatus::Pending,
-            "PARTIALLY_FILLED" => OrderStatus::Pending,
-            _ => OrderStatus::Filled,
-        };
-
-        Ok(Order {
-            id: resp.order_id.to_string(),
-            order_type: order.order_type.clone(),
-            order_side: order.order_side,
-            order_status,
-            quantity: resp.orig_qty,
-            executed_quantity: resp.executed_qty,
-            price: match &order.order_type {
-                OrderType::Stop => resp.stop_price,
-                _ => resp.price,
-            },
-            average_price: resp.avg_price,
-            commission: resp.commission(),
-            timestamp: Timestamp::from_milliseconds(resp.update_time),
-            is_update: false,
-        })
+        Ok(resp.into_placed(order))
     }
 
     pub fn place_order_sync(&self, order: NewOrder) -> Result<Order> {
         self.runtime.block_on(self.place_order(order))
     }
 
-    pub async fn cancel_order(&self, order: &Order) -> Result<Order> {
-        let params = vec![
+    pub fn cancel_order_params(&self, order: &Order) -> Vec<(&'static str, String)> {
+        vec![
             ("symbol", self.symbol.clone()),
             ("orderId", order.id.to_string()),
-        ];
+        ]
+    }
+
+    pub async fn cancel_order(&self, order: &Order) -> Result<Order> {
+        let params = self.cancel_order_params(order);
         let resp: BinanceOrder = self.delete_signed("/fapi/v1/order", params).await?;
+        resp.into_canceled()
+    }
 
-        let order_type = match resp.order_type.as_str() {
-            "MARKET" => OrderType::Market,
-            "LIMIT" => OrderType::Limit,
-            "STOP_MARKET" => panic!("Use AlgoOrder for STOP orders!"),
-            _ => return Err(BinanceError::ParseError("Unknown order type".to_string())),
-        };
+    pub fn cancel_order_sync(&self, order: &Order) -> Result<Order> {
+        self.runtime.block_on(self.cancel_order(order))
+    }
 
-        let order_side = match resp.order_side.as_str() {
-            "BUY" => OrderSide::Buy,
-            "SELL" => OrderSide::Sell,
-            _ => return Err(BinanceError::ParseError("Unknown order side".to_string())),
+    /// Cancels all open orders of the symbol, and all STOP (algo) orders when `stop_orders` is set.
+    pub async fn cancel_all_orders(&self, stop_orders: bool) -> Result<()> {
+        let params = vec![("symbol", self.symbol.clone())];
+        let orders = self.delete_signed::<Value>("/fapi/v1/allOpenOrders", params.clone());
+        if stop_orders {
+            let (orders, stop_orders) = tokio::join!(
+                orders,
+                self.delete_signed::<Value>("/fapi/v1/algoOpenOrders", params)
+            );
+            orders?;
+            stop_orders?;
+        } else {
+            orders.await?;
+        }
+        Ok(())
+    }
+
+    pub fn cancel_all_orders_sync(&self, stop_orders: bool) -> Result<()> {
+        self.runtime.block_on(self.cancel_all_orders(stop_orders))
+    }
+
+    /// Parameters moving a LIMIT order to `price`, the quantity is kept.
+    pub fn modify_order_params(
+        &self,
+        order: &Order,
+        price: Decimal,
+    ) -> Vec<(&'static str, String)> {
+        let order_side = match order.order_side {
+            OrderSide::Buy => "BUY",
+            OrderSide::Sell => "SELL",
         };
+        vec![
+            ("symbol", self.symbol.clone()),
+            ("orderId", order.id.to_string()),
+            ("side", order_side.to_string()),
+            ("quantity", order.quantity.to_string()),
+            ("price", price.to_string()),
+        ]
+    }
 
-        Ok(Order {
-            id: resp.order_id.to_string(),
-            order_type,
-            order_side,
-            order_status: OrderStatus::Filled,
-            quantity: resp.orig_qty,
-            executed_quantity: resp.executed_qty,
-            price: resp.price,
-            average_price: resp.avg_price,
-            commission: resp.commission(),
-            timestamp: Timestamp::from_milliseconds(resp.update_time),
-            is_update: false,
-        })
+    pub async fn modify_order(&self, order: &Order, price: Decimal) -> Result<Order> {
+        let params = self.modify_order_params(order, price);
+        let resp: BinanceOrder = self.put_signed("/fapi/v1/order", params).await?;
+        resp.into_order()
     }
 
-    pub fn cancel_order_sync(&self, order: &Order) -> Result<Order> {
-        self.runtime.block_on(self.cancel_order(order))
+    pub fn modify_order_sync(&self, order: &Order, price: Decimal) -> Result<Order> {
+        self.runtime.block_on(self.modify_order(order, price))
+    }
+
+    /// Adds the API key, timestamp and signature to the params of a WebSocket API request.
+    pub fn sign_ws_params(&self, params: Vec<(&str, String)>) -> Result<Value> {
+        let access_key = self
+            .access_key
+            .as_ref()
+            .ok_or_else(|| BinanceError::AuthError("API key not set".to_string()))?;
+        let secret_key = self
+            .secret_key
+            .as_ref()
+            .ok_or_else(|| BinanceError::AuthError("Secret key not set".to_string()))?;
+
+        let mut all_params = params;
+        all_params.push(("apiKey", access_key.clone()));
+        all_params.push(("recvWindow", self.recv_window_ms.to_string()));
+        all_params.push(("timestamp", self.clock.now_ms().to_string()));
+        // the WebSocket API signs the params sorted by name
+        all_params.sort_by_key(|(k, _)| *k);
+
+        let query = all_params
+            .iter()
+            .map(|(k, v)| format!("{}={}", k, v))
+            .collect::<Vec<_>>()
+            .join("&");
+        let mut signed: Map<String, Value> = all_params
+            .into_iter()
+            .map(|(k, v)| (k.to_string(), Value::String(v)))
+            .collect();
+        signed.insert(
+            "signature".to_string(),
+            Value::String(sign(secret_key, &query)),
+        );
+        Ok(Value::Object(signed))
     }
 
     pub async fn place_stop_order(&self, order: NewOrder) -> Result<Order> {
@@ -528,7 +628,7 @@ impl BinanceClient {
             id: order.id.to_string(),
             order_type: order.order_type.clone(),
             order_side: order.order_side.clone(),
-            order_status: OrderStatus::Filled,
+            order_status: OrderStatus::Canceled,
             quantity: Decimal::ZERO,
             executed_quantity: Decimal::ZERO,
             price: order.price.clone(),
@@ -570,6 +670,7 @@ pub struct ExchangeInfo {
 #[serde(rename_all = "camelCase")]
 pub struct SymbolInfo {
     pub symbol: String,
+    pub status: String,
     pub filters: Vec<Filter>,
 }
 
@@ -618,6 +719,103 @@ pub struct BinanceOrder {
 }
 
 impl BinanceOrder {
+    pub fn into_order(self) -> Result<Order> {
+        let order_type = match self.order_type.as_str() {
+            "MARKET" => OrderType::Market,
+            "LIMIT" => OrderType::Limit,
+            "STOP_MARKET" => OrderType::Stop,
+            _ => return Err(BinanceError::ParseError("Unknown order type".to_string())),
+        };
+
+        let order_side = match self.order_side.as_str() {
+            "BUY" => OrderSide::Buy,
+            "SELL" => OrderSide::Sell,
+            _ => return Err(BinanceError::ParseError("Unknown order side".to_string())),
+        };
+
+        let order_status = match self.status.as_str() {
+            "NEW" => OrderStatus::Pending,
+            "PARTIALLY_FILLED" => OrderStatus::Pending,
+            "CANCELED" | "EXPIRED" | "EXPIRED_IN_MATCH" => OrderStatus::Canceled,
+            _ => OrderStatus::Filled,
+        };
+
+        let price = match &order_type {
+            OrderType::Stop => self.stop_price,
+            _ => self.price,
+        };
+
+        Ok(Order {
+            id: self.order_id.to_string(),
+            order_type,
+            order_side,
+            order_status,
+            quantity: self.orig_qty,
+            executed_quantity: self.executed_qty,
+            price,
+            average_price: self.avg_price,
+            commission: self.commission(),
+            timestamp: Timestamp::from_milliseconds(self.update_time),
+            is_update: false,
+        })
+    }
+
+    /// The response to a new order, the type and side are taken from the request.
+    pub fn into_placed(self, order: NewOrder) -> Order {
+        let order_status = match self.status.as_str() {
+            "NEW" => OrderStatus::Pending,
+            "PARTIALLY_FILLED" => OrderStatus::Pending,
+            "CANCELED" | "EXPIRED" | "EXPIRED_IN_MATCH" => OrderStatus::Canceled,
+            _ => OrderStatus::Filled,
+        };
+
+        Order {
+            id: self.order_id.to_string(),
+            order_type: order.order_type.clone(),
+            order_side: order.order_side,
+            order_status,
+            quantity: self.orig_qty,
+            executed_quantity: self.executed_qty,
+            price: match &order.order_type {
+                OrderType::Stop => self.stop_price,
+                _ => self.price,
+            },
+            average_price: self.avg_price,
+            commission: self.commission(),
+            timestamp: Timestamp::from_milliseconds(self.update_time),
+            is_update: false,
+        }
+    }
+
+    pub fn into_canceled(self) -> Result<Order> {
+        let order_type = match self.order_type.as_str() {
+            "MARKET" => OrderType::Market,
+            "LIMIT" => OrderType::Limit,
+            "STOP_MARKET" => panic!("Use AlgoOrder for STOP orders!"),
+            _ => return Err(BinanceError::ParseError("Unknown order type".to_string())),
+        };
+
+        let order_side = match self.order_side.as_str() {
+            "BUY" => OrderSide::Buy,
+            "SELL" => OrderSide::Sell,
+            _ => return Err(BinanceError::ParseError("Unknown order side".to_string())),
+        };
+
+        Ok(Order {
+            id: self.order_id.to_string(),
+            order_type,
+            order_side,
+            order_status: OrderStatus::Canceled,
+            quantity: self.orig_qty,
+            executed_quantity: self.executed_qty,
+            price: self.price,
+            average_price: self.avg_price,
+            commission: self.commission(),
+            timestamp: Timestamp::from_milliseconds(self.update_time),
+            is_update: false,
+        })
+    }
+
     pub fn commission(&self) -> Decimal {
         let rate = match self.order_type.as_str() {
             "LIMIT" => Decimal::from_str("0.0002").unwrap(),
@@ -641,6 +839,12 @@ pub struct BinanceCancelStopOrder {
     pub algo_id: u64,
 }
 
+#[derive(Deserialize)]
+struct ServerTime {
+    #[serde(rename = "serverTime")]
+    server_time: u64,
+}
+
 #[derive(Deserialize)]
 struct ListenKey {
     #[serde(rename = "listenKey")]
@@ -1,12 +1,16 @@
 use super::client::BinanceClient;
 use super::market_stream::start_market_stream;
 use super::open_interest_stream::start_open_interest_stream;
+use super::order_api::{OrderApi, OrderRequest, send_over_rest, start_order_api};
 use super::orders_stream::start_orders_stream;
-use crate::exchanges::base::exchange::Exchange;
+use crate::exchanges::base::exchange::{Exchange, RECONNECT_DELAY, kline_interval};
+use crate::exchanges::base::keep_clock_synced;
 use crate::models::{
-    CandlesState, Interval, Log, LogLevel, NewOrder, OpenInterestState, Order, OrderBookState,
-    OrderFlowState, OrderType, SharedCandlesState, SharedState, Symbol,
+    CandlesState, Capabilities, ClockConfig, Interval, Log, LogLevel, NewOrder, NotificationEvent,
+    OpenInterestState, Order, OrderBookState, OrderFlowState, OrderStatus, OrderType,
+    RateLimitStatus, SharedCandlesState, SharedState, Sound, Symbol, Timestamp,
 };
+use rust_decimal::Decimal;
 use std::sync::{Arc, RwLock, mpsc::Sender};
 use std::thread;
 use std::time::Duration;
@@ -24,6 +28,9 @@ pub struct BinanceUSDFuturesExchange {
     client: Arc<BinanceClient>,
     stop_tx: Option<oneshot::Sender<()>>,
     handle: Option<thread::JoinHandle<()>>,
+    clock_config: ClockConfig,
+    // orders go over the WebSocket API when set
+    order_api: Option<Arc<OrderApi>>,
 }
 
 impl Exchange for BinanceUSDFuturesExchange {
@@ -31,6 +38,19 @@ impl Exchange for BinanceUSDFuturesExchange {
         self.name
     }
 
+    fn capabilities(&self) -> Capabilities {
+        Capabilities {
+            trading: true,
+            stop_orders: true,
+            trailing_stop: false,
+            modify_orders: true,
+            open_interest: true,
+            shorting: true,
+            position_fetch: false,
+            ws_orders: self.order_api.is_some(),
+        }
+    }
+
     fn start(
         &mut self,
         interval: Interval,
@@ -55,22 +75,32 @@ impl Exchange for BinanceUSDFuturesExchange {
         let orders_sender_clone = self.orders_sender.clone();
 
         let client_clone = self.client.clone();
+        let clock_config = self.clock_config.clone();
+        let order_api_clone = self.order_api.clone();
 
         self.set_interval(interval);
 
-        let keep_listen_key_alive = async |client: &BinanceClient, logs_sender: &Sender<Log>| {
-            loop {
-                sleep(Duration::from_mins(30)).await;
-                if client.has_auth() {
-                    let _ = client.refresh_listen_key().await;
-                    let _ = logs_sender.send(Log::new(
-                        LogLevel::Info,
-                        "Refreshed listen key".to_string(),
-                        None,
-                    ));
+        let keep_listen_key_alive =
+            async |client: &BinanceClient, logs_sender: &Sender<Log>, component: &str| {
+                loop {
+                    sleep(Duration::from_mins(30)).await;
+                    if client.has_auth() {
+                        let log = match client.refresh_listen_key().await {
+                            Ok(_) => Log::new(
+                                LogLevel::Recovered("AUTH".to_string()),
+                                "Refreshed listen key".to_string(),
+                                None,
+                            ),
+                            Err(e) => Log::new(
+                                LogLevel::Error("AUTH".to_string()),
+                                format!("Failed to refresh listen key: {:?}", e),
+                                None,
+                            ),
+                        };
+                        let _ = logs_sender.send(log.with_component(component));
+                    }
                 }
-            }
-        };
+            };
 
         let (shutdown_tx, shutdown_rx) = oneshot::channel::<()>();
 
@@ -82,41 +112,88 @@ impl Exchange for BinanceUSDFuturesExchange {
                 .expect("Failed to build tokio runtime for streams");
 
             rt.block_on(async move {
+                let market_component = format!("{} market stream", symbol_clone);
+                let open_interest_component = format!("{} open interest", symbol_clone);
+                let user_data_component = format!("{} user data stream", symbol_clone);
+                let order_api_component = format!("{} order API", symbol_clone);
+                let connected = |message: &str, component: &str| {
+                    logs_sender_clone.send(Log::new(LogLevel::Recovered("CONN".to_string()), message.to_string(), None).with_component(component)).ok();
+                };
+                let disconnected = |e: Box<dyn std::error::Error>, component: &str| {
+                    logs_sender_clone.send(Log::new(LogLevel::Error("CONN".to_string()), format!("{:?}", e), Some(Sound::Disconnected)).with_event(NotificationEvent::ConnectionLost).with_component(component)).ok();
+                };
+
                 tokio::select! {
-                    res = start_market_stream(
-                        &client_clone,
-                        &symbol_clone,
-                        500,
-                        candles_clone,
-                        order_book_clone,
-                        order_flow_clone,
-                    ) => {
-                        if let Err(e) = res {
-                            logs_sender_clone.send(Log::new(LogLevel::Error("CONN".to_string()), format!("{:?}", e), None)).ok();
+                    _ = async {
+                        loop {
+                            let res = start_market_stream(
+                                &client_clone,
+                                &symbol_clone,
+                                500,
+                                candles_clone.clone(),
+                                order_book_clone.clone(),
+                                order_flow_clone.clone(),
+                                || connected("Market stream connected", &market_component),
+                            ).await;
+                            if let Err(e) = res {
+                                disconnected(e, &market_component);
+                            }
+                            sleep(RECONNECT_DELAY).await;
                         }
-                    }
+                    } => {}
 
-                    res = start_open_interest_stream(
-                        &client_clone,
-                        open_interest_clone,
-                    ) => {
-                        if let Err(e) = res {
-                            logs_sender_clone.send(Log::new(LogLevel::Error("CONN".to_string()), format!("{:?}", e), None)).ok();
+                    _ = async {
+                        loop {
+                            let res = start_open_interest_stream(
+                                &client_clone,
+                                open_interest_clone.clone(),
+                                || connected("Open interest loaded", &open_interest_component),
+                            ).await;
+                            if let Err(e) = res {
+                                disconnected(e, &open_interest_component);
+                            }
+                            sleep(RECONNECT_DELAY).await;
                         }
-                    }
+                    } => {}
 
-                    res = start_orders_stream(
-                        &client_clone,
-                        &symbol_clone,
-                        &logs_sender_clone,
-                        orders_sender_clone,
-                    ) => {
-                        if let Err(e) = res {
-                            logs_sender_clone.send(Log::new(LogLevel::Error("CONN".to_string()), format!("{:?}", e), None)).ok();
+                    _ = async {
+                        loop {
+                            let res = start_orders_stream(
+                                &client_clone,
+                                &symbol_clone,
+                                &logs_sender_clone,
+                                &user_data_component,
+                                orders_sender_clone.clone(),
+                            ).await;
+                            if let Err(e) = res {
+                                disconnected(e, &user_data_component);
+                            }
+                            sleep(RECONNECT_DELAY).await;
                         }
-                    }
+                    } => {}
+
+                    _ = async {
+                        let Some(order_api) = &order_api_clone else {
+                            return std::future::pending().await;
+                        };
+                        loop {
+                            let res = start_order_api(
+                                &client_clone,
+                                order_api,
+                                &orders_sender_clone,
+                                &logs_sender_clone,
+                                || connected("Order API connected", &order_api_component),
+                            ).await;
+                            if let Err(e) = res {
+                                disconnected(e, &order_api_component);
+                            }
+                            sleep(RECONNECT_DELAY).await;
+                        }
+                    } => {}
 
-                    _ = keep_listen_key_alive(&client_clone, &logs_sender_clone) => {}
+                    _ = keep_listen_key_alive(&client_clone, &logs_sender_clone, &user_data_component) => {}
+
+                    _ = keep_clock_synced(async || client_clone.sync_clock().await, &logs_sender_clone, &clock_config) => {}
 
                     _ = shutdown_rx => {
                         logs_sender_clone.send(Log::new(LogLevel::Info, "Shutting down market stream listener".to_string(), None)).ok();
@@ -141,7 +218,7 @@ impl Exchange for BinanceUSDFuturesExchange {
         ))
     }
 
-    fn stop(&mut self) -> () {
+    fn stop(&mut self) {
         if let Some(stop_tx) = self.stop_tx.take() {
             let _ = stop_tx.send(());
         }
@@ -152,17 +229,14 @@ impl Exchange for BinanceUSDFuturesExchange {
         self.handle = None;
     }
 
-    fn set_interval(&self, interval: Interval) -> () {
-        let interval_str = match interval {
-            Interval::M1 => "1m",
-            Interval::M5 => "5m",
-            Interval::M15 => "15m",
-            Interval::H1 => "1h",
-        };
+    fn symbols(&self) -> Result<Vec<String>, Box<dyn std::error::Error>> {
+        Ok(self.client.get_symbols_sync()?)
+    }
 
+    fn set_interval(&self, interval: Interval) {
         let candles = self
             .client
-            .get_candles_sync(&interval_str, self.candles_limit)
+            .get_candles_sync(kline_interval(interval), self.candles_limit)
             .unwrap();
 
         if let Some(shared_candles_state) = self.shared_candles_state.as_ref() {
@@ -174,58 +248,144 @@ impl Exchange for BinanceUSDFuturesExchange {
         }
     }
 
-    fn place_order(&self, new_order: NewOrder) -> () {
+    fn load_candles(&self, interval: Interval, until: Timestamp) {
+        let Some(shared_candles_state) = self.shared_candles_state.clone() else {
+            return;
+        };
         let client = self.client.clone();
-        let sender_clone = self.orders_sender.clone();
+        let limit = self.candles_limit;
         let logs_sender_clone = self.logs_sender.clone();
         thread::spawn(move || {
-            let order = match new_order.order_type {
-                OrderType::Stop => client.place_stop_order_sync(new_order),
-                _ => client.place_order_sync(new_order),
-            };
-            match order {
-                Ok(order) => sender_clone.send(order).unwrap(),
+            match client.get_candles_until_sync(kline_interval(interval), limit, Some(until)) {
+                Ok(candles) => {
+                    let mut buffer = shared_candles_state.write().unwrap();
+                    // the interval could be changed while loading
+                    if buffer.interval == interval {
+                        buffer.prepend(candles);
+                    }
+                }
                 Err(e) => {
-                    logs_sender_clone
-                        .send(Log::new(
-                            LogLevel::Warning("WARN".to_string(), None),
-                            format!("Failed to create order: {:?}", e),
-                            None,
-                        ))
-                        .unwrap();
+                    let _ = logs_sender_clone.send(Log::new(
+                        LogLevel::Warning("WARN".to_string(), None),
+                        format!("Failed to load candles: {:?}", e),
+                        None,
+                    ));
                 }
-            };
+            }
         });
     }
 
-    fn cancel_order(&self, order: Order) -> () {
+    fn snapshot(
+        &self,
+        interval: Interval,
+        at: Timestamp,
+    ) -> Result<(Symbol, SharedState), Box<dyn std::error::Error>> {
+        let symbol = self.client.get_symbol_sync()?;
+        let candles = self.client.get_candles_until_sync(
+            kline_interval(interval),
+            self.candles_limit,
+            Some(Timestamp::from_milliseconds(at.milliseconds() + 1)),
+        )?;
+
+        let shared_state = SharedState::new(self.candles_limit, interval);
+        {
+            let mut buffer = shared_state.candles.write().unwrap();
+            for candle in candles {
+                buffer.push(candle);
+            }
+        }
+
+        Ok((symbol, shared_state))
+    }
+
+    fn place_order(&self, new_order: NewOrder) {
+        self.send_order_request(OrderRequest::Place(new_order));
+    }
+
+    fn cancel_order(&self, order: Order) {
+        self.send_order_request(OrderRequest::Cancel(order));
+    }
+
+    fn cancel_all_orders(&self, orders: Vec<Order>) {
         let client = self.client.clone();
         let orders_sender_clone = self.orders_sender.clone();
         let logs_sender_clone = self.logs_sender.clone();
-        thread::spawn(move || {
-            let result = match order.order_type {
-                OrderType::Stop => client.cancel_stop_order_sync(&order),
-                _ => client.cancel_order_sync(&order),
-            };
-            match result {
-                Ok(order) => {
-                    orders_sender_clone.send(order).unwrap();
-                }
-                Err(e) => {
-                    logs_sender_clone
-                        .send(Log::new(
-                            LogLevel::Warning("WARN".to_string(), None),
-                            format!("Failed to cancel order {}: {:?}", order.id, e),
-                            None,
-                        ))
-                        .unwrap();
-                    if let Ok(order) = client.get_order_sync(&order.id) {
-                        orders_sender_clone.send(order).unwrap();
+        let stop_orders = orders.iter().any(|o| o.order_type == OrderType::Stop);
+        thread::spawn(move || match client.cancel_all_orders_sync(stop_orders) {
+            Ok(()) => {
+                // stop orders are algo orders, their updates come from the user data stream
+                let mut cancelled = 0;
+                for order in orders.iter().filter(|o| o.order_type != OrderType::Stop) {
+                    match client.get_order_sync(&order.id) {
+                        Ok(order) => {
+                            if order.order_status == OrderStatus::Canceled {
+                                cancelled += 1;
+                            }
+                            orders_sender_clone.send(order).ok();
+                        }
+                        Err(e) => {
+                            logs_sender_clone
+                                .send(
+                                    Log::new(
+                                        LogLevel::Warning("CANCEL".to_string(), None),
+                                        format!("Failed to get the cancelled order: {:?}", e),
+                                        None,
+                                    )
+                                    .with_order_id(&order.id),
+                                )
+                                .ok();
+                        }
                     }
                 }
+                logs_sender_clone
+                    .send(Log::new(
+                        LogLevel::Info,
+                        format!("Cancelled {} open orders", cancelled),
+                        None,
+                    ))
+                    .ok();
+            }
+            Err(e) => {
+                logs_sender_clone
+                    .send(Log::new(
+                        LogLevel::Warning("CANCEL".to_string(), None),
+                        format!(
+                            "Failed to cancel all orders, cancelling one by one: {:?}",
+                            e
+                        ),
+                        None,
+                    ))
+                    .ok();
+                for order in orders {
+                    send_over_rest(
+                        client.clone(),
+                        OrderRequest::Cancel(order),
+                        orders_sender_clone.clone(),
+                        logs_sender_clone.clone(),
+                    );
+                }
             }
         });
     }
+
+    fn modify_order(&self, order: Order, price: Decimal) {
+        if order.order_type != OrderType::Limit {
+            let _ = self.logs_sender.send(
+                Log::new(
+                    LogLevel::Warning("WARN".to_string(), None),
+                    format!("Only limit orders can be moved, order {}", order.id),
+                    None,
+                )
+                .with_order_id(&order.id),
+            );
+            return;
+        }
+        self.send_order_request(OrderRequest::Modify(order, price));
+    }
+
+    fn rate_limit(&self) -> RateLimitStatus {
+        self.client.rate_limit()
+    }
 }
 
 impl BinanceUSDFuturesExchange {
@@ -236,11 +396,13 @@ impl BinanceUSDFuturesExchange {
         logs_sender: Sender<Log>,
         access_key: Option<String>,
         secret_key: Option<String>,
+        clock_config: ClockConfig,
     ) -> Self {
         let client = Arc::new(BinanceClient::new(
             symbol.clone(),
             access_key.clone(),
             secret_key.clone(),
+            clock_config.recv_window_ms,
         ));
 
         Self {
@@ -253,6 +415,30 @@ impl BinanceUSDFuturesExchange {
             client,
             stop_tx: None,
             handle: None,
+            clock_config,
+            order_api: None,
         }
     }
+
+    /// Places, cancels and moves orders over the WebSocket API, REST is used while it is disconnected.
+    pub fn with_ws_orders(mut self, enabled: bool) -> Self {
+        self.order_api = enabled.then(|| Arc::new(OrderApi::new()));
+        self
+    }
+
+    fn send_order_request(&self, request: OrderRequest) {
+        let request = match &self.order_api {
+            Some(order_api) if !request.is_stop() => match order_api.send(request) {
+                Some(request) => request,
+                None => return,
+            },
+            _ => request,
+        };
+        send_over_rest(
+            self.client.clone(),
+            request,
+            self.orders_sender.clone(),
+            self.logs_sender.clone(),
+        );
+    }
 }
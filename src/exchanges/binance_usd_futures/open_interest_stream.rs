@@ -5,6 +5,8 @@ use tokio::time::{Duration, sleep};
 pub async fn start_open_interest_stream(
     client: &BinanceClient,
     open_interest_state: SharedOpenInterestState,
+    // called once the history is loaded, after a reconnect it reports the recovery
+    on_connected: impl Fn(),
 ) -> Result<(), Box<dyn std::error::Error>> {
     let hist = client.get_open_interest_hist().await?;
 
@@ -14,6 +16,7 @@ pub async fn start_open_interest_stream(
             state.push(ts, *oi);
         }
     }
+    on_connected();
 
     // Then poll every 5 seconds for current open interest
     loop {
@@ -0,0 +1,347 @@
+use super::client::{BinanceClient, BinanceOrder};
+use crate::exchanges::binance_base::errors::BinanceError;
+use crate::models::{Log, LogLevel, NewOrder, Order, OrderType, Sound, Timestamp};
+use futures_util::{SinkExt, StreamExt};
+use rust_decimal::Decimal;
+use serde::Deserialize;
+use serde_json::{Value, json};
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::mpsc::Sender;
+use std::sync::{Arc, LazyLock, Mutex};
+use std::thread;
+use std::time::Duration;
+use tokio::sync::mpsc::{UnboundedSender, unbounded_channel};
+use tokio::time::sleep;
+use tokio_tungstenite::{connect_async, tungstenite::protocol::Message};
+
+const WS_API_URL: &str = "wss://ws-fapi.binance.com/ws-fapi/v1";
+
+// request ids are also the client order ids: unique across the charts of the process,
+// and the start time keeps them unique across restarts
+static ID_PREFIX: LazyLock<String> =
+    LazyLock::new(|| format!("scalper-{}", Timestamp::now().milliseconds()));
+static NEXT_ID: AtomicU64 = AtomicU64::new(1);
+
+#[derive(Debug, Clone)]
+pub enum OrderRequest {
+    Place(NewOrder),
+    Cancel(Order),
+    // moves a LIMIT order to the price
+    Modify(Order, Decimal),
+}
+
+impl OrderRequest {
+    fn method(&self) -> &'static str {
+        match self {
+            OrderRequest::Place(_) => "order.place",
+            OrderRequest::Cancel(_) => "order.cancel",
+            OrderRequest::Modify(_, _) => "order.modify",
+        }
+    }
+
+    /// STOP orders are algo orders, they are only available over REST.
+    pub fn is_stop(&self) -> bool {
+        match self {
+            OrderRequest::Place(new_order) => new_order.order_type == OrderType::Stop,
+            OrderRequest::Cancel(order) | OrderRequest::Modify(order, _) => {
+                order.order_type == OrderType::Stop
+            }
+        }
+    }
+}
+
+/// Sends order requests on a persistent authenticated WebSocket API connection kept open by `start_order_api`.
+pub struct OrderApi {
+    // set while connected
+    requests: Mutex<Option<UnboundedSender<(String, OrderRequest)>>>,
+}
+
+impl OrderApi {
+    pub fn new() -> Self {
+        Self {
+            requests: Mutex::new(None),
+        }
+    }
+
+    /// Queues the request without waiting for the response.
+    /// Returns the request back while disconnected, so it can be sent over REST.
+    pub fn send(&self, request: OrderRequest) -> Option<OrderRequest> {
+        let requests = self.requests.lock().unwrap();
+        let Some(sender) = requests.as_ref() else {
+            return Some(request);
+        };
+        let id = format!("{}-{}", *ID_PREFIX, NEXT_ID.fetch_add(1, Ordering::Relaxed));
+        sender.send((id, request)).err().map(|e| e.0.1)
+    }
+}
+
+#[derive(Deserialize)]
+struct Response {
+    id: String,
+    result: Option<Value>,
+    error: Option<ApiError>,
+}
+
+#[derive(Deserialize)]
+struct ApiError {
+    code: i32,
+    msg: String,
+}
+
+/// Keeps the WebSocket API connection until it is lost. Responses are matched with the requests by id
+/// and reported like the REST responses. Unsent requests go over REST after a disconnect.
+pub async fn start_order_api(
+    client: &Arc<BinanceClient>,
+    order_api: &OrderApi,
+    orders_sender: &Sender<Order>,
+    logs_sender: &Sender<Log>,
+    on_connected: impl Fn(),
+) -> Result<(), Box<dyn std::error::Error>> {
+    if !client.has_auth() {
+        loop {
+            sleep(Duration::from_secs(5)).await;
+        }
+    }
+
+    let (ws_stream, _) = connect_async(WS_API_URL).await?;
+    let (mut write, mut read) = ws_stream.split();
+    let (sender, mut receiver) = unbounded_channel::<(String, OrderRequest)>();
+    *order_api.requests.lock().unwrap() = Some(sender);
+    on_connected();
+
+    let mut pending: HashMap<String, OrderRequest> = HashMap::new();
+    let result: Result<(), Box<dyn std::error::Error>> = loop {
+        tokio::select! {
+            Some((id, request)) = receiver.recv() => {
+                let params = match client.sign_ws_params(request_params(client, &id, &request)) {
+                    Ok(params) => params,
+                    Err(e) => {
+                        report(client, request, Err(e), orders_sender, logs_sender);
+                        continue;
+                    }
+                };
+                let message = json!({"id": id, "method": request.method(), "params": params});
+                pending.insert(id, request);
+                if let Err(e) = write.send(Message::Text(message.to_string().into())).await {
+                    break Err(e.into());
+                }
+            }
+            msg = read.next() => match msg {
+                Some(Ok(Message::Text(text))) => {
+                    let Ok(response) = serde_json::from_str::<Response>(&text) else {
+                        continue;
+                    };
+                    if let Some(request) = pending.remove(&response.id) {
+                        let result = parse_response(&request, response);
+                        report(client, request, result, orders_sender, logs_sender);
+                    }
+                }
+                Some(Ok(Message::Close(frame))) => break Err(format!("WebSocket API closed: {:?}", frame).into()),
+                Some(Err(e)) => break Err(e.into()),
+                None => break Err("WebSocket API closed".into()),
+                _ => {}
+            }
+        }
+    };
+
+    *order_api.requests.lock().unwrap() = None;
+    while let Ok((_, request)) = receiver.try_recv() {
+        send_over_rest(
+            client.clone(),
+            request,
+            orders_sender.clone(),
+            logs_sender.clone(),
+        );
+    }
+    for (id, request) in pending {
+        match request {
+            // cancelling twice is harmless
+            OrderRequest::Cancel(_) => send_over_rest(
+                client.clone(),
+                request,
+                orders_sender.clone(),
+                logs_sender.clone(),
+            ),
+            _ => {
+                logs_sender
+                    .send(Log::new(
+                        LogLevel::Warning("ORDER".to_string(), None),
+                        format!(
+                            "No response to {} {} before the WebSocket API disconnected, the order state comes from the user data stream",
+                            request.method(),
+                            id
+                        ),
+                        None,
+                    ))
+                    .ok();
+            }
+        }
+    }
+    result
+}
+
+fn request_params(
+    client: &BinanceClient,
+    id: &str,
+    request: &OrderRequest,
+) -> Vec<(&'static str, String)> {
+    match request {
+        OrderRequest::Place(new_order) => {
+            let mut params = client.new_order_params(new_order);
+            params.push(("newClientOrderId", id.to_string()));
+            params
+        }
+        OrderRequest::Cancel(order) => client.cancel_order_params(order),
+        OrderRequest::Modify(order, price) => client.modify_order_params(order, *price),
+    }
+}
+
+fn parse_response(request: &OrderRequest, response: Response) -> Result<Order, BinanceError> {
+    if let Some(error) = response.error {
+        return Err(BinanceError::ApiError {
+            code: error.code,
+            msg: error.msg,
+        });
+    }
+    let result = response
+        .result
+        .ok_or_else(|| BinanceError::ParseError("Empty WebSocket API response".to_string()))?;
+    let order: BinanceOrder = serde_json::from_value(result)?;
+    match request {
+        OrderRequest::Place(new_order) => Ok(order.into_placed(new_order.clone())),
+        OrderRequest::Cancel(_) => order.into_canceled(),
+        OrderRequest::Modify(_, _) => order.into_order(),
+    }
+}
+
+/// Sends the request over REST in a new thread.
+pub fn send_over_rest(
+    client: Arc<BinanceClient>,
+    request: OrderRequest,
+    orders_sender: Sender<Order>,
+    logs_sender: Sender<Log>,
+) {
+    thread::spawn(move || {
+        let result = match &request {
+            OrderRequest::Place(new_order) => match new_order.order_type {
+                OrderType::Stop => client.place_stop_order_sync(new_order.clone()),
+                _ => client.place_order_sync(new_order.clone()),
+            },
+            OrderRequest::Cancel(order) => match order.order_type {
+                OrderType::Stop => client.cancel_stop_order_sync(order),
+                _ => client.cancel_order_sync(order),
+            },
+            OrderRequest::Modify(order, price) => client.modify_order_sync(order, *price),
+        };
+        report(&client, request, result, &orders_sender, &logs_sender);
+    });
+}
+
+/// Sends the new order state, or logs the failure and reloads the order that failed to change.
+/// A failed new order is sent as rejected.
+fn report(
+    client: &Arc<BinanceClient>,
+    request: OrderRequest,
+    result: Result<Order, BinanceError>,
+    orders_sender: &Sender<Order>,
+    logs_sender: &Sender<Log>,
+) {
+    let e = match result {
+        Ok(order) => {
+            orders_sender.send(order).ok();
+            return;
+        }
+        Err(e) => e,
+    };
+
+    let (log, order) = match request {
+        OrderRequest::Place(new_order) => {
+            orders_sender.send(Order::rejected(new_order)).ok();
+            (
+                Log::new(
+                    LogLevel::Warning("WARN".to_string(), None),
+                    format!("Failed to create order: {:?}", e),
+                    Some(Sound::OrderRejected),
+                ),
+                None,
+            )
+        }
+        OrderRequest::Cancel(order) => (
+            Log::new(
+                LogLevel::Warning("WARN".to_string(), None),
+                format!("Failed to cancel order {}: {:?}", order.id, e),
+                None,
+            )
+            .with_order_id(&order.id),
+            Some(order),
+        ),
+        OrderRequest::Modify(order, price) => (
+            Log::new(
+                LogLevel::Warning("WARN".to_string(), None),
+                format!("Failed to move order {} to {}: {:?}", order.id, price, e),
+                Some(Sound::OrderRejected),
+            )
+            .with_order_id(&order.id),
+            Some(order),
+        ),
+    };
+    logs_sender.send(log).ok();
+
+    if let Some(order) = order {
+        let client = client.clone();
+        let orders_sender = orders_sender.clone();
+        thread::spawn(move || {
+            if let Ok(order) = client.get_order_sync(&order.id) {
+                orders_sender.send(order).ok();
+            }
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{OrderApi, OrderRequest, Response, parse_response};
+    use crate::models::{NewOrder, OrderSide, OrderStatus, OrderType};
+    use rust_decimal::Decimal;
+
+    fn new_order() -> NewOrder {
+        NewOrder {
+            order_type: OrderType::Limit,
+            order_side: OrderSide::Buy,
+            quantity: Decimal::ONE,
+            price: Some(Decimal::from(100)),
+        }
+    }
+
+    #[test]
+    fn test_send_while_disconnected() {
+        let order_api = OrderApi::new();
+        // returned to be sent over REST
+        assert!(matches!(
+            order_api.send(OrderRequest::Place(new_order())),
+            Some(OrderRequest::Place(_))
+        ));
+    }
+
+    #[test]
+    fn test_parse_response() {
+        let request = OrderRequest::Place(new_order());
+        let response: Response = serde_json::from_str(
+            r#"{"id":"scalper-1","status":200,"result":{"orderId":42,"price":"100","stopPrice":"0",
+            "origQty":"1","executedQty":"0","status":"NEW","type":"LIMIT","side":"BUY","avgPrice":"0",
+            "updateTime":1700000000000}}"#,
+        )
+        .unwrap();
+        let order = parse_response(&request, response).unwrap();
+        assert_eq!(order.id, "42");
+        assert_eq!(order.order_status, OrderStatus::Pending);
+        assert_eq!(order.price, Decimal::from(100));
+
+        let response: Response = serde_json::from_str(
+            r#"{"id":"scalper-2","status":400,"error":{"code":-2019,"msg":"Margin is insufficient."}}"#,
+        )
+        .unwrap();
+        assert!(parse_response(&request, response).is_err());
+    }
+}
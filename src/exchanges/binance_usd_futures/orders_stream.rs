@@ -14,6 +14,7 @@ pub async fn start_orders_stream(
     client: &BinanceClient,
     symbol: &String,
     logs_sender: &Sender<Log>,
+    component: &str,
     orders_sender: Sender<Order>,
 ) -> Result<(), Box<dyn std::error::Error>> {
     if !client.has_auth() {
@@ -22,12 +23,33 @@ pub async fn start_orders_stream(
         }
     }
 
+    let mut auth_failed = false;
     loop {
         match client.create_listen_key().await {
             Ok(listen_key) => {
+                if auth_failed {
+                    auth_failed = false;
+                    logs_sender.send(
+                        Log::new(
+                            LogLevel::Recovered("AUTH".to_string()),
+                            "Created listen key".to_string(),
+                            None,
+                        )
+                        .with_component(component),
+                    )?;
+                }
+
                 let ws_url = format!("wss://fstream.binance.com/ws/{}", listen_key);
                 let (ws_stream, _) = connect_async(ws_url).await?;
                 let (_write, mut read) = ws_stream.split();
+                logs_sender.send(
+                    Log::new(
+                        LogLevel::Recovered("CONN".to_string()),
+                        "User data stream connected".to_string(),
+                        None,
+                    )
+                    .with_component(component),
+                )?;
 
                 while let Some(msg) = read.next().await {
                     match msg {
@@ -69,15 +91,17 @@ pub async fn start_orders_stream(
                 }
             }
             Err(er) => {
-                logs_sender.send(Log::new(
-                    LogLevel::Error("AUTH".to_string()),
-                    format!("{:?}", er),
-                    None,
-                ))?;
-
-                loop {
-                    sleep(Duration::from_mins(5)).await;
-                }
+                auth_failed = true;
+                logs_sender.send(
+                    Log::new(
+                        LogLevel::Error("AUTH".to_string()),
+                        format!("{:?}", er),
+                        None,
+                    )
+                    .with_component(component),
+                )?;
+
+                sleep(Duration::from_mins(5)).await;
             }
         }
     }
@@ -109,11 +133,16 @@ fn process_filled_order(er: &ExecutionReport, orders_sender: &Sender<Order>) {
     let order_status = match &er.current_order_status {
         Some(s) if s.eq("NEW") => OrderStatus::Pending,
         Some(s) if s.eq("PARTIALLY_FILLED") => OrderStatus::Pending,
+        Some(s) if s.eq("CANCELED") || s.eq("EXPIRED") || s.eq("EXPIRED_IN_MATCH") => {
+            OrderStatus::Canceled
+        }
         _ => OrderStatus::Filled,
     };
 
     let price = match order_status {
-        OrderStatus::Filled => er.avg_price.unwrap(),
+        OrderStatus::Filled | OrderStatus::Canceled | OrderStatus::Rejected => {
+            er.avg_price.unwrap()
+        }
         OrderStatus::Pending => match order_type {
             OrderType::Stop => er.stop_price.unwrap(),
             OrderType::Limit => er.price.unwrap(),
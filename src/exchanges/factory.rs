@@ -20,14 +20,18 @@ impl ExchangeFactory {
         orders_sender: Sender<Order>,
     ) -> Result<Box<dyn Exchange>, ExchangeError> {
         match name {
-            "binance_usd_futures" => Ok(Box::new(BinanceUSDFuturesExchange::new(
-                symbol,
-                candles_limit,
-                orders_sender,
-                logs_sender,
-                config.binance_access_key.clone(),
-                config.binance_secret_key.clone(),
-            ))),
+            "binance_usd_futures" => Ok(Box::new(
+                BinanceUSDFuturesExchange::new(
+                    symbol,
+                    candles_limit,
+                    orders_sender,
+                    logs_sender,
+                    config.binance_access_key.clone(),
+                    config.binance_secret_key.clone(),
+                    config.clock.clone(),
+                )
+                .with_ws_orders(config.binance_ws_orders),
+            )),
             "binance_spot" => Ok(Box::new(BinanceSpotExchange::new(
                 symbol,
                 candles_limit,
@@ -51,6 +55,7 @@ impl ExchangeFactory {
                 logs_sender,
                 config.gateio_access_key.clone(),
                 config.gateio_secret_key.clone(),
+                config.clock.clone(),
             ))),
             _ => Err(ExchangeError::UnknownExchange(name.to_string())),
         }
@@ -1,17 +1,31 @@
-use crate::exchanges::base::USER_AGENT;
-use crate::models::{Candle, Symbol, Timestamp};
+use crate::exchanges::base::{
+    Limit, Priority, RateLimiter, ServerClock, USER_AGENT, Usage, local_ms,
+};
+use crate::models::{Candle, RateLimitStatus, Symbol, Timestamp};
 use hmac::{Hmac, Mac};
-use reqwest::{Client, Response};
+use reqwest::{Client, RequestBuilder, Response};
 use rust_decimal::Decimal;
 use serde::Deserialize;
 use serde::de::DeserializeOwned;
 use sha2::Sha512;
 use std::str::FromStr;
-use std::time::{SystemTime, UNIX_EPOCH};
+use std::sync::OnceLock;
+use std::time::Duration;
 use tokio::runtime::Runtime;
 
 const BASE_URL: &str = "https://api.gateio.ws/api/v4";
 
+// https://www.gate.io/docs/developers/apiv4/#frequency-limit-rule
+const LIMITS: [Limit; 1] = [Limit {
+    usage: Usage::Remaining {
+        remaining: "x-gate-ratelimit-requests-remain",
+        reset_timestamp: "x-gate-ratelimit-reset-timestamp",
+    },
+    limit: 200,
+    window: Duration::from_secs(10),
+    orders_only: false,
+}];
+
 type HmacSha512 = Hmac<Sha512>;
 
 pub struct GateioClient {
@@ -21,6 +35,10 @@ pub struct GateioClient {
     access_key: Option<String>,
     secret_key: Option<String>,
     runtime: Runtime,
+    clock: ServerClock,
+    rate_limiter: RateLimiter,
+    // contracts to the base quantity, known after `get_symbol`
+    quanto_multiplier: OnceLock<Decimal>,
 }
 
 #[derive(Debug)]
@@ -29,6 +47,7 @@ pub enum GateioError {
     ParseError(String),
     ApiError { label: String, message: String },
     AuthError(String),
+    RateLimitError(String),
 }
 
 impl From<reqwest::Error> for GateioError {
@@ -46,6 +65,7 @@ impl std::fmt::Display for GateioError {
                 write!(f, "API error {}: {}", label, message)
             }
             GateioError::AuthError(e) => write!(f, "Auth error: {}", e),
+            GateioError::RateLimitError(e) => write!(f, "Rate limit: {}", e),
         }
     }
 }
@@ -63,6 +83,9 @@ impl GateioClient {
             access_key,
             secret_key,
             runtime: Runtime::new().expect("Failed to create GateioClient Tokio runtime"),
+            clock: ServerClock::new(),
+            rate_limiter: RateLimiter::new(&LIMITS),
+            quanto_multiplier: OnceLock::new(),
         }
     }
 
@@ -70,13 +93,6 @@ impl GateioClient {
         self.access_key.is_some() && self.secret_key.is_some()
     }
 
-    fn get_timestamp() -> u64 {
-        SystemTime::now()
-            .duration_since(UNIX_EPOCH)
-            .unwrap()
-            .as_secs()
-    }
-
     fn sign_request(
         method: &str,
         path: &str,
@@ -120,6 +136,24 @@ impl GateioClient {
         }
     }
 
+    async fn send<T: DeserializeOwned>(
+        &self,
+        request: RequestBuilder,
+        priority: Priority,
+    ) -> Result<T> {
+        self.rate_limiter.acquire(priority).await.map_err(|wait| {
+            GateioError::RateLimitError(format!("Requests are paused for {}s", wait.as_secs() + 1))
+        })?;
+        let response = request.send().await?;
+        self.rate_limiter
+            .update(response.status(), response.headers());
+        self.handle_response(response).await
+    }
+
+    pub fn rate_limit(&self) -> RateLimitStatus {
+        self.rate_limiter.status()
+    }
+
     async fn get_public<T: DeserializeOwned>(
         &self,
         endpoint: &str,
@@ -136,13 +170,11 @@ impl GateioClient {
             url.push_str(&format!("?{}", query));
         }
 
-        let response = self
-            .client
-            .get(&url)
-            .header("Gate-Size-Decimal", "1")
-            .send()
-            .await?;
-        self.handle_response(response).await
+        self.send(
+            self.client.get(&url).header("Gate-Size-Decimal", "1"),
+            Priority::MarketData,
+        )
+        .await
     }
 
     async fn get_signed<T: DeserializeOwned>(
@@ -169,7 +201,7 @@ impl GateioClient {
             String::new()
         };
 
-        let timestamp = Self::get_timestamp();
+        let timestamp = self.clock.now_ms() / 1000;
         let signature = Self::sign_request("GET", endpoint, &query, "", timestamp, secret_key);
 
         let mut url = format!("{}{}", BASE_URL, endpoint);
@@ -177,17 +209,14 @@ impl GateioClient {
             url.push_str(&format!("?{}", query));
         }
 
-        let response = self
+        let request = self
             .client
             .get(&url)
             .header("KEY", access_key)
             .header("Timestamp", timestamp.to_string())
             .header("SIGN", signature)
-            .header("X-Gate-Size-Decimal", "1")
-            .send()
-            .await?;
-
-        self.handle_response(response).await
+            .header("X-Gate-Size-Decimal", "1");
+        self.send(request, Priority::Order).await
     }
 
     async fn post_signed<T: DeserializeOwned>(&self, endpoint: &str, body: String) -> Result<T> {
@@ -200,7 +229,7 @@ impl GateioClient {
             .as_ref()
             .ok_or_else(|| GateioError::AuthError("Secret key not set".to_string()))?;
 
-        let timestamp = Self::get_timestamp();
+        let timestamp = self.clock.now_ms() / 1000;
         let body_hash = {
             use sha2::{Digest, Sha512};
             let mut hasher = Sha512::new();
@@ -212,7 +241,7 @@ impl GateioClient {
 
         let url = format!("{}{}", BASE_URL, endpoint);
 
-        let response = self
+        let request = self
             .client
             .post(&url)
             .header("KEY", access_key)
@@ -220,11 +249,8 @@ impl GateioClient {
             .header("SIGN", signature)
             .header("Content-Type", "application/json")
             .header("X-Gate-Size-Decimal", "1")
-            .body(body)
-            .send()
-            .await?;
-
-        self.handle_response(response).await
+            .body(body);
+        self.send(request, Priority::Order).await
     }
 
     async fn delete_signed<T: DeserializeOwned>(
@@ -247,22 +273,19 @@ impl GateioClient {
             .collect::<Vec<_>>()
             .join("&");
 
-        let timestamp = Self::get_timestamp();
+        let timestamp = self.clock.now_ms() / 1000;
         let signature = Self::sign_request("DELETE", endpoint, &query, "", timestamp, secret_key);
 
         let url = format!("{}{}?{}", BASE_URL, endpoint, query);
 
-        let response = self
+        let request = self
             .client
             .delete(&url)
             .header("KEY", access_key)
             .header("Timestamp", timestamp.to_string())
             .header("SIGN", signature)
-            .header("X-Gate-Size-Decimal", "1")
-            .send()
-            .await?;
-
-        self.handle_response(response).await
+            .header("X-Gate-Size-Decimal", "1");
+        self.send(request, Priority::Order).await
     }
 
     // === Public API endpoints ===
@@ -275,6 +298,7 @@ impl GateioClient {
             Decimal::from_str(&contract_info.order_price_round).unwrap_or(Decimal::new(1, 1));
         let step_size = Decimal::from_str(&contract_info.quanto_multiplier).unwrap_or(Decimal::ONE);
         let min_notional = Decimal::from(contract_info.order_size_min);
+        self.quanto_multiplier.set(step_size).ok();
 
         Ok(Symbol {
             slug: self.contract.clone(),
@@ -288,8 +312,22 @@ impl GateioClient {
         self.runtime.block_on(self.get_symbol())
     }
 
+    /// Returns all contracts that are not being delisted.
+    pub async fn get_contracts(&self) -> Result<Vec<String>> {
+        let endpoint = format!("/futures/{}/contracts", self.settle);
+        let contracts: Vec<ContractName> = self.get_public(&endpoint, None).await?;
+        Ok(contracts
+            .into_iter()
+            .filter(|contract| !contract.in_delisting)
+            .map(|contract| contract.name)
+            .collect())
+    }
+
+    pub fn get_contracts_sync(&self) -> Result<Vec<String>> {
+        self.runtime.block_on(self.get_contracts())
+    }
+
     pub async fn get_candles(&self, interval: &str, limit: usize) -> Result<Vec<Candle>> {
-        let endpoint = format!("/futures/{}/candlesticks", self.settle);
         let limit_str = limit.to_string();
         let params: Vec<(&str, &str)> = vec![
             ("contract", self.contract.as_str()),
@@ -297,7 +335,37 @@ impl GateioClient {
             ("limit", limit_str.as_str()),
         ];
 
-        let data: Vec<CandleData> = self.get_public(&endpoint, Some(&params)).await?;
+        self.fetch_candles(&params).await
+    }
+
+    /// Returns candles opened within `[from, to)`.
+    /// Gate.io rejects `limit` together with `from` and `to`, so the range defines the amount.
+    pub async fn get_candles_range(
+        &self,
+        interval: &str,
+        from: Timestamp,
+        to: Timestamp,
+    ) -> Result<Vec<Candle>> {
+        let from_str = from.seconds().to_string();
+        let to_str = (to.seconds() - 1).to_string();
+        let params: Vec<(&str, &str)> = vec![
+            ("contract", self.contract.as_str()),
+            ("interval", interval),
+            ("from", from_str.as_str()),
+            ("to", to_str.as_str()),
+        ];
+
+        self.fetch_candles(&params).await
+    }
+
+    async fn fetch_candles(&self, params: &[(&str, &str)]) -> Result<Vec<Candle>> {
+        let endpoint = format!("/futures/{}/candlesticks", self.settle);
+        let data: Vec<CandleData> = self.get_public(&endpoint, Some(params)).await?;
+        let multiplier = self
+            .quanto_multiplier
+            .get()
+            .copied()
+            .unwrap_or(Decimal::ONE);
 
         let candles: Vec<Candle> = data
             .iter()
@@ -307,7 +375,7 @@ impl GateioClient {
                 high: Decimal::from_str(&k.h).unwrap_or_default(),
                 low: Decimal::from_str(&k.l).unwrap_or_default(),
                 close: Decimal::from_str(&k.c).unwrap_or_default(),
-                volume: Decimal::from(k.v),
+                volume: Decimal::from(k.v) * multiplier,
             })
             .collect();
 
@@ -318,6 +386,16 @@ impl GateioClient {
         self.runtime.block_on(self.get_candles(interval, limit))
     }
 
+    pub fn get_candles_range_sync(
+        &self,
+        interval: &str,
+        from: Timestamp,
+        to: Timestamp,
+    ) -> Result<Vec<Candle>> {
+        self.runtime
+            .block_on(self.get_candles_range(interval, from, to))
+    }
+
     pub async fn get_depth(&self, limit: usize) -> Result<DepthSnapshot> {
         let endpoint = format!("/futures/{}/order_book", self.settle);
         let limit_str = limit.to_string();
@@ -345,11 +423,18 @@ impl GateioClient {
         self.get_public(&endpoint, Some(&params)).await
     }
 
+    /// Syncs the clock used for signed requests with the server time, returns the offset in milliseconds.
+    pub async fn sync_clock(&self) -> Result<i64> {
+        let sent_ms = local_ms();
+        let server_time: ServerTime = self.get_public("/spot/time", None).await?;
+        Ok(self
+            .clock
+            .sync(server_time.server_time, sent_ms, local_ms()))
+    }
+
     pub fn get_contract_stats_sync(&self, limit: usize) -> Result<Vec<ContractStats>> {
         self.runtime.block_on(self.get_contract_stats(limit))
     }
-
-    // === Private API endpoints (require authentication) ===
 }
 
 #[derive(Debug, Clone, Deserialize)]
@@ -358,6 +443,11 @@ pub struct ApiError {
     pub message: String,
 }
 
+#[derive(Deserialize)]
+struct ServerTime {
+    server_time: u64,
+}
+
 #[derive(Deserialize, Debug)]
 pub struct ContractInfo {
     order_price_round: String,
@@ -365,6 +455,13 @@ pub struct ContractInfo {
     order_size_min: i64,
 }
 
+#[derive(Deserialize)]
+struct ContractName {
+    name: String,
+    #[serde(default)]
+    in_delisting: bool,
+}
+
 #[derive(Deserialize)]
 struct CandleData {
     t: u64,
@@ -1,11 +1,14 @@
 use super::client::GateioClient;
 use super::market_stream::start_market_stream;
 use super::open_interest_stream::start_open_interest_stream;
-use crate::exchanges::base::exchange::Exchange;
+use crate::exchanges::base::exchange::{Exchange, RECONNECT_DELAY, kline_interval};
+use crate::exchanges::base::keep_clock_synced;
 use crate::models::{
-    CandlesState, Interval, Log, LogLevel, NewOrder, OpenInterestState, Order, OrderBookState,
-    OrderFlowState, SharedCandlesState, SharedState, Symbol,
+    CandlesState, Capabilities, ClockConfig, Interval, Log, LogLevel, NewOrder, NotificationEvent,
+    OpenInterestState, Order, OrderBookState, OrderFlowState, RateLimitStatus, SharedCandlesState,
+    SharedState, Sound, Symbol, Timestamp,
 };
+use rust_decimal::Decimal;
 use std::sync::{Arc, RwLock, mpsc::Sender};
 use std::thread;
 use tokio::runtime;
@@ -24,6 +27,7 @@ pub struct GateioUSDFuturesExchange {
     secret_key: Option<String>,
     stop_tx: Option<oneshot::Sender<()>>,
     handle: Option<thread::JoinHandle<()>>,
+    clock_config: ClockConfig,
 }
 
 impl Exchange for GateioUSDFuturesExchange {
@@ -31,6 +35,13 @@ impl Exchange for GateioUSDFuturesExchange {
         self.name
     }
 
+    fn capabilities(&self) -> Capabilities {
+        Capabilities {
+            open_interest: true,
+            ..Capabilities::DATA_ONLY
+        }
+    }
+
     fn start(
         &mut self,
         interval: Interval,
@@ -46,6 +57,7 @@ impl Exchange for GateioUSDFuturesExchange {
         self.shared_candles_state = Some(shared_candles_state.clone());
 
         let contract_clone = self.contract.clone();
+        let symbol_clone = symbol.clone();
         let settle_clone = self.settle.clone();
         let candles_clone = shared_candles_state.clone();
         let order_book_clone = shared_order_book_state.clone();
@@ -58,6 +70,7 @@ impl Exchange for GateioUSDFuturesExchange {
         let client_clone = self.client.clone();
         let access_key_clone = self.access_key.clone();
         let secret_key_clone = self.secret_key.clone();
+        let clock_config = self.clock_config.clone();
 
         self.set_interval(interval);
 
@@ -71,31 +84,49 @@ impl Exchange for GateioUSDFuturesExchange {
                 .expect("Failed to build tokio runtime for streams");
 
             rt.block_on(async move {
+                let component = format!("{} market stream", contract_clone);
+                let open_interest_component = format!("{} open interest", contract_clone);
                 tokio::select! {
-                    res = start_market_stream(
-                        &client_clone,
-                        &contract_clone,
-                        &settle_clone,
-                        100,
-                        candles_clone,
-                        order_book_clone,
-                        order_flow_clone,
-                    ) => {
-                        if let Err(e) = res {
-                            logs_sender_clone.send(Log::new(LogLevel::Error("CONN".to_string()), format!("{:?}", e), None)).ok();
+                    _ = async {
+                        loop {
+                            let res = start_market_stream(
+                                &client_clone,
+                                &symbol_clone,
+                                100,
+                                candles_clone.clone(),
+                                order_book_clone.clone(),
+                                order_flow_clone.clone(),
+                                || {
+                                    logs_sender_clone.send(Log::new(LogLevel::Recovered("CONN".to_string()), "Market stream connected".to_string(), None).with_component(&component)).ok();
+                                },
+                            ).await;
+                            if let Err(e) = res {
+                                logs_sender_clone.send(Log::new(LogLevel::Error("CONN".to_string()), format!("{:?}", e), Some(Sound::Disconnected)).with_event(NotificationEvent::ConnectionLost).with_component(&component)).ok();
+                            }
+                            tokio::time::sleep(RECONNECT_DELAY).await;
                         }
-                    }
+                    } => {}
 
-                    res = start_open_interest_stream(
-                        &client_clone,
-                        &contract_clone,
-                        &settle_clone,
-                        open_interest_clone,
-                    ) => {
-                        if let Err(e) = res {
-                            logs_sender_clone.send(Log::new(LogLevel::Error("CONN".to_string()), format!("{:?}", e), None)).ok();
+                    _ = async {
+                        loop {
+                            let res = start_open_interest_stream(
+                                &client_clone,
+                                &contract_clone,
+                                &settle_clone,
+                                open_interest_clone.clone(),
+                                logs_sender_clone.clone(),
+                                || {
+                                    logs_sender_clone.send(Log::new(LogLevel::Recovered("CONN".to_string()), "Open interest loaded".to_string(), None).with_component(&open_interest_component)).ok();
+                                },
+                            ).await;
+                            if let Err(e) = res {
+                                logs_sender_clone.send(Log::new(LogLevel::Error("CONN".to_string()), format!("{:?}", e), Some(Sound::Disconnected)).with_event(NotificationEvent::ConnectionLost).with_component(&open_interest_component)).ok();
+                            }
+                            tokio::time::sleep(RECONNECT_DELAY).await;
                         }
-                    }
+                    } => {}
+
+                    _ = keep_clock_synced(async || client_clone.sync_clock().await, &logs_sender_clone, &clock_config) => {}
 
                     _ = shutdown_rx => {
                         logs_sender_clone.send(Log::new(LogLevel::Info, "Shutting down market stream listener".to_string(), None)).ok();
@@ -120,7 +151,7 @@ impl Exchange for GateioUSDFuturesExchange {
         ))
     }
 
-    fn stop(&mut self) -> () {
+    fn stop(&mut self) {
         if let Some(stop_tx) = self.stop_tx.take() {
             let _ = stop_tx.send(());
         }
@@ -131,17 +162,14 @@ impl Exchange for GateioUSDFuturesExchange {
         self.handle = None;
     }
 
-    fn set_interval(&self, interval: Interval) -> () {
-        let interval_str = match interval {
-            Interval::M1 => "1m",
-            Interval::M5 => "5m",
-            Interval::M15 => "15m",
-            Interval::H1 => "1h",
-        };
+    fn symbols(&self) -> Result<Vec<String>, Box<dyn std::error::Error>> {
+        Ok(self.client.get_contracts_sync()?)
+    }
 
+    fn set_interval(&self, interval: Interval) {
         let candles = self
             .client
-            .get_candles_sync(&interval_str, self.candles_limit)
+            .get_candles_sync(kline_interval(interval), self.candles_limit)
             .unwrap();
 
         if let Some(shared_candles_state) = self.shared_candles_state.as_ref() {
@@ -153,7 +181,65 @@ impl Exchange for GateioUSDFuturesExchange {
         }
     }
 
-    fn place_order(&self, _new_order: NewOrder) -> () {
+    fn load_candles(&self, interval: Interval, until: Timestamp) {
+        let Some(shared_candles_state) = self.shared_candles_state.clone() else {
+            return;
+        };
+        let client = self.client.clone();
+        let from = Timestamp::from_seconds(
+            until
+                .seconds()
+                .saturating_sub(self.candles_limit as u64 * interval.seconds() as u64),
+        );
+        let logs_sender_clone = self.logs_sender.clone();
+        thread::spawn(move || {
+            match client.get_candles_range_sync(kline_interval(interval), from, until) {
+                Ok(candles) => {
+                    let mut buffer = shared_candles_state.write().unwrap();
+                    // the interval could be changed while loading
+                    if buffer.interval == interval {
+                        buffer.prepend(candles);
+                    }
+                }
+                Err(e) => {
+                    let _ = logs_sender_clone.send(Log::new(
+                        LogLevel::Warning("WARN".to_string(), None),
+                        format!("Failed to load candles: {:?}", e),
+                        None,
+                    ));
+                }
+            }
+        });
+    }
+
+    fn snapshot(
+        &self,
+        interval: Interval,
+        at: Timestamp,
+    ) -> Result<(Symbol, SharedState), Box<dyn std::error::Error>> {
+        let symbol = self.client.get_symbol_sync()?;
+        let from = Timestamp::from_seconds(
+            at.seconds()
+                .saturating_sub(self.candles_limit as u64 * interval.seconds() as u64),
+        );
+        let candles = self.client.get_candles_range_sync(
+            kline_interval(interval),
+            from,
+            Timestamp::from_seconds(at.seconds() + 1),
+        )?;
+
+        let shared_state = SharedState::new(self.candles_limit, interval);
+        {
+            let mut buffer = shared_state.candles.write().unwrap();
+            for candle in candles {
+                buffer.push(candle);
+            }
+        }
+
+        Ok((symbol, shared_state))
+    }
+
+    fn place_order(&self, _new_order: NewOrder) {
         let _ = self.logs_sender.send(Log::new(
             LogLevel::Warning("NA".to_string(), None),
             format!("Trading is not supported on {}", self.name()).to_string(),
@@ -161,13 +247,25 @@ impl Exchange for GateioUSDFuturesExchange {
         ));
     }
 
-    fn cancel_order(&self, _order: Order) -> () {
+    fn cancel_order(&self, _order: Order) {
         let _ = self.logs_sender.send(Log::new(
             LogLevel::Warning("NA".to_string(), None),
             format!("Trading is not supported on {}", self.name()).to_string(),
             None,
         ));
     }
+
+    fn modify_order(&self, _order: Order, _price: Decimal) {
+        let _ = self.logs_sender.send(Log::new(
+            LogLevel::Warning("NA".to_string(), None),
+            format!("Trading is not supported on {}", self.name()).to_string(),
+            None,
+        ));
+    }
+
+    fn rate_limit(&self) -> RateLimitStatus {
+        self.client.rate_limit()
+    }
 }
 
 impl GateioUSDFuturesExchange {
@@ -178,6 +276,7 @@ impl GateioUSDFuturesExchange {
         logs_sender: Sender<Log>,
         access_key: Option<String>,
         secret_key: Option<String>,
+        clock_config: ClockConfig,
     ) -> Self {
         let client = Arc::new(GateioClient::new(
             contract.clone(),
@@ -198,6 +297,7 @@ impl GateioUSDFuturesExchange {
             secret_key,
             stop_tx: None,
             handle: None,
+            clock_config,
         }
     }
 }
@@ -1,7 +1,7 @@
 use super::client::GateioClient;
 use crate::models::{
-    Candle, CandlesState, Interval, SharedCandlesState, SharedOrderBookState, SharedOrderFlowState,
-    Timestamp,
+    Candle, CandlesState, Interval, OrderSide, SharedCandlesState, SharedOrderBookState,
+    SharedOrderFlowState, Symbol, Timestamp, Trade,
 };
 use futures_util::SinkExt;
 use futures_util::stream::StreamExt;
@@ -12,13 +12,18 @@ use tokio_tungstenite::{connect_async, tungstenite::protocol::Message};
 
 pub async fn start_market_stream(
     client: &GateioClient,
-    contract: &String,
-    _settle: &String,
+    // the contract, its step size is the multiplier from contracts to the base quantity
+    symbol: &Symbol,
     dom_limit: usize,
     shared_candles_state: SharedCandlesState,
     shared_dom_state: SharedOrderBookState,
     shared_order_flow_state: SharedOrderFlowState,
+    // called on the first message, after a reconnect it reports the recovery
+    on_connected: impl Fn(),
 ) -> Result<(), Box<dyn std::error::Error>> {
+    let contract = &symbol.slug;
+    // sizes are contract counts
+    let to_base = |size: i64| Decimal::from(size) * symbol.step_size;
     let ws_url = "wss://fx-ws.gateio.ws/v4/ws/usdt";
     let (ws_stream, _) = connect_async(ws_url).await?;
     let (mut write, mut read) = ws_stream.split();
@@ -72,7 +77,7 @@ pub async fn start_market_stream(
             .iter()
             .filter_map(|a| {
                 let price = Decimal::from_str(&a.p).unwrap();
-                let qty = Decimal::from(a.s);
+                let qty = to_base(a.s);
                 Some((price, qty))
             })
             .collect();
@@ -81,7 +86,7 @@ pub async fn start_market_stream(
             .iter()
             .filter_map(|b| {
                 let price = Decimal::from_str(&b.p).unwrap();
-                let qty = Decimal::from(b.s);
+                let qty = to_base(b.s);
                 Some((price, qty))
             })
             .collect();
@@ -90,9 +95,14 @@ pub async fn start_market_stream(
         buffer.init_snapshot(bids, asks);
     }
 
+    let mut connected = false;
     while let Some(msg) = read.next().await {
         match msg {
             Ok(Message::Text(text)) => {
+                if !connected {
+                    connected = true;
+                    on_connected();
+                }
                 if let Ok(wrapper) = serde_json::from_str::<EventWrapper>(&text) {
                     if wrapper.event == "update" {
                         match wrapper.channel.as_str() {
@@ -101,7 +111,11 @@ pub async fn start_market_stream(
                                     serde_json::from_value::<OrderBookEvent>(wrapper.result)
                                 {
                                     if event.first_update_id >= depth_snapshot.id {
-                                        apply_order_book_update(&shared_dom_state, &event);
+                                        apply_order_book_update(
+                                            &shared_dom_state,
+                                            &event,
+                                            symbol.step_size,
+                                        );
                                     }
                                 }
                             }
@@ -111,16 +125,26 @@ pub async fn start_market_stream(
                                 {
                                     for event in events {
                                         if let Ok(price) = Decimal::from_str(&event.price) {
-                                            let qty = Decimal::from(event.size.abs());
+                                            let qty = to_base(event.size.abs());
                                             let mut buffer =
                                                 shared_order_flow_state.write().unwrap();
-                                            if event.size > 0 {
-                                                buffer.buy(price, qty);
-                                            } else {
-                                                buffer.sell(price, qty);
-                                            }
+                                            buffer.add_trade(Trade {
+                                                time: Timestamp::from_milliseconds(
+                                                    event.create_time,
+                                                ),
+                                                price,
+                                                quantity: qty,
+                                                side: if event.size > 0 {
+                                                    OrderSide::Buy
+                                                } else {
+                                                    OrderSide::Sell
+                                                },
+                                            });
                                             buffer.updated =
                                                 Timestamp::from_milliseconds(event.create_time);
+                                            buffer.stats.record(Timestamp::from_milliseconds(
+                                                event.create_time,
+                                            ));
                                             buffer.online = true;
                                         }
                                     }
@@ -137,7 +161,7 @@ pub async fn start_market_stream(
                                             high: Decimal::from_str(&event.h).unwrap_or_default(),
                                             low: Decimal::from_str(&event.l).unwrap_or_default(),
                                             close: Decimal::from_str(&event.c).unwrap_or_default(),
-                                            volume: Decimal::from(event.v),
+                                            volume: to_base(event.v),
                                         };
                                         candles_state_1m.push(candle);
 
@@ -147,6 +171,11 @@ pub async fn start_market_stream(
                                         {
                                             buffer.push(candle);
                                             buffer.updated = Timestamp::now();
+                                            if let Some(time_ms) = wrapper.time_ms {
+                                                buffer
+                                                    .stats
+                                                    .record(Timestamp::from_milliseconds(time_ms));
+                                            }
                                             buffer.online = true;
                                         }
                                     }
@@ -160,14 +189,12 @@ pub async fn start_market_stream(
             Ok(Message::Close(_)) => {
                 let mut buffer = shared_candles_state.write().unwrap();
                 buffer.online = false;
-                println!("WebSocket closed");
-                break;
+                return Err("WebSocket closed".into());
             }
             Err(e) => {
                 let mut buffer = shared_candles_state.write().unwrap();
                 buffer.online = false;
-                eprintln!("Error: {}", e);
-                break;
+                return Err(e.into());
             }
             _ => {}
         }
@@ -183,22 +210,28 @@ fn get_timestamp() -> u64 {
         .as_secs()
 }
 
-fn apply_order_book_update(shared_dom_state: &SharedOrderBookState, event: &OrderBookEvent) {
+fn apply_order_book_update(
+    shared_dom_state: &SharedOrderBookState,
+    event: &OrderBookEvent,
+    // contracts to the base quantity
+    multiplier: Decimal,
+) {
     let mut buffer = shared_dom_state.write().unwrap();
     // Gate.io futures: swap 'b' and 'a' - they're reversed from expected
     for b in event.b.iter() {
         if let Ok(price) = Decimal::from_str(&b.p) {
-            let qty = Decimal::from(b.s);
+            let qty = Decimal::from(b.s) * multiplier;
             buffer.update_bid(price, qty);
         }
     }
     for a in event.a.iter() {
         if let Ok(price) = Decimal::from_str(&a.p) {
-            let qty = Decimal::from(a.s);
+            let qty = Decimal::from(a.s) * multiplier;
             buffer.update_ask(price, qty);
         }
     }
     buffer.updated = Timestamp::from_milliseconds(event.t);
+    buffer.stats.record(Timestamp::from_milliseconds(event.t));
     buffer.online = true;
 }
 
@@ -206,6 +239,8 @@ fn apply_order_book_update(shared_dom_state: &SharedOrderBookState, event: &Orde
 struct EventWrapper {
     event: String,
     channel: String,
+    // server time of the message
+    time_ms: Option<u64>,
     result: serde_json::Value,
 }
 
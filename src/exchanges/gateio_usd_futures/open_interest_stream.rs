@@ -1,6 +1,7 @@
-use super::client::GateioClient;
-use crate::models::{SharedOpenInterestState, Timestamp};
+use super::client::{ContractStats, GateioClient};
+use crate::models::{Log, LogLevel, SharedOpenInterestState, Timestamp};
 use rust_decimal::Decimal;
+use std::sync::mpsc::Sender;
 use tokio::time::{Duration, sleep};
 
 pub async fn start_open_interest_stream(
@@ -8,37 +9,47 @@ pub async fn start_open_interest_stream(
     _contract: &String,
     _settle: &String,
     shared_open_interest_state: SharedOpenInterestState,
+    logs_sender: Sender<Log>,
+    // called once the history is loaded, after a reconnect it reports the recovery
+    on_connected: impl Fn(),
 ) -> Result<(), Box<dyn std::error::Error>> {
-    let get_oi = async |limit| match client.get_contract_stats(limit).await {
-        Ok(contract_stats_list) => {
-            let mut buffer = shared_open_interest_state.write().unwrap();
-            let mut last_time = 0;
+    let push = |contract_stats_list: Vec<ContractStats>| {
+        let mut buffer = shared_open_interest_state.write().unwrap();
+        let mut last_time = 0;
 
-            for contract_stats in &contract_stats_list {
-                last_time = contract_stats.time;
-                let value = Decimal::from(contract_stats.open_interest);
-                let timestamp = Timestamp::from_seconds(contract_stats.time);
+        for contract_stats in &contract_stats_list {
+            last_time = contract_stats.time;
+            let value = Decimal::from(contract_stats.open_interest);
+            let timestamp = Timestamp::from_seconds(contract_stats.time);
 
-                buffer.push(&timestamp, value);
-            }
-
-            if last_time > 0 {
-                buffer.updated = Timestamp::from_seconds(last_time);
-                buffer.online = true;
-            }
+            buffer.push(&timestamp, value);
         }
-        Err(e) => {
-            eprintln!("Failed to fetch open interest: {:?}", e);
-            let mut buffer = shared_open_interest_state.write().unwrap();
-            buffer.online = false;
+
+        if last_time > 0 {
+            buffer.updated = Timestamp::from_seconds(last_time);
+            buffer.online = true;
         }
     };
 
-    get_oi(1000).await;
+    push(client.get_contract_stats(1000).await?);
+    on_connected();
 
     loop {
         sleep(Duration::from_secs(20)).await;
 
-        get_oi(5).await;
+        match client.get_contract_stats(5).await {
+            Ok(contract_stats_list) => push(contract_stats_list),
+            Err(e) => {
+                logs_sender
+                    .send(Log::new(
+                        LogLevel::Warning("OI".to_string(), None),
+                        format!("Failed to fetch open interest: {:?}", e),
+                        None,
+                    ))
+                    .ok();
+                let mut buffer = shared_open_interest_state.write().unwrap();
+                buffer.online = false;
+            }
+        }
     }
 }
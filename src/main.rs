@@ -1,61 +1,97 @@
 mod exchanges;
 mod models;
+mod pane;
 mod renderer;
 mod trader;
 mod utils;
 
-use crate::exchanges::ExchangeFactory;
-use crate::models::{Log, LogLevel, Orders, Sound};
-use crate::renderer::Renderer;
-use crate::trader::Trader;
-use crate::utils::{allow_sleep, prevent_sleep};
+use crate::models::{Layout, Log, LogFile, LogLevel, Panel, SymbolSearch};
+use crate::pane::Pane;
+use crate::utils::{Notifier, allow_sleep, prevent_sleep};
 use console::Term;
-use font_kit::family_name::FamilyName;
-use font_kit::properties::Properties;
-use font_kit::source::SystemSource;
-use minifb::{Key, MouseButton, MouseMode, Window, WindowOptions};
-use models::{AlertTriggerType, Alerts, ColorSchema, Config, Interval, LogManager};
+use minifb::{CursorStyle, Key, MouseButton, MouseMode, Window, WindowOptions};
+use models::{Alert, ColorSchema, Config, LogManager, SoundPlayer};
 use rust_decimal::Decimal;
 use std::sync::mpsc;
 
 fn main() {
     let (logs_sender, logs_receiver) = mpsc::channel();
-    let (orders_sender, orders_receiver) = mpsc::channel();
 
-    let mut logs_manager = LogManager::new(logs_receiver, Term::stdout(), false);
+    let mut logs_manager = LogManager::new(logs_receiver, Term::stdout());
 
-    let config = Config::load().unwrap_or_else(|err| {
+    let mut config = Config::load().unwrap_or_else(|err| {
         logs_manager.log_error(&format!("Error loading config: {}", err));
         std::process::exit(1);
     });
 
-    logs_manager.set_with_sound(config.sound);
+    if config.log_file.enabled {
+        match LogFile::open(&config.log_file) {
+            Ok(log_file) => logs_manager.set_log_file(Some(log_file)),
+            Err(err) => logs_manager.log_warning(&format!("Log file is disabled: {}", err)),
+        }
+    }
 
-    let mut interval = Interval::M1;
-    let mut exchange = ExchangeFactory::create(
-        config.exchange.as_str(),
-        config.symbol.clone(),
-        200,
-        &config,
+    let sound_player = (config.sound || config.tape_sound.enabled)
+        .then(|| SoundPlayer::start(&config.sounds, logs_sender.clone()));
+    if config.sound {
+        logs_manager.set_sound_player(sound_player.clone());
+    }
+    logs_manager.set_notifier(Notifier::start(
+        config.notifications.clone(),
         logs_sender.clone(),
-        orders_sender,
-    )
-    .unwrap_or_else(|err| {
-        logs_manager.log_error(&format!("Error creating exchange: {}", err));
-        std::process::exit(1);
-    });
+    ));
 
-    let (symbol, shared_state) = exchange.start(interval).unwrap_or_else(|err| {
-        logs_manager.log_error(&format!("Error starting streams: {}", err));
+    if let Err(err) = ColorSchema::load(&config.theme) {
+        logs_manager.log_error(&format!("Error loading theme: {}", err));
         std::process::exit(1);
-    });
+    }
 
-    let mut alerts = Alerts::new();
+    // the configured window size is in unscaled pixels
+    let ui_scale = config.ui_scale();
+    let window_width = (config.window_width as f32 * ui_scale) as usize;
+    let window_height = (config.window_height as f32 * ui_scale) as usize;
+
+    if let Some(at) = config.render_at {
+        let area = Layout::grid(window_width as i32, window_height as i32, 1)[0];
+        let result = Pane::render_snapshot(&config, &config.symbol, at, area, logs_sender.clone());
+        let code = match result {
+            Ok(path) => {
+                logs_manager.log_info(&format!("Chart saved to {}", path.display()));
+                0
+            }
+            Err(err) => {
+                logs_manager.log_error(&format!("Error rendering chart: {}", err));
+                1
+            }
+        };
+        logs_manager.consume();
+        std::process::exit(code);
+    }
+
+    let areas = Layout::grid(
+        window_width as i32,
+        window_height as i32,
+        config.symbols.len(),
+    );
+    let mut panes: Vec<Pane> = config
+        .symbols
+        .iter()
+        .zip(areas)
+        .map(|(symbol, area)| {
+            Pane::start(&config, symbol, config.interval, area, logs_sender.clone()).unwrap_or_else(
+                |err| {
+                    logs_manager.log_error(&format!("Error starting {}: {}", symbol, err));
+                    std::process::exit(1);
+                },
+            )
+        })
+        .collect();
+    let mut active = 0;
 
     let mut window = Window::new(
-        &format!("{} - {}", symbol.slug, exchange.name()),
-        config.window_width,
-        config.window_height,
+        &window_title(&panes[active]),
+        window_width,
+        window_height,
         WindowOptions {
             resize: true,
             ..WindowOptions::default()
@@ -64,80 +100,26 @@ fn main() {
     .unwrap();
     window.set_target_fps(60);
 
-    let mut trader = Trader::new(
-        symbol.clone(),
-        Orders::new(),
-        [
-            config.lot_mult_1.unwrap(),
-            config.lot_mult_2.unwrap(),
-            config.lot_mult_3.unwrap(),
-            config.lot_mult_4.unwrap(),
-        ],
-        config.lot_size.unwrap(),
-        config.sl_pnl,
-    );
-
-    let font = SystemSource::new()
-        .select_best_match(&[FamilyName::Monospace], &Properties::new())
-        .unwrap()
-        .load()
-        .unwrap();
-    let mut renderer = Renderer::new(
-        config.window_width,
-        config.window_height,
-        symbol.tick_size,
-        ColorSchema::for_theme(config.theme),
-        font,
-    );
-
     prevent_sleep();
 
-    let consume_orders = |trader: &mut Trader| {
-        let mut consumed = false;
-        match orders_receiver.try_recv() {
-            Ok(value) => {
-                let order_str = value.to_string();
-                let filled = trader.consume_order(value);
-                if filled {
-                    logs_sender
-                        .send(Log::new(
-                            LogLevel::Info,
-                            order_str,
-                            Some(Sound::OrderFilled),
-                        ))
-                        .ok();
-                }
-                consumed = true;
-            }
-            Err(mpsc::TryRecvError::Empty) => {}
-            Err(mpsc::TryRecvError::Disconnected) => {}
-        }
-
-        consumed
-    };
-
     let mut force_redraw = true;
     let mut left_was_pressed = false;
-    let mut sl_triggered = false;
-    while window.is_open() && !window.is_key_down(Key::Escape) {
-        force_redraw = force_redraw || consume_orders(&mut trader);
-
-        if trader.bid.is_some() && trader.ask.is_some() {
-            for alert in alerts.scan(trader.bid.unwrap(), trader.ask.unwrap()) {
-                logs_sender
-                    .send(Log::new(
-                        LogLevel::Info,
-                        format!("Price alert triggered at {:.8}", alert.price),
-                        Some(Sound::Alert),
-                    ))
-                    .unwrap();
-                force_redraw = true;
-            }
+    // panel whose border is being dragged
+    let mut resizing: Option<Panel> = None;
+    let mut symbols: Option<Vec<String>> = None;
+    let mut symbol_search: Option<SymbolSearch> = None;
+    let mut log_console = false;
+    while window.is_open() {
+        let searching = symbol_search.is_some();
+        if !searching && !log_console && window.is_key_pressed(Key::Escape, minifb::KeyRepeat::No) {
+            break;
         }
 
-        {
-            let order_book = shared_state.order_book.read().unwrap();
-            trader.set_bid_ask(order_book.bid(), order_book.ask());
+        for (i, pane) in panes.iter_mut().enumerate() {
+            // only the active chart is heard on the tape
+            let tape_player = sound_player.as_ref().filter(|_| i == active);
+            force_redraw = pane.consume_orders(&logs_sender) || force_redraw;
+            force_redraw = pane.update(&config, &logs_sender, tape_player) || force_redraw;
         }
 
         logs_manager.consume();
@@ -145,160 +127,446 @@ fn main() {
         let ctrl_pressed = window.is_key_down(Key::LeftCtrl) || window.is_key_down(Key::RightCtrl);
         let shift_pressed =
             window.is_key_down(Key::LeftShift) || window.is_key_down(Key::RightShift);
-
-        if window.is_key_pressed(Key::Key1, minifb::KeyRepeat::No) {
-            trader.set_size_multiplier_index(0);
+        let draw_pressed = window.is_key_down(Key::D);
+
+        let mouse_pos = window.get_mouse_pos(MouseMode::Discard);
+        let hovered = mouse_pos.and_then(|(x, y)| {
+            panes
+                .iter()
+                .position(|pane| pane.area.contains(x as i32, y as i32))
+        });
+
+        let mut switch_to: Option<String> = None;
+        if let Some(search) = symbol_search.as_mut() {
+            let mut close = false;
+            for key in window.get_keys_pressed(minifb::KeyRepeat::Yes) {
+                match key {
+                    Key::Escape => close = true,
+                    Key::Enter | Key::NumPadEnter => {
+                        switch_to = search.selected().map(str::to_string);
+                        close = true;
+                    }
+                    Key::Backspace => search.pop(),
+                    Key::Up => search.select_prev(),
+                    Key::Down => search.select_next(),
+                    key => {
+                        if let Some(c) = key_to_char(key) {
+                            search.push(c);
+                        }
+                    }
+                }
+            }
+            if close {
+                symbol_search = None;
+                force_redraw = true;
+            }
         }
 
-        if window.is_key_pressed(Key::Key2, minifb::KeyRepeat::No) {
-            trader.set_size_multiplier_index(1);
+        let left_pressed = window.get_mouse_down(MouseButton::Left);
+        // the first click on another pane only activates it
+        if !searching
+            && left_pressed
+            && !left_was_pressed
+            && let Some(index) = hovered
+            && index != active
+        {
+            active = index;
+            window.set_title(&window_title(&panes[active]));
+            left_was_pressed = left_pressed;
+            force_redraw = true;
         }
 
-        if window.is_key_pressed(Key::Key3, minifb::KeyRepeat::No) {
-            trader.set_size_multiplier_index(2);
-        }
+        if !searching {
+            if window.is_key_pressed(Key::L, minifb::KeyRepeat::No) {
+                log_console = !log_console;
+                force_redraw = true;
+            }
+            if log_console {
+                if window.is_key_pressed(Key::Escape, minifb::KeyRepeat::No) {
+                    log_console = false;
+                    force_redraw = true;
+                }
+                if window.is_key_pressed(Key::Enter, minifb::KeyRepeat::No)
+                    || window.is_key_pressed(Key::NumPadEnter, minifb::KeyRepeat::No)
+                {
+                    logs_manager.acknowledge();
+                }
+            }
 
-        if window.is_key_pressed(Key::Key4, minifb::KeyRepeat::No) {
-            trader.set_size_multiplier_index(3);
-        }
+            if window.is_key_pressed(Key::Tab, minifb::KeyRepeat::No) {
+                active = (active + 1) % panes.len();
+                window.set_title(&window_title(&panes[active]));
+                force_redraw = true;
+            }
 
-        if window.is_key_pressed(Key::Equal, minifb::KeyRepeat::No) {
-            if let Some(new_order) = trader.market_buy() {
-                exchange.place_order(new_order);
+            if window.is_key_pressed(Key::T, minifb::KeyRepeat::No) {
+                let themes = ColorSchema::available_themes();
+                let next = themes
+                    .iter()
+                    .position(|theme| *theme == config.theme)
+                    .map_or(0, |i| (i + 1) % themes.len());
+                match ColorSchema::load(&themes[next]) {
+                    Ok(color_schema) => {
+                        for pane in panes.iter_mut() {
+                            pane.renderer.set_color_schema(color_schema.clone());
+                        }
+                        config.theme = themes[next].clone();
+                        logs_sender
+                            .send(Log::new(
+                                LogLevel::Info,
+                                format!("Theme: {}", String::from(config.theme.clone())),
+                                None,
+                            ))
+                            .ok();
+                    }
+                    Err(e) => {
+                        logs_sender
+                            .send(Log::new(
+                                LogLevel::Warning("THEME".to_string(), Some(5)),
+                                e,
+                                None,
+                            ))
+                            .ok();
+                    }
+                }
             }
-        }
 
-        if window.is_key_pressed(Key::Minus, minifb::KeyRepeat::No) {
-            if let Some(new_order) = trader.market_sell() {
-                exchange.place_order(new_order);
+            if window.is_key_pressed(Key::P, minifb::KeyRepeat::No) {
+                let pane = &panes[active];
+                let log = match pane.screenshot(&config) {
+                    Ok(path) => Log::new(
+                        LogLevel::Info,
+                        format!("Screenshot saved to {}", path.display()),
+                        None,
+                    ),
+                    Err(e) => Log::new(
+                        LogLevel::Warning("SCREENSHOT".to_string(), Some(5)),
+                        format!("Failed to save screenshot: {}", e),
+                        None,
+                    ),
+                };
+                logs_sender.send(log).ok();
             }
-        }
 
-        if window.is_key_pressed(Key::Key0, minifb::KeyRepeat::No) {
-            if let Some(new_order) = trader.flat() {
-                exchange.place_order(new_order);
+            let pane = &mut panes[active];
+            // trading hotkeys are ignored on data-only exchanges, and while the log console is open,
+            // so a key meant for the console does not place or cancel orders
+            let trading = pane.exchange.capabilities().trading && !log_console;
+
+            if window.is_key_pressed(Key::Slash, minifb::KeyRepeat::No) {
+                if symbols.is_none() {
+                    match pane.exchange.symbols() {
+                        Ok(list) => symbols = Some(list),
+                        Err(e) => {
+                            logs_sender
+                                .send(Log::new(
+                                    LogLevel::Warning("SYMBOL".to_string(), None),
+                                    format!("Failed to load symbols: {}", e),
+                                    None,
+                                ))
+                                .ok();
+                        }
+                    }
+                }
+                if let Some(list) = symbols.as_ref() {
+                    symbol_search = Some(SymbolSearch::new(list.clone(), 10));
+                }
             }
-        }
 
-        if window.is_key_pressed(Key::R, minifb::KeyRepeat::No) {
-            if let Some(new_order) = trader.reverse() {
-                exchange.place_order(new_order);
+            if window.is_key_pressed(Key::Key1, minifb::KeyRepeat::No) {
+                pane.trader.set_size_multiplier_index(0);
             }
-        }
 
-        if window.is_key_pressed(Key::N, minifb::KeyRepeat::No) {
-            shared_state.order_flow.write().unwrap().reset();
-            renderer.reset_volume_range();
-        }
+            if window.is_key_pressed(Key::Key2, minifb::KeyRepeat::No) {
+                pane.trader.set_size_multiplier_index(1);
+            }
 
-        if window.is_key_pressed(Key::Up, minifb::KeyRepeat::No) && shift_pressed {
-            renderer.scale_out();
-            force_redraw = true;
-        }
+            if window.is_key_pressed(Key::Key3, minifb::KeyRepeat::No) {
+                pane.trader.set_size_multiplier_index(2);
+            }
 
-        if window.is_key_pressed(Key::Down, minifb::KeyRepeat::No) && shift_pressed {
-            renderer.scale_in();
-            force_redraw = true;
-        }
+            if window.is_key_pressed(Key::Key4, minifb::KeyRepeat::No) {
+                pane.trader.set_size_multiplier_index(3);
+            }
+
+            if trading
+                && window.is_key_pressed(Key::Equal, minifb::KeyRepeat::No)
+                && let Some(new_order) = pane.trader.market_buy()
+            {
+                pane.place_order(new_order);
+            }
+
+            if trading
+                && window.is_key_pressed(Key::Minus, minifb::KeyRepeat::No)
+                && let Some(new_order) = pane.trader.market_sell()
+            {
+                pane.place_order(new_order);
+            }
+
+            if trading
+                && window.is_key_pressed(Key::Key0, minifb::KeyRepeat::No)
+                && let Some(new_order) = pane.trader.flat()
+            {
+                pane.place_order(new_order);
+            }
+
+            if trading
+                && window.is_key_pressed(Key::R, minifb::KeyRepeat::No)
+                && let Some(new_order) = pane.trader.reverse()
+            {
+                pane.place_order(new_order);
+            }
+
+            if window.is_key_pressed(Key::N, minifb::KeyRepeat::No) {
+                pane.shared_state.order_flow.write().unwrap().reset();
+                pane.renderer.reset_volume_range();
+            }
 
-        if window.is_key_pressed(Key::Right, minifb::KeyRepeat::No) && shift_pressed {
-            let new_interval = interval.up();
-            if new_interval != interval {
-                interval = new_interval;
-                exchange.set_interval(new_interval);
+            if window.is_key_pressed(Key::H, minifb::KeyRepeat::No) {
+                pane.renderer.toggle_heatmap();
+            }
+
+            if window.is_key_pressed(Key::Up, minifb::KeyRepeat::No) && shift_pressed {
+                pane.renderer.scale_out();
                 force_redraw = true;
             }
-        }
 
-        if window.is_key_pressed(Key::Left, minifb::KeyRepeat::No) && shift_pressed {
-            let new_interval = interval.down();
-            if new_interval != interval {
-                interval = new_interval;
-                exchange.set_interval(new_interval);
+            if window.is_key_pressed(Key::Down, minifb::KeyRepeat::No) && shift_pressed {
+                pane.renderer.scale_in();
                 force_redraw = true;
             }
-        }
 
-        let left_pressed = window.get_mouse_down(MouseButton::Left);
-        if left_pressed && !left_was_pressed {
-            if let Some((_x, y)) = window.get_mouse_pos(MouseMode::Clamp) {
-                let price = renderer.px_to_price(y as i32);
+            if window.is_key_pressed(Key::Right, minifb::KeyRepeat::No) && shift_pressed {
+                pane.set_interval(pane.interval.up());
+                force_redraw = true;
+            }
+
+            if window.is_key_pressed(Key::Left, minifb::KeyRepeat::No) && shift_pressed {
+                pane.set_interval(pane.interval.down());
+                force_redraw = true;
+            }
+
+            if window.is_key_pressed(Key::End, minifb::KeyRepeat::No) {
+                pane.renderer.snap_to_live();
+            }
+
+            if let Some((_, dy)) = window.get_scroll_wheel() {
+                if dy > 0.0 {
+                    pane.renderer.zoom_in();
+                } else if dy < 0.0 {
+                    pane.renderer.zoom_out();
+                }
+            }
+
+            if left_pressed
+                && !left_was_pressed
+                && let Some((x, y)) = window.get_mouse_pos(MouseMode::Clamp)
+            {
+                let (x, y) = pane.local_pos(x, y);
+                let price = pane.renderer.px_to_price(y);
                 if ctrl_pressed {
-                    if price > Decimal::ZERO {
+                    if trading && price > Decimal::ZERO {
                         if shift_pressed {
-                            if let Some(new_order) = trader.stop(price) {
-                                exchange.place_order(new_order);
+                            if let Some(new_order) = pane.trader.stop(price) {
+                                pane.place_order(new_order);
                             }
                         } else {
-                            if let Some(new_order) = trader.limit(price) {
-                                exchange.place_order(new_order);
+                            if let Some(new_order) = pane.trader.limit(price) {
+                                pane.place_order(new_order);
                             }
                         };
                         force_redraw = true;
                     }
-                } else if shift_pressed && trader.bid.is_some() {
-                    alerts.add_alert(
-                        price,
-                        if trader.bid.unwrap() >= price {
-                            AlertTriggerType::Lte
-                        } else {
-                            AlertTriggerType::Gte
-                        },
-                    );
+                } else if draw_pressed && pane.start_drawing(x, y, shift_pressed) {
                     force_redraw = true;
+                } else if shift_pressed && let Some(bid) = pane.trader.bid {
+                    pane.add_alert(Alert::price(price, bid), &logs_sender);
+                    force_redraw = true;
+                } else if let Some(panel) = pane.renderer.layout().border_at(x) {
+                    resizing = Some(panel);
+                } else if !pane.start_moving_drawing(x, y) {
+                    pane.drag_x = Some(x);
                 }
             }
+            if let Some(panel) = resizing
+                && let Some((x, y)) = window.get_mouse_pos(MouseMode::Clamp)
+            {
+                let (x, _) = pane.local_pos(x, y);
+                let width = pane.renderer.layout().dragged_width(panel, x);
+                config.layout.set_width(panel, width);
+            }
+            if pane.is_drawing()
+                && let Some((x, y)) = window.get_mouse_pos(MouseMode::Clamp)
+            {
+                let (x, y) = pane.local_pos(x, y);
+                pane.drag_drawing(x, y);
+            }
+            if !left_pressed {
+                pane.drag_x = None;
+                pane.finish_drawing(&logs_sender);
+                if resizing.take().is_some()
+                    && let Err(e) = config.save_layout()
+                {
+                    logs_sender
+                        .send(Log::new(
+                            LogLevel::Warning("CONFIG".to_string(), Some(5)),
+                            format!("Failed to save layout: {}", e),
+                            None,
+                        ))
+                        .ok();
+                }
+            }
+
+            let over_border = resizing.is_some()
+                || (hovered == Some(active)
+                    && mouse_pos.is_some_and(|(x, y)| {
+                        let (x, _) = pane.local_pos(x, y);
+                        pane.renderer.layout().border_at(x).is_some()
+                    }));
+            window.set_cursor_style(if over_border {
+                CursorStyle::ResizeLeftRight
+            } else {
+                CursorStyle::Arrow
+            });
+            if let Some(start_x) = pane.drag_x
+                && let Some((x, y)) = window.get_mouse_pos(MouseMode::Clamp)
+            {
+                // dragging to the right moves the view back in time
+                let (x, _) = pane.local_pos(x, y);
+                let candle_width = pane.renderer.candle_width();
+                let candles = (x - start_x) / candle_width;
+                if candles != 0 {
+                    pane.renderer
+                        .pan(candles, &pane.shared_state.candles.read().unwrap());
+                    pane.drag_x = Some(start_x + candles * candle_width);
+                }
+            }
+
+            if let Some((x, y)) = mouse_pos
+                && hovered == Some(active)
+            {
+                let (x, y) = pane.local_pos(x, y);
+                if (window.is_key_pressed(Key::Delete, minifb::KeyRepeat::No)
+                    || window.is_key_pressed(Key::Backspace, minifb::KeyRepeat::No))
+                    && pane.delete_drawing_at(x, y, &logs_sender)
+                {
+                    force_redraw = true;
+                }
+                if window.is_key_pressed(Key::A, minifb::KeyRepeat::No)
+                    && pane.level_to_alert(x, y, shift_pressed, &logs_sender)
+                {
+                    force_redraw = true;
+                }
+                if trading
+                    && window.is_key_pressed(Key::M, minifb::KeyRepeat::No)
+                    && pane.move_order(y)
+                {
+                    force_redraw = true;
+                }
+            }
+
+            if trading && window.is_key_pressed(Key::C, minifb::KeyRepeat::No) {
+                pane.cancel_all_orders();
+                force_redraw = true;
+            }
+
+            if window.is_key_pressed(Key::X, minifb::KeyRepeat::No) {
+                pane.clear_alerts(&logs_sender);
+                force_redraw = true;
+            }
         }
         left_was_pressed = left_pressed;
 
-        if window.is_key_pressed(Key::C, minifb::KeyRepeat::No) {
-            for o in trader.get_open_orders() {
-                exchange.cancel_order(o.clone());
-            }
-            alerts.clear();
-            force_redraw = true;
+        for pane in panes.iter_mut() {
+            pane.load_older_candles();
         }
 
-        if trader.bid.is_some() {
-            if let Some(sl_pnl) = config.sl_pnl
-                && !sl_triggered
-            {
-                if trader.get_pnl() < -sl_pnl.abs() {
-                    sl_triggered = true;
+        if let Some(new_symbol) = switch_to {
+            let pane = &mut panes[active];
+            if pane.trader.has_exposure() {
+                logs_sender
+                    .send(Log::new(
+                        LogLevel::Warning("SYMBOL".to_string(), Some(5)),
+                        format!(
+                            "Close the position and cancel orders on {} before switching symbol",
+                            pane.symbol.slug
+                        ),
+                        None,
+                    ))
+                    .ok();
+            } else {
+                match Pane::start(
+                    &config,
+                    &new_symbol,
+                    pane.interval,
+                    pane.area,
+                    logs_sender.clone(),
+                ) {
+                    Ok(new_pane) => {
+                        pane.stop();
+                        *pane = new_pane;
+                        window.set_title(&window_title(pane));
+                        force_redraw = true;
 
-                    trader.flat();
-                    consume_orders(&mut trader);
-                    for o in trader.get_open_orders() {
-                        exchange.cancel_order(o.clone());
+                        logs_sender
+                            .send(Log::new(
+                                LogLevel::Info,
+                                format!("Switched to {}", pane.symbol.slug),
+                                None,
+                            ))
+                            .ok();
+                    }
+                    Err(e) => {
+                        logs_sender
+                            .send(Log::new(
+                                LogLevel::Warning("SYMBOL".to_string(), Some(5)),
+                                format!("Failed to switch to {}: {}", new_symbol, e),
+                                None,
+                            ))
+                            .ok();
                     }
-                    consume_orders(&mut trader);
-                    trader.flat();
-                    force_redraw = true;
-
-                    logs_sender
-                        .send(Log::new(
-                            LogLevel::Error("SL".to_string()),
-                            format!("Stop-loss triggered at pnl: {:.2}", trader.get_pnl()),
-                            None,
-                        ))
-                        .unwrap()
                 }
             }
         }
 
+        for pane in panes.iter_mut() {
+            force_redraw = pane.check_stop_loss(&config, &logs_sender) || force_redraw;
+        }
+
         let (window_width, window_height) = window.get_size();
-        renderer.set_size(window_width, window_height);
-        renderer.render(
-            &shared_state,
-            &trader,
-            logs_manager.status(),
-            interval,
-            &alerts,
-            ctrl_pressed,
-            force_redraw,
-        );
-
-        let pixels_buffer: Vec<u32> = renderer.to_pixes_buffer();
+        let areas = Layout::grid(window_width as i32, window_height as i32, panes.len());
+        let status = logs_manager.status();
+        let multiple_panes = panes.len() > 1;
+        for (i, (pane, area)) in panes.iter_mut().zip(areas).enumerate() {
+            pane.set_area(area);
+            pane.renderer.set_layout_config(&config.layout);
+            let cursor = mouse_pos
+                .filter(|_| hovered == Some(i))
+                .map(|(x, y)| pane.local_pos(x, y));
+            pane.renderer.set_cursor(cursor);
+            pane.renderer.set_highlighted(multiple_panes && i == active);
+            pane.render(status.clone(), ctrl_pressed && i == active, force_redraw);
+        }
+
+        if let Some(search) = symbol_search.as_ref() {
+            panes[active].renderer.draw_symbol_search(search);
+        } else if log_console {
+            panes[active]
+                .renderer
+                .draw_log_console(logs_manager.history(), &status);
+        }
+
+        let mut pixels_buffer: Vec<u32> = vec![0; window_width * window_height];
+        for pane in panes.iter() {
+            let area = pane.area;
+            let pane_buffer = pane.renderer.to_pixes_buffer();
+            for row in 0..area.height as usize {
+                let src = row * area.width as usize;
+                let dst = (area.top as usize + row) * window_width + area.left as usize;
+                pixels_buffer[dst..dst + area.width as usize]
+                    .copy_from_slice(&pane_buffer[src..src + area.width as usize]);
+            }
+        }
         window
             .update_with_buffer(&pixels_buffer, window_width, window_height)
             .unwrap();
@@ -306,19 +574,62 @@ fn main() {
         force_redraw = false;
     }
 
-    if config.cleanup_on_shutdown {
-        trader.flat();
-        consume_orders(&mut trader);
-        for o in trader.get_open_orders() {
-            exchange.cancel_order(o.clone());
+    for pane in panes.iter_mut() {
+        if config.cleanup_on_shutdown {
+            pane.cleanup(&logs_sender);
         }
-        consume_orders(&mut trader);
-        trader.flat();
+        pane.stop();
     }
 
-    exchange.stop();
-
     logs_manager.consume();
 
     allow_sleep();
 }
+
+fn window_title(pane: &Pane) -> String {
+    format!("{} - {}", pane.symbol.slug, pane.exchange.name())
+}
+
+fn key_to_char(key: Key) -> Option<char> {
+    let c = match key {
+        Key::A => 'A',
+        Key::B => 'B',
+        Key::C => 'C',
+        Key::D => 'D',
+        Key::E => 'E',
+        Key::F => 'F',
+        Key::G => 'G',
+        Key::H => 'H',
+        Key::I => 'I',
+        Key::J => 'J',
+        Key::K => 'K',
+        Key::L => 'L',
+        Key::M => 'M',
+        Key::N => 'N',
+        Key::O => 'O',
+        Key::P => 'P',
+        Key::Q => 'Q',
+        Key::R => 'R',
+        Key::S => 'S',
+        Key::T => 'T',
+        Key::U => 'U',
+        Key::V => 'V',
+        Key::W => 'W',
+        Key::X => 'X',
+        Key::Y => 'Y',
+        Key::Z => 'Z',
+        Key::Key0 | Key::NumPad0 => '0',
+        Key::Key1 | Key::NumPad1 => '1',
+        Key::Key2 | Key::NumPad2 => '2',
+        Key::Key3 | Key::NumPad3 => '3',
+        Key::Key4 | Key::NumPad4 => '4',
+        Key::Key5 | Key::NumPad5 => '5',
+        Key::Key6 | Key::NumPad6 => '6',
+        Key::Key7 | Key::NumPad7 => '7',
+        Key::Key8 | Key::NumPad8 => '8',
+        Key::Key9 | Key::NumPad9 => '9',
+        Key::Minus => '_',
+        _ => return None,
+    };
+    Some(c)
+}
@@ -1,34 +1,56 @@
 mod alerts;
+mod big_trades;
 mod candles;
+mod capabilities;
 mod color;
 mod color_schema;
 mod config;
+mod drawings;
+mod indicators;
 mod interval;
+mod latency;
 mod layout;
+mod log_file;
 mod logs;
+mod notifications;
 mod open_interest;
 mod order_book;
+mod order_book_history;
 mod order_flow;
 mod orders;
+mod session_recording;
 mod shared_state;
 mod sound;
 mod symbol;
+mod symbol_search;
+mod tape_sound;
 mod timestamp;
 
-pub use alerts::{AlertTriggerType, Alerts};
+pub use alerts::{Alert, AlertCondition, AlertContext, Alerts};
+pub use big_trades::BigTrades;
 pub use candles::{Candle, CandlesState, SharedCandlesState};
+pub use capabilities::Capabilities;
 pub use color::Color;
 pub use color_schema::ColorSchema;
-pub use config::Config;
+pub use config::{ClockConfig, Config};
+pub use drawings::{Anchor, Drawing, Drawings};
+pub use indicators::Indicators;
 pub use interval::Interval;
-pub use layout::Layout;
+pub use latency::{Health, LatencyConfig, OrderRoundTrip, RateLimitStatus};
+pub use layout::{Area, Layout, LayoutConfig, Panel};
+pub use log_file::LogFile;
 pub use logs::Status;
 pub use logs::{Log, LogLevel, LogManager};
+pub use notifications::{NotificationConfig, NotificationEvent, NotificationSink, WebhookFormat};
 pub use open_interest::{OpenInterestState, SharedOpenInterestState};
 pub use order_book::{OrderBookState, SharedOrderBookState};
-pub use order_flow::{OrderFlowState, SharedOrderFlowState};
+pub use order_book_history::OrderBookHistory;
+pub use order_flow::{OrderFlowState, SharedOrderFlowState, Trade};
 pub use orders::{NewOrder, Order, OrderSide, OrderStatus, OrderType, Orders};
+pub use session_recording::{SessionRecorder, replay_session};
 pub use shared_state::SharedState;
-pub use sound::Sound;
+pub use sound::{Sound, SoundPlayer};
 pub use symbol::Symbol;
+pub use symbol_search::SymbolSearch;
+pub use tape_sound::TapeSound;
 pub use timestamp::Timestamp;
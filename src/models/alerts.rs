@@ -1,52 +1,322 @@
+use super::candles::Candle;
+use super::timestamp::Timestamp;
 use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
 
-#[derive(Copy, Clone)]
-pub enum AlertTriggerType {
-    Gte,
-    Lte,
+// the longest window of the percent move and open interest alerts
+const MAX_HISTORY_SECONDS: u64 = 3600;
+// the alerts file is checked for changes at most this often
+const FILE_CHECK_INTERVAL_MS: u64 = 1000;
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum AlertCondition {
+    /// Ask at or above the price.
+    Gte {
+        price: Decimal,
+    },
+    /// Bid at or below the price.
+    Lte {
+        price: Decimal,
+    },
+    CloseAbove {
+        price: Decimal,
+    },
+    CloseBelow {
+        price: Decimal,
+    },
+    /// Mid price crosses the price in either direction.
+    Cross {
+        price: Decimal,
+    },
+    /// Mid price moves by `percent` (up or down) within `seconds`.
+    PercentMove {
+        percent: Decimal,
+        seconds: u64,
+    },
+    /// Volume of the current candle reaches `multiplier` times the average volume.
+    VolumeSpike {
+        multiplier: Decimal,
+    },
+    /// Open interest changes by `percent` (up or down) within `seconds`.
+    OpenInterestChange {
+        percent: Decimal,
+        seconds: u64,
+    },
+    /// Spread reaches `ticks`.
+    SpreadAbove {
+        ticks: Decimal,
+    },
+}
+
+impl AlertCondition {
+    /// The price of the alert line on the chart.
+    pub fn price(&self) -> Option<Decimal> {
+        match self {
+            AlertCondition::Gte { price }
+            | AlertCondition::Lte { price }
+            | AlertCondition::CloseAbove { price }
+            | AlertCondition::CloseBelow { price }
+            | AlertCondition::Cross { price } => Some(*price),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for AlertCondition {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AlertCondition::Gte { price } => write!(f, "ask >= {}", price),
+            AlertCondition::Lte { price } => write!(f, "bid <= {}", price),
+            AlertCondition::CloseAbove { price } => write!(f, "candle closed above {}", price),
+            AlertCondition::CloseBelow { price } => write!(f, "candle closed below {}", price),
+            AlertCondition::Cross { price } => write!(f, "price crossed {}", price),
+            AlertCondition::PercentMove { percent, seconds } => {
+                write!(f, "price moved {}% in {}s", percent, seconds)
+            }
+            AlertCondition::VolumeSpike { multiplier } => {
+                write!(f, "volume {}x the average", multiplier)
+            }
+            AlertCondition::OpenInterestChange { percent, seconds } => {
+                write!(f, "open interest changed {}% in {}s", percent, seconds)
+            }
+            AlertCondition::SpreadAbove { ticks } => write!(f, "spread >= {} ticks", ticks),
+        }
+    }
 }
 
-#[derive(Copy, Clone)]
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
 pub struct Alert {
-    pub trigger_type: AlertTriggerType,
-    pub price: Decimal,
+    #[serde(flatten)]
+    pub condition: AlertCondition,
+    // a repeating alert triggers again once its condition was false
+    #[serde(default)]
+    pub repeat: bool,
+    // the condition is true since the last trigger
+    #[serde(skip)]
+    active: bool,
 }
 
+impl Alert {
+    pub fn new(condition: AlertCondition, repeat: bool) -> Self {
+        Self {
+            condition,
+            repeat,
+            active: false,
+        }
+    }
+
+    /// Triggers when the price moves from `bid` to `price`.
+    pub fn price(price: Decimal, bid: Decimal) -> Self {
+        Self::new(
+            if bid >= price {
+                AlertCondition::Lte { price }
+            } else {
+                AlertCondition::Gte { price }
+            },
+            false,
+        )
+    }
+}
+
+/// Market data the alerts are checked against.
+pub struct AlertContext {
+    pub time: Timestamp,
+    pub bid: Decimal,
+    pub ask: Decimal,
+    pub tick_size: Decimal,
+    pub last_closed_candle: Option<Candle>,
+    pub current_candle: Option<Candle>,
+    // volume of the previous candles
+    pub average_volume: Option<Decimal>,
+    pub open_interest: Option<Decimal>,
+}
+
+/// Alerts of a symbol, stored in `$HOME/.scalper-rs/alerts/<exchange>-<symbol>.json`.
 pub struct Alerts {
     pub alerts: Vec<Alert>,
     pub last_triggered: Option<Alert>,
+    path: Option<PathBuf>,
+    // modification time of the file when it was last read or written
+    modified: Option<SystemTime>,
+    checked_at: Timestamp,
+    // (time, mid price, open interest), one sample per second
+    history: VecDeque<(Timestamp, Decimal, Option<Decimal>)>,
+    last_mid: Option<Decimal>,
+    last_closed_time: Option<Timestamp>,
 }
 
 impl Alerts {
+    /// Alerts that are not saved.
     pub fn new() -> Self {
         Self {
             alerts: Vec::<Alert>::new(),
             last_triggered: None,
+            path: None,
+            modified: None,
+            checked_at: Timestamp::from(0),
+            history: VecDeque::new(),
+            last_mid: None,
+            last_closed_time: None,
         }
     }
 
-    pub fn add_alert(&mut self, price: Decimal, trigger_type: AlertTriggerType) {
-        let alert = Alert {
-            trigger_type,
-            price,
+    pub fn load(exchange: &str, symbol: &str) -> Result<Self, Box<dyn std::error::Error>> {
+        let home = dirs::home_dir().ok_or("No home directory.")?;
+        let path = home
+            .join(".scalper-rs")
+            .join("alerts")
+            .join(format!("{}-{}.json", exchange, symbol));
+        Self::load_from(&path)
+    }
+
+    /// Reads the alerts file, a missing file means no alerts.
+    pub fn load_from(path: &Path) -> Result<Self, Box<dyn std::error::Error>> {
+        let modified = file_modified(path);
+        Ok(Self {
+            alerts: read_alerts(path)?,
+            path: Some(path.to_path_buf()),
+            modified,
+            ..Self::new()
+        })
+    }
+
+    pub fn save(&mut self) -> Result<(), Box<dyn std::error::Error>> {
+        let Some(path) = &self.path else {
+            return Ok(());
+        };
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(path, serde_json::to_string_pretty(&self.alerts)?)?;
+        self.modified = file_modified(path);
+        Ok(())
+    }
+
+    /// Reads the file again if it was changed since it was last read or written, e.g. edited by hand.
+    /// Alerts that are still in the file keep their state. Returns true if the alerts were reloaded.
+    pub fn reload(&mut self) -> Result<bool, Box<dyn std::error::Error>> {
+        let Some(path) = &self.path else {
+            return Ok(false);
         };
+        let modified = file_modified(path);
+        if modified == self.modified {
+            return Ok(false);
+        }
+        // an invalid file is reported once, not on every check
+        self.modified = modified;
+        let mut alerts = read_alerts(path)?;
+        for alert in alerts.iter_mut() {
+            alert.active = self
+                .alerts
+                .iter()
+                .any(|old| old.active && old.condition == alert.condition);
+        }
+        self.alerts = alerts;
+        Ok(true)
+    }
+
+    /// `reload` at most once per `FILE_CHECK_INTERVAL_MS`.
+    pub fn check_file(&mut self, now: Timestamp) -> Result<bool, Box<dyn std::error::Error>> {
+        if now.milliseconds() < self.checked_at.milliseconds() + FILE_CHECK_INTERVAL_MS {
+            return Ok(false);
+        }
+        self.checked_at = now;
+        self.reload()
+    }
+
+    pub fn add_alert(&mut self, alert: Alert) {
         self.alerts.push(alert);
     }
 
-    pub fn scan(&mut self, bid: Decimal, ask: Decimal) -> Vec<Alert> {
+    /// Returns the triggered alerts, one-shot alerts are removed.
+    pub fn scan(&mut self, context: &AlertContext) -> Vec<Alert> {
+        let mid = (context.bid + context.ask) / Decimal::TWO;
+        let previous_mid = self.last_mid.replace(mid);
+        // the candle closed since the last scan
+        let closed = context
+            .last_closed_candle
+            .filter(|candle| self.last_closed_time.is_some_and(|t| candle.open_time > t));
+        if let Some(candle) = context.last_closed_candle {
+            self.last_closed_time = Some(candle.open_time);
+        }
+
+        if self
+            .history
+            .back()
+            .is_none_or(|(time, _, _)| time.seconds() < context.time.seconds())
+        {
+            self.history
+                .push_back((context.time, mid, context.open_interest));
+        }
+        while let Some((time, _, _)) = self.history.front()
+            && time.seconds() + MAX_HISTORY_SECONDS < context.time.seconds()
+        {
+            self.history.pop_front();
+        }
+
         let mut triggered_alerts = Vec::new();
-        self.alerts.retain(|alert| {
-            let triggered = match alert.trigger_type {
-                AlertTriggerType::Gte => ask >= alert.price,
-                AlertTriggerType::Lte => bid <= alert.price,
+        let history = &self.history;
+        self.alerts.retain_mut(|alert| {
+            let condition = match alert.condition {
+                AlertCondition::Gte { price } => Some(context.ask >= price),
+                AlertCondition::Lte { price } => Some(context.bid <= price),
+                AlertCondition::CloseAbove { price } => closed.map(|c| c.close > price),
+                AlertCondition::CloseBelow { price } => closed.map(|c| c.close < price),
+                AlertCondition::Cross { price } => Some(
+                    previous_mid.is_some_and(|prev| (prev < price) != (mid < price) && prev != mid),
+                ),
+                AlertCondition::PercentMove { percent, seconds } => {
+                    let oldest = history
+                        .iter()
+                        .find(|(time, _, _)| time.seconds() + seconds >= context.time.seconds())
+                        .map(|(_, mid, _)| *mid);
+                    Some(oldest.is_some_and(|old| exceeds_percent(old, mid, percent)))
+                }
+                AlertCondition::VolumeSpike { multiplier } => Some(
+                    context
+                        .current_candle
+                        .zip(context.average_volume)
+                        .is_some_and(|(c, avg)| {
+                            avg > Decimal::ZERO && c.volume >= avg * multiplier
+                        }),
+                ),
+                AlertCondition::OpenInterestChange { percent, seconds } => {
+                    let oldest = history
+                        .iter()
+                        .filter(|(time, _, _)| time.seconds() + seconds >= context.time.seconds())
+                        .find_map(|(_, _, oi)| *oi);
+                    Some(
+                        oldest
+                            .zip(context.open_interest)
+                            .is_some_and(|(old, oi)| exceeds_percent(old, oi, percent)),
+                    )
+                }
+                AlertCondition::SpreadAbove { ticks } => {
+                    Some(context.ask - context.bid >= ticks * context.tick_size)
+                }
+            };
+
+            // None: nothing to check until the next candle closes
+            let Some(condition) = condition else {
+                return true;
             };
+            let triggered = condition && !alert.active;
+            alert.active = condition;
             if triggered {
-                self.last_triggered = Some(alert.clone());
-                triggered_alerts.push(alert.clone());
+                triggered_alerts.push(*alert);
             }
-            !triggered
+            !triggered || alert.repeat
         });
 
+        if let Some(alert) = triggered_alerts.last() {
+            self.last_triggered = Some(*alert);
+        }
         triggered_alerts
     }
 
@@ -56,46 +326,217 @@ impl Alerts {
     }
 }
 
+/// A missing file means no alerts.
+fn read_alerts(path: &Path) -> Result<Vec<Alert>, Box<dyn std::error::Error>> {
+    match fs::read_to_string(path) {
+        Ok(contents) => Ok(serde_json::from_str(&contents)
+            .map_err(|e| format!("Invalid alerts file {}: {}", path.display(), e))?),
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => Ok(Vec::new()),
+        Err(err) => Err(err.into()),
+    }
+}
+
+fn file_modified(path: &Path) -> Option<SystemTime> {
+    fs::metadata(path).and_then(|m| m.modified()).ok()
+}
+
+fn exceeds_percent(from: Decimal, to: Decimal, percent: Decimal) -> bool {
+    !from.is_zero() && ((to - from) / from * Decimal::ONE_HUNDRED).abs() >= percent
+}
+
 #[cfg(test)]
 mod tests {
-    use super::{AlertTriggerType, Alerts};
+    use super::{Alert, AlertCondition, AlertContext, Alerts};
+    use crate::models::{Candle, Timestamp};
     use rust_decimal::Decimal;
 
+    fn context(seconds: u64, bid: i64, ask: i64) -> AlertContext {
+        AlertContext {
+            time: Timestamp::from_seconds(seconds),
+            bid: Decimal::from(bid),
+            ask: Decimal::from(ask),
+            tick_size: Decimal::ONE,
+            last_closed_candle: None,
+            current_candle: None,
+            average_volume: None,
+            open_interest: None,
+        }
+    }
+
+    fn candle(seconds: u64, close: i64, volume: i64) -> Candle {
+        Candle {
+            open_time: Timestamp::from_seconds(seconds),
+            open: Decimal::from(close),
+            high: Decimal::from(close),
+            low: Decimal::from(close),
+            close: Decimal::from(close),
+            volume: Decimal::from(volume),
+        }
+    }
+
     #[test]
     fn test_scan_gte_triggered() {
         let mut alerts = Alerts::new();
-        alerts.add_alert(Decimal::from(100), AlertTriggerType::Gte);
+        alerts.add_alert(Alert::price(Decimal::from(100), Decimal::from(90)));
 
-        let triggered = alerts.scan(Decimal::from(90), Decimal::from(110));
+        let triggered = alerts.scan(&context(0, 90, 110));
 
         assert_eq!(triggered.len(), 1);
-        assert_eq!(triggered[0].price, Decimal::from(100));
+        assert_eq!(triggered[0].condition.price(), Some(Decimal::from(100)));
         assert!(alerts.alerts.is_empty());
-        assert_eq!(alerts.last_triggered.unwrap().price, Decimal::from(100));
+        assert_eq!(
+            alerts.last_triggered.unwrap().condition.price(),
+            Some(Decimal::from(100))
+        );
     }
 
     #[test]
     fn test_scan_lte_triggered() {
         let mut alerts = Alerts::new();
-        alerts.add_alert(Decimal::from(50), AlertTriggerType::Lte);
+        alerts.add_alert(Alert::price(Decimal::from(50), Decimal::from(60)));
 
-        let triggered = alerts.scan(Decimal::from(40), Decimal::from(60));
+        let triggered = alerts.scan(&context(0, 40, 60));
 
         assert_eq!(triggered.len(), 1);
-        assert_eq!(triggered[0].price, Decimal::from(50));
+        assert_eq!(triggered[0].condition.price(), Some(Decimal::from(50)));
         assert!(alerts.alerts.is_empty());
-        assert_eq!(alerts.last_triggered.unwrap().price, Decimal::from(50));
+        assert_eq!(
+            alerts.last_triggered.unwrap().condition.price(),
+            Some(Decimal::from(50))
+        );
     }
 
     #[test]
     fn test_clear() {
         let mut alerts = Alerts::new();
-        alerts.add_alert(Decimal::from(100), AlertTriggerType::Gte);
-        alerts.add_alert(Decimal::from(50), AlertTriggerType::Lte);
+        alerts.add_alert(Alert::price(Decimal::from(100), Decimal::from(90)));
+        alerts.add_alert(Alert::price(Decimal::from(50), Decimal::from(90)));
 
         alerts.clear();
 
         assert!(alerts.alerts.is_empty());
         assert!(alerts.last_triggered.is_none());
     }
+
+    #[test]
+    fn test_repeating_cross() {
+        let mut alerts = Alerts::new();
+        alerts.add_alert(Alert::new(
+            AlertCondition::Cross {
+                price: Decimal::from(100),
+            },
+            true,
+        ));
+
+        assert!(alerts.scan(&context(0, 98, 98)).is_empty());
+        assert_eq!(alerts.scan(&context(1, 101, 101)).len(), 1);
+        assert!(alerts.scan(&context(2, 102, 102)).is_empty());
+        assert_eq!(alerts.scan(&context(3, 99, 99)).len(), 1);
+        assert_eq!(alerts.alerts.len(), 1);
+    }
+
+    #[test]
+    fn test_close_above() {
+        let mut alerts = Alerts::new();
+        alerts.add_alert(Alert::new(
+            AlertCondition::CloseAbove {
+                price: Decimal::from(100),
+            },
+            false,
+        ));
+
+        // the candle closed before the alerts were loaded is skipped
+        let mut ctx = context(60, 101, 101);
+        ctx.last_closed_candle = Some(candle(0, 105, 1));
+        assert!(alerts.scan(&ctx).is_empty());
+
+        ctx.time = Timestamp::from_seconds(120);
+        ctx.last_closed_candle = Some(candle(60, 99, 1));
+        assert!(alerts.scan(&ctx).is_empty());
+
+        ctx.time = Timestamp::from_seconds(180);
+        ctx.last_closed_candle = Some(candle(120, 101, 1));
+        assert_eq!(alerts.scan(&ctx).len(), 1);
+        assert!(alerts.alerts.is_empty());
+    }
+
+    #[test]
+    fn test_percent_move_and_volume_spike() {
+        let mut alerts = Alerts::new();
+        alerts.add_alert(Alert::new(
+            AlertCondition::PercentMove {
+                percent: Decimal::from(2),
+                seconds: 60,
+            },
+            false,
+        ));
+        alerts.add_alert(Alert::new(
+            AlertCondition::VolumeSpike {
+                multiplier: Decimal::from(3),
+            },
+            false,
+        ));
+
+        assert!(alerts.scan(&context(0, 100, 100)).is_empty());
+        // 2% move, but outside of the window
+        assert!(alerts.scan(&context(100, 102, 102)).is_empty());
+        let mut ctx = context(110, 105, 105);
+        ctx.current_candle = Some(candle(60, 105, 30));
+        ctx.average_volume = Some(Decimal::from(10));
+        let triggered = alerts.scan(&ctx);
+        assert_eq!(triggered.len(), 2);
+        assert!(alerts.alerts.is_empty());
+    }
+
+    #[test]
+    fn test_save_and_load() {
+        let path =
+            std::env::temp_dir().join(format!("scalper-alerts-test-{}.json", std::process::id()));
+        std::fs::remove_file(&path).ok();
+
+        let mut alerts = Alerts::load_from(&path).unwrap();
+        assert!(alerts.alerts.is_empty());
+        alerts.add_alert(Alert::new(
+            AlertCondition::SpreadAbove {
+                ticks: Decimal::from(5),
+            },
+            true,
+        ));
+        alerts.save().unwrap();
+
+        let loaded = Alerts::load_from(&path).unwrap();
+        assert_eq!(loaded.alerts, alerts.alerts);
+
+        std::fs::remove_file(&path).ok();
+    }
+
+    #[test]
+    fn test_reload_edited_file() {
+        let path =
+            std::env::temp_dir().join(format!("scalper-alerts-reload-{}.json", std::process::id()));
+        std::fs::remove_file(&path).ok();
+
+        let mut alerts = Alerts::load_from(&path).unwrap();
+        alerts.add_alert(Alert::price(Decimal::from(110), Decimal::from(100)));
+        alerts.save().unwrap();
+        assert!(!alerts.reload().unwrap());
+
+        std::fs::write(
+            &path,
+            r#"[{ "type": "gte", "price": "110" }, { "type": "cross", "price": "105", "repeat": true }]"#,
+        )
+        .unwrap();
+        // the modification time can be the same within the file system resolution
+        alerts.modified = None;
+        assert!(alerts.reload().unwrap());
+        assert_eq!(alerts.alerts.len(), 2);
+        assert!(alerts.alerts[1].repeat);
+
+        // the edit is kept by the next save
+        alerts.add_alert(Alert::price(Decimal::from(90), Decimal::from(100)));
+        alerts.save().unwrap();
+        assert_eq!(Alerts::load_from(&path).unwrap().alerts.len(), 3);
+
+        std::fs::remove_file(&path).ok();
+    }
 }
@@ -0,0 +1,169 @@
+use super::order_flow::Trade;
+use super::timestamp::Timestamp;
+use rust_decimal::Decimal;
+use std::collections::VecDeque;
+
+/// Detects trades above the notional threshold.
+/// Trades with the same price and side within `aggregate_ms` are treated as a single trade.
+pub struct BigTrades {
+    threshold: Decimal,
+    aggregate_ms: u64,
+    capacity: usize,
+    current: Option<Trade>,
+    current_detected: bool,
+    trades: VecDeque<Trade>,
+    pub updated: Timestamp,
+}
+
+impl BigTrades {
+    pub fn new(threshold: Decimal, aggregate_ms: u64, capacity: usize) -> Self {
+        Self {
+            threshold,
+            aggregate_ms,
+            capacity,
+            current: None,
+            current_detected: false,
+            trades: VecDeque::with_capacity(capacity),
+            updated: Timestamp::from(0),
+        }
+    }
+
+    pub fn threshold(&self) -> Decimal {
+        self.threshold
+    }
+
+    /// Returns the trade if it (or the aggregate it belongs to) just crossed the threshold.
+    pub fn consume(&mut self, trade: Trade) -> Option<Trade> {
+        let current = match self.current.take() {
+            Some(mut current)
+                if current.side == trade.side
+                    && current.price == trade.price
+                    && trade.time.milliseconds()
+                        <= current.time.milliseconds() + self.aggregate_ms =>
+            {
+                current.quantity += trade.quantity;
+                current.time = trade.time;
+                current
+            }
+            _ => {
+                self.current_detected = false;
+                trade
+            }
+        };
+
+        let mut detected = None;
+        if current.notional() >= self.threshold {
+            if self.current_detected {
+                // the aggregate was reported already, only update its size
+                if let Some(last) = self.trades.back_mut() {
+                    *last = current.clone();
+                }
+            } else {
+                if self.trades.len() == self.capacity {
+                    self.trades.pop_front();
+                }
+                self.trades.push_back(current.clone());
+                self.current_detected = true;
+                detected = Some(current.clone());
+            }
+            self.updated = Timestamp::now();
+        }
+
+        self.current = Some(current);
+
+        detected
+    }
+
+    pub fn trades(&self) -> &VecDeque<Trade> {
+        &self.trades
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::BigTrades;
+    use crate::models::{OrderSide, Timestamp, Trade};
+    use rust_decimal::Decimal;
+
+    fn trade(time: u64, price: i64, quantity: i64, side: OrderSide) -> Trade {
+        Trade {
+            time: Timestamp::from_milliseconds(time),
+            price: Decimal::from(price),
+            quantity: Decimal::from(quantity),
+            side,
+        }
+    }
+
+    #[test]
+    fn test_consume_single() {
+        let mut big_trades = BigTrades::new(Decimal::from(1000), 5, 10);
+
+        assert!(
+            big_trades
+                .consume(trade(0, 100, 5, OrderSide::Buy))
+                .is_none()
+        );
+        let detected = big_trades.consume(trade(100, 100, 10, OrderSide::Sell));
+
+        assert_eq!(detected.unwrap().quantity, Decimal::from(10));
+        assert_eq!(big_trades.trades().len(), 1);
+    }
+
+    #[test]
+    fn test_consume_aggregated() {
+        let mut big_trades = BigTrades::new(Decimal::from(1000), 5, 10);
+
+        assert!(
+            big_trades
+                .consume(trade(0, 100, 6, OrderSide::Buy))
+                .is_none()
+        );
+        assert!(
+            big_trades
+                .consume(trade(3, 100, 6, OrderSide::Buy))
+                .is_some()
+        );
+        // same aggregate, already reported
+        assert!(
+            big_trades
+                .consume(trade(5, 100, 6, OrderSide::Buy))
+                .is_none()
+        );
+
+        assert_eq!(big_trades.trades().len(), 1);
+        assert_eq!(big_trades.trades()[0].quantity, Decimal::from(18));
+    }
+
+    #[test]
+    fn test_consume_not_aggregated() {
+        let mut big_trades = BigTrades::new(Decimal::from(1000), 5, 10);
+
+        big_trades.consume(trade(0, 100, 6, OrderSide::Buy));
+        // too late
+        assert!(
+            big_trades
+                .consume(trade(10, 100, 6, OrderSide::Buy))
+                .is_none()
+        );
+        // another side
+        assert!(
+            big_trades
+                .consume(trade(11, 100, 6, OrderSide::Sell))
+                .is_none()
+        );
+
+        assert!(big_trades.trades().is_empty());
+    }
+
+    #[test]
+    fn test_capacity() {
+        let mut big_trades = BigTrades::new(Decimal::from(1000), 5, 2);
+
+        for i in 0..3 {
+            big_trades.consume(trade(i * 100, 100 + i as i64, 10, OrderSide::Buy));
+        }
+
+        assert_eq!(big_trades.trades().len(), 2);
+        assert_eq!(big_trades.trades()[0].price, Decimal::from(101));
+    }
+}
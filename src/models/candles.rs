@@ -1,10 +1,14 @@
 use super::interval::Interval;
+use super::latency::StreamStats;
 use super::timestamp::Timestamp;
 use rust_decimal::Decimal;
 use rust_decimal::prelude::Zero;
 use serde::{Deserialize, Serialize};
 use std::sync::{Arc, RwLock};
 
+// limit for the buffer growth when older candles are loaded
+const MAX_CAPACITY: usize = 5000;
+
 #[derive(Debug, Clone, Copy, Serialize, Deserialize)]
 pub struct Candle {
     pub open_time: Timestamp,
@@ -26,9 +30,12 @@ pub struct CandlesState {
     head: usize,
     size: usize,
     capacity: usize,
+    generation: u64,
+    revision: u64,
     pub interval: Interval,
     pub online: bool,
     pub updated: Timestamp,
+    pub stats: StreamStats,
 }
 
 /// Circular buffer for candles
@@ -39,9 +46,12 @@ impl CandlesState {
             head: 0,
             size: 0,
             capacity,
+            generation: 0,
+            revision: 0,
             interval,
             online: false,
             updated: Timestamp::now(),
+            stats: StreamStats::default(),
         }
     }
 
@@ -49,7 +59,18 @@ impl CandlesState {
         self.capacity
     }
 
+    /// Incremented on every `clear`, lets consumers know the buffer was repopulated.
+    pub fn generation(&self) -> u64 {
+        self.generation
+    }
+
+    /// Incremented on every change, lets consumers skip the unchanged buffer.
+    pub fn revision(&self) -> u64 {
+        self.revision
+    }
+
     pub fn push(&mut self, candle: Candle) {
+        self.revision += 1;
         if let Some(last_candle) = self.last() {
             if last_candle.open_time == candle.open_time {
                 // Update existing candle
@@ -78,6 +99,52 @@ impl CandlesState {
         result
     }
 
+    /// Iterates the candles from the oldest without copying the buffer.
+    pub fn iter(&self) -> impl DoubleEndedIterator<Item = Candle> + '_ {
+        (0..self.size).filter_map(move |i| {
+            let index = (self.head + self.capacity - self.size + i) % self.capacity;
+            self.data[index]
+        })
+    }
+
+    /// Adds older candles to the beginning of the buffer, growing it up to `MAX_CAPACITY`.
+    pub fn prepend(&mut self, candles: Vec<Candle>) {
+        if self.size >= MAX_CAPACITY {
+            return;
+        }
+        let first_open_time = self.first().map(|c| c.open_time);
+        let mut all: Vec<Candle> = candles
+            .into_iter()
+            .filter(|c| first_open_time.is_none_or(|t| c.open_time < t))
+            .collect();
+        if all.is_empty() {
+            return;
+        }
+        all.extend(self.to_vec());
+        if all.len() > MAX_CAPACITY {
+            all.drain(..all.len() - MAX_CAPACITY);
+        }
+
+        self.capacity = self.capacity.max(all.len());
+        self.size = all.len();
+        self.head = self.size % self.capacity;
+        let mut data = vec![None; self.capacity];
+        for (i, candle) in all.into_iter().enumerate() {
+            data[i] = Some(candle);
+        }
+        self.data = data.into_boxed_slice();
+        self.generation += 1;
+        self.revision += 1;
+    }
+
+    pub fn first(&self) -> Option<Candle> {
+        if self.size == 0 {
+            return None;
+        }
+        let index = (self.head + self.capacity - self.size) % self.capacity;
+        self.data[index]
+    }
+
     pub fn last(&self) -> Option<Candle> {
         if self.size == 0 {
             return None;
@@ -90,6 +157,8 @@ impl CandlesState {
         self.data = vec![None; self.capacity].into_boxed_slice();
         self.head = 0;
         self.size = 0;
+        self.generation += 1;
+        self.revision += 1;
         self.interval = interval;
         self.online = false;
         self.updated = Timestamp::now();
@@ -199,6 +268,39 @@ mod tests {
         assert_eq!(last_candle.open_time, candle5.open_time);
     }
 
+    #[test]
+    fn test_prepend() {
+        let mut buffer = CandlesState::new(3, Interval::M1);
+
+        buffer.push(create_candle(3, "3", "3", "3", "3", "1"));
+        buffer.push(create_candle(4, "4", "4", "4", "4", "1"));
+        let generation = buffer.generation();
+
+        buffer.prepend(vec![
+            create_candle(1, "1", "1", "1", "1", "1"),
+            create_candle(2, "2", "2", "2", "2", "1"),
+            // overlaps with the buffer
+            create_candle(3, "5", "5", "5", "5", "1"),
+        ]);
+
+        let vec = buffer.to_vec();
+        assert_eq!(vec.len(), 4);
+        assert_eq!(buffer.capacity(), 4);
+        assert_eq!(vec[0].open_time, Timestamp::from_milliseconds(1));
+        assert_eq!(vec[2].open, Decimal::from(3));
+        assert_eq!(buffer.first().unwrap().open_time, vec[0].open_time);
+        assert_ne!(buffer.generation(), generation);
+
+        buffer.push(create_candle(5, "5", "5", "5", "5", "1"));
+        let vec = buffer.to_vec();
+        assert_eq!(vec.len(), 4);
+        assert_eq!(vec[0].open_time, Timestamp::from_milliseconds(2));
+        assert_eq!(
+            buffer.last().unwrap().open_time,
+            Timestamp::from_milliseconds(5)
+        );
+    }
+
     #[test]
     fn test_to_candle() {
         let mut buffer = CandlesState::new(5, Interval::M1);
@@ -0,0 +1,31 @@
+/// Features of an exchange, the UI hides or disables what is not supported.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Capabilities {
+    // market and limit orders, data-only exchanges have no order UI
+    pub trading: bool,
+    pub stop_orders: bool,
+    pub trailing_stop: bool,
+    // moving an open limit order to another price
+    pub modify_orders: bool,
+    // `OpenInterestState` is populated
+    pub open_interest: bool,
+    // selling more than the position, false on spot
+    pub shorting: bool,
+    // the position is loaded from the exchange on start
+    pub position_fetch: bool,
+    pub ws_orders: bool,
+}
+
+impl Capabilities {
+    /// Market data only.
+    pub const DATA_ONLY: Self = Self {
+        trading: false,
+        stop_orders: false,
+        trailing_stop: false,
+        modify_orders: false,
+        open_interest: false,
+        shorting: false,
+        position_fetch: false,
+        ws_orders: false,
+    };
+}
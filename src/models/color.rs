@@ -1,6 +1,8 @@
 use raqote::SolidSource;
+use serde::{Deserialize, Serialize};
 
-#[derive(Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
 pub struct Color {
     r: u8,
     g: u8,
@@ -12,6 +14,39 @@ impl Color {
     pub fn new(r: u8, g: u8, b: u8, a: u8) -> Self {
         Self { r, g, b, a }
     }
+
+    pub fn alpha(&self) -> u8 {
+        self.a
+    }
+
+    pub fn with_alpha(&self, a: u8) -> Self {
+        Self { a, ..*self }
+    }
+
+    /// Parses `#RRGGBB` or `#RRGGBBAA`.
+    pub fn from_hex(hex: &str) -> Result<Self, String> {
+        let digits = hex.trim().trim_start_matches('#');
+        if digits.len() != 6 && digits.len() != 8 {
+            return Err(format!("Invalid color: {}", hex));
+        }
+        let component = |i: usize| {
+            u8::from_str_radix(&digits[i..i + 2], 16).map_err(|_| format!("Invalid color: {}", hex))
+        };
+        Ok(Self {
+            r: component(0)?,
+            g: component(2)?,
+            b: component(4)?,
+            a: if digits.len() == 8 {
+                component(6)?
+            } else {
+                255
+            },
+        })
+    }
+
+    pub fn to_hex(self) -> String {
+        format!("#{:02X}{:02X}{:02X}{:02X}", self.r, self.g, self.b, self.a)
+    }
 }
 
 impl From<Color> for SolidSource {
@@ -19,3 +54,41 @@ impl From<Color> for SolidSource {
         SolidSource::from_unpremultiplied_argb(color.a, color.r, color.g, color.b)
     }
 }
+
+impl TryFrom<String> for Color {
+    type Error = String;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        Color::from_hex(&value)
+    }
+}
+
+impl From<Color> for String {
+    fn from(color: Color) -> Self {
+        color.to_hex()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Color;
+
+    #[test]
+    fn test_from_hex() {
+        assert_eq!(
+            Color::from_hex("#22C55E").unwrap(),
+            Color::new(34, 197, 94, 255)
+        );
+        assert_eq!(
+            Color::from_hex("22c55e80").unwrap(),
+            Color::new(34, 197, 94, 128)
+        );
+        assert!(Color::from_hex("#22C55").is_err());
+        assert!(Color::from_hex("#GGC55E").is_err());
+    }
+
+    #[test]
+    fn test_to_hex() {
+        assert_eq!(Color::new(34, 197, 94, 128).to_hex(), "#22C55E80");
+    }
+}
@@ -1,13 +1,17 @@
 use crate::models::Color;
 use chrono::{Local, Timelike};
 use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::PathBuf;
 
-#[derive(Debug, Serialize, Deserialize, Clone, Copy)]
-#[serde(rename_all = "lowercase")]
+/// `dark`, `light`, `auto` or the name of a theme in `~/.scalper-rs/themes/<name>.toml`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(from = "String", into = "String")]
 pub enum Theme {
     Light,
     Dark,
     Auto,
+    Custom(String),
 }
 
 impl Default for Theme {
@@ -16,6 +20,29 @@ impl Default for Theme {
     }
 }
 
+impl From<String> for Theme {
+    fn from(value: String) -> Self {
+        match value.to_lowercase().as_str() {
+            "light" => Theme::Light,
+            "dark" => Theme::Dark,
+            "auto" => Theme::Auto,
+            _ => Theme::Custom(value),
+        }
+    }
+}
+
+impl From<Theme> for String {
+    fn from(theme: Theme) -> Self {
+        match theme {
+            Theme::Light => "light".to_string(),
+            Theme::Dark => "dark".to_string(),
+            Theme::Auto => "auto".to_string(),
+            Theme::Custom(name) => name,
+        }
+    }
+}
+
+#[derive(Clone, Serialize, Deserialize)]
 pub struct ColorSchema {
     pub theme: Theme,
 
@@ -30,6 +57,7 @@ pub struct ColorSchema {
     pub volume_sell: Color,
     pub open_interest: Color,
     pub alert: Color,
+    pub heatmap: Color,
 
     pub text_light: Color,
     pub text_dark: Color,
@@ -40,7 +68,10 @@ pub struct ColorSchema {
     pub border: Color,
     pub crosshair: Color,
     pub scale_bar: Color,
+    pub grid: Color,
     pub sl_line: Color,
+    // levels, trend lines and zones, zones are filled with a lower alpha
+    pub drawing: Color,
 
     pub status_ok: Color,
     pub status_warning: Color,
@@ -71,6 +102,8 @@ impl ColorSchema {
 
             alert: Color::new(255, 255, 255, 128), // #FFFFFF at 50% opacity
 
+            heatmap: Color::new(245, 158, 11, 255), // #F59E0B, alpha is scaled by size
+
             // Text colors
             text_light: Color::new(229, 231, 235, 255), // #E5E7EB
             text_dark: Color::new(31, 41, 55, 255),     // #1F2937
@@ -82,7 +115,9 @@ impl ColorSchema {
             border: Color::new(55, 65, 81, 255),       // #374151
             crosshair: Color::new(156, 163, 175, 255), // #9CA3AF
             scale_bar: Color::new(139, 92, 246, 255),  // #8B5CF6
+            grid: Color::new(31, 41, 55, 255),         // #1F2937
             sl_line: Color::new(220, 38, 38, 255),     // #DC2626
+            drawing: Color::new(56, 189, 248, 255),    // #38BDF8
 
             // status
             status_ok: Color::new(16, 185, 129, 255), // #10B981
@@ -114,6 +149,8 @@ impl ColorSchema {
 
             alert: Color::new(0, 0, 0, 128), // #000000 at 50% opacity
 
+            heatmap: Color::new(37, 99, 235, 255), // #2563EB, alpha is scaled by size
+
             // Text colors
             text_light: Color::new(31, 41, 55, 255), // #1F2937
             text_dark: Color::new(249, 250, 251, 255), // #F9FAFB
@@ -125,7 +162,9 @@ impl ColorSchema {
             border: Color::new(220, 220, 220, 255), // #DCDCDC
             crosshair: Color::new(107, 114, 128, 255), // #6B7280
             scale_bar: Color::new(139, 92, 246, 255), // #8B5CF6
+            grid: Color::new(243, 244, 246, 255),   // #F3F4F6
             sl_line: Color::new(220, 38, 38, 255),  // #DC2626
+            drawing: Color::new(2, 132, 199, 255),  // #0284C7
 
             // status
             status_ok: Color::new(5, 150, 105, 255), // #059669
@@ -134,10 +173,75 @@ impl ColorSchema {
         }
     }
 
-    pub fn for_theme(theme: Theme) -> Self {
+    /// Loads a built-in theme or a theme file.
+    pub fn load(theme: &Theme) -> Result<Self, String> {
+        match theme {
+            Theme::Custom(name) => {
+                let path = themes_dir()
+                    .ok_or("No home directory.")?
+                    .join(format!("{}.toml", name));
+                let contents = fs::read_to_string(&path)
+                    .map_err(|e| format!("Failed to read theme {}: {}", path.display(), e))?;
+                let mut schema = Self::from_theme_toml(&contents)
+                    .map_err(|e| format!("Invalid theme {}: {}", path.display(), e))?;
+                schema.theme = theme.clone();
+                Ok(schema)
+            }
+            _ => Ok(Self::builtin(theme)),
+        }
+    }
+
+    /// Built-in themes followed by the theme files, in the order they are cycled.
+    pub fn available_themes() -> Vec<Theme> {
+        let mut names: Vec<String> = themes_dir()
+            .and_then(|dir| fs::read_dir(dir).ok())
+            .map(|entries| {
+                entries
+                    .filter_map(|entry| entry.ok().map(|e| e.path()))
+                    .filter(|path| path.extension().is_some_and(|ext| ext == "toml"))
+                    .filter_map(|path| Some(path.file_stem()?.to_str()?.to_string()))
+                    .collect()
+            })
+            .unwrap_or_default();
+        names.sort();
+
+        let mut themes = vec![Theme::Dark, Theme::Light];
+        themes.extend(names.into_iter().map(Theme::Custom));
+        themes
+    }
+
+    /// Theme file: an optional `base` theme (dark by default) and colors overriding its fields.
+    fn from_theme_toml(contents: &str) -> Result<Self, String> {
+        let mut overrides: toml::Table = toml::from_str(contents).map_err(|e| e.to_string())?;
+        let base = match overrides.remove("base") {
+            None => Theme::Dark,
+            Some(toml::Value::String(base)) => match Theme::from(base) {
+                Theme::Custom(name) => return Err(format!("Unknown base theme: {}", name)),
+                theme => theme,
+            },
+            Some(_) => return Err("base must be a string".to_string()),
+        };
+
+        let mut table = match toml::Value::try_from(Self::builtin(&base)) {
+            Ok(toml::Value::Table(table)) => table,
+            _ => return Err("Failed to serialize the base theme".to_string()),
+        };
+        for (name, value) in overrides {
+            if name == "theme" || !table.contains_key(&name) {
+                return Err(format!("Unknown color: {}", name));
+            }
+            table.insert(name, value);
+        }
+
+        toml::Value::Table(table)
+            .try_into()
+            .map_err(|e: toml::de::Error| e.to_string())
+    }
+
+    fn builtin(theme: &Theme) -> Self {
         match theme {
             Theme::Light => Self::light(),
-            Theme::Dark => Self::dark(),
+            Theme::Dark | Theme::Custom(_) => Self::dark(),
             Theme::Auto => {
                 let hour = Local::now().hour();
                 if hour >= 20 || hour < 6 {
@@ -149,3 +253,45 @@ impl ColorSchema {
         }
     }
 }
+
+fn themes_dir() -> Option<PathBuf> {
+    Some(dirs::home_dir()?.join(".scalper-rs").join("themes"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ColorSchema, Theme};
+    use crate::models::Color;
+
+    #[test]
+    fn test_theme_from_string() {
+        assert_eq!(Theme::from("Light".to_string()), Theme::Light);
+        assert_eq!(
+            Theme::from("Colorblind".to_string()),
+            Theme::Custom("Colorblind".to_string())
+        );
+        assert_eq!(String::from(Theme::Auto), "auto");
+    }
+
+    #[test]
+    fn test_from_theme_toml() {
+        let schema = ColorSchema::from_theme_toml(
+            r##"
+base = "light"
+bullish_candle = "#3B82F6"
+bid_bar = "#3B82F6C8"
+"##,
+        )
+        .unwrap();
+        assert_eq!(schema.bullish_candle, Color::new(59, 130, 246, 255));
+        assert_eq!(schema.bid_bar, Color::new(59, 130, 246, 200));
+        assert_eq!(schema.background, ColorSchema::light().background);
+
+        let schema = ColorSchema::from_theme_toml("bearish_candle = '#F97316'").unwrap();
+        assert_eq!(schema.background, ColorSchema::dark().background);
+
+        assert!(ColorSchema::from_theme_toml("bullish = '#3B82F6'").is_err());
+        assert!(ColorSchema::from_theme_toml("bullish_candle = '#3B82'").is_err());
+        assert!(ColorSchema::from_theme_toml("base = 'other'").is_err());
+    }
+}
@@ -1,7 +1,19 @@
 use super::color_schema::Theme;
+use super::indicators::IndicatorConfig;
+use super::interval::Interval;
+use super::latency::LatencyConfig;
+use super::layout::LayoutConfig;
+use super::log_file::LogFileConfig;
+use super::notifications::NotificationConfig;
+use super::session_recording::RecordingConfig;
+use super::sound::SoundConfig;
+use super::tape_sound::TapeSoundConfig;
+use super::timestamp::Timestamp;
+use crate::utils::detect_ui_scale;
 use clap::Parser;
 use rust_decimal::Decimal;
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use std::fs;
 use std::path::PathBuf;
 
@@ -9,6 +21,9 @@ use std::path::PathBuf;
 pub struct Config {
     #[serde(default)]
     pub symbol: String,
+    // all symbols from the command line, each one gets a pane
+    #[serde(default)]
+    pub symbols: Vec<String>,
     #[serde(default = "default_exchange")]
     pub exchange: String,
     #[serde(default)]
@@ -17,13 +32,25 @@ pub struct Config {
     pub window_width: usize,
     #[serde(default = "default_height")]
     pub window_height: usize,
+    // HiDPI scale factor, detected if not set
+    pub ui_scale: Option<f32>,
+    pub font_family: Option<String>,
+    // a .ttf/.otf file, takes precedence over font_family
+    pub font_path: Option<PathBuf>,
 
     pub binance_access_key: Option<String>,
     pub binance_secret_key: Option<String>,
+    // place, cancel and move Binance futures orders over the WebSocket API instead of REST
+    #[serde(default)]
+    pub binance_ws_orders: bool,
 
     pub gateio_access_key: Option<String>,
     pub gateio_secret_key: Option<String>,
 
+    // exchange clock synchronization for signed requests
+    #[serde(default)]
+    pub clock: ClockConfig,
+
     #[serde(default = "default_size")]
     pub lot_size: Option<Decimal>,
     #[serde(default = "default_mult_1")]
@@ -39,16 +66,67 @@ pub struct Config {
 
     #[serde(default)]
     pub sound: bool,
+    #[serde(default)]
+    pub sounds: SoundConfig,
+    // clicks for the trades of the active chart
+    #[serde(default)]
+    pub tape_sound: TapeSoundConfig,
 
     #[serde(default)]
     pub cleanup_on_shutdown: bool,
+
+    #[serde(default = "default_heatmap")]
+    pub heatmap: bool,
+
+    // trade notional (in quote) to be shown as a bubble, can be overridden per symbol
+    #[serde(default = "default_big_trade_threshold")]
+    pub big_trade_threshold: Decimal,
+    #[serde(default)]
+    pub big_trade_thresholds: HashMap<String, Decimal>,
+    #[serde(default)]
+    pub big_trade_sound: bool,
+
+    #[serde(default)]
+    pub indicators: Vec<IndicatorConfig>,
+
+    #[serde(default)]
+    pub layout: LayoutConfig,
+
+    // sinks for alerts, fills, stop-loss and connection errors
+    #[serde(default)]
+    pub notifications: Vec<NotificationConfig>,
+
+    // JSON-lines log files in `~/.scalper-rs/logs/`
+    #[serde(default)]
+    pub log_file: LogFileConfig,
+
+    // order book and trades of the charts in `~/.scalper-rs/sessions/`, replayed by the headless mode
+    #[serde(default)]
+    pub recording: RecordingConfig,
+
+    // thresholds of the stream latency and order round-trip indicators in the status bar
+    #[serde(default)]
+    pub latency: LatencyConfig,
+
+    // header with the symbol, exchange, interval and PnL on screenshots
+    #[serde(default = "default_screenshot_header")]
+    pub screenshot_header: bool,
+
+    // command line only
+    #[serde(skip)]
+    pub interval: Interval,
+    // headless mode: render the chart at this time to `output` and exit
+    #[serde(skip)]
+    pub render_at: Option<Timestamp>,
+    #[serde(skip)]
+    pub output: Option<PathBuf>,
 }
 
 #[derive(Parser, Debug)]
 #[command(about = "Scalper")]
 struct Cli {
-    #[arg(index = 1)]
-    symbol: String,
+    #[arg(index = 1, required = true, num_args = 1..)]
+    symbols: Vec<String>,
     #[arg(long)]
     exchange: Option<String>,
     #[arg(long)]
@@ -57,6 +135,36 @@ struct Cli {
     lot_size: Option<Decimal>,
     #[arg(long)]
     sl_pnl: Option<Decimal>,
+    /// 1M, 5M, 15M or 1H
+    #[arg(long)]
+    interval: Option<Interval>,
+    /// Render the chart at this time (UTC) to a PNG file without opening a window
+    #[arg(long, value_parser = Timestamp::parse_utc)]
+    render_at: Option<Timestamp>,
+    /// PNG file for --render-at, saved to the screenshots folder by default
+    #[arg(long)]
+    output: Option<PathBuf>,
+}
+
+/// `[clock]` config section.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ClockConfig {
+    // Binance `recvWindow`: signed requests are rejected when they arrive later than this after their timestamp
+    pub recv_window_ms: u64,
+    pub sync_interval_secs: u64,
+    // the local clock is reported when it is off the exchange clock by more than this
+    pub drift_warning_ms: u64,
+}
+
+impl Default for ClockConfig {
+    fn default() -> Self {
+        Self {
+            recv_window_ms: 5000,
+            sync_interval_secs: 300,
+            drift_warning_ms: 1000,
+        }
+    }
 }
 
 fn default_exchange() -> String {
@@ -83,6 +191,18 @@ fn default_mult_4() -> Option<usize> {
     Some(10)
 }
 
+fn default_heatmap() -> bool {
+    true
+}
+
+fn default_big_trade_threshold() -> Decimal {
+    Decimal::from(100_000)
+}
+
+fn default_screenshot_header() -> bool {
+    true
+}
+
 fn default_width() -> usize {
     800
 }
@@ -111,7 +231,8 @@ impl Config {
         let mut config: Config = toml::from_str(&contents)?;
 
         let cli_overrides = Cli::parse();
-        config.symbol = cli_overrides.symbol.clone();
+        config.symbol = cli_overrides.symbols[0].clone();
+        config.symbols = cli_overrides.symbols.clone();
         if let Some(exchange) = cli_overrides.exchange {
             config.exchange = exchange;
         }
@@ -126,19 +247,126 @@ impl Config {
                 config.sl_pnl = Some(-sl_pnl);
             }
         }
+        if let Some(interval) = cli_overrides.interval {
+            config.interval = interval;
+        }
+        config.render_at = cli_overrides.render_at;
+        config.output = cli_overrides.output;
         if let Some(theme) = cli_overrides.theme {
-            config.theme = match theme.to_lowercase().as_str() {
-                "light" => Theme::Light,
-                "auto" => Theme::Auto,
-                _ => Theme::Dark,
-            };
+            config.theme = Theme::from(theme);
         }
 
         Ok(config)
     }
 
+    pub fn big_trade_threshold_for(&self, symbol: &str) -> Decimal {
+        self.big_trade_thresholds
+            .iter()
+            .find(|(s, _)| s.eq_ignore_ascii_case(symbol))
+            .map(|(_, threshold)| *threshold)
+            .unwrap_or(self.big_trade_threshold)
+    }
+
+    pub fn ui_scale(&self) -> f32 {
+        self.ui_scale
+            .filter(|scale| *scale > 0.0)
+            .unwrap_or_else(detect_ui_scale)
+    }
+
+    /// Writes the `[layout]` section back to the config file.
+    /// Only the layout values are replaced, other settings, comments and formatting are kept.
+    pub fn save_layout(&self) -> Result<(), Box<dyn std::error::Error>> {
+        let config_path = Self::config_path()?;
+
+        let contents = match fs::read_to_string(&config_path) {
+            Ok(contents) => contents,
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => String::new(),
+            Err(err) => return Err(err.into()),
+        };
+        let contents = with_layout(&contents, &self.layout)?;
+
+        if let Some(parent) = config_path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        // replaced at once, the file is never left half written
+        let temp_path = config_path.with_extension("tmp");
+        fs::write(&temp_path, contents)?;
+        // the file holds the API keys, keep its permissions
+        if let Ok(metadata) = fs::metadata(&config_path) {
+            fs::set_permissions(&temp_path, metadata.permissions())?;
+        }
+        fs::rename(&temp_path, &config_path)?;
+
+        Ok(())
+    }
+
+    /// Returns `$HOME/.scalper-rs/screenshots/<symbol>-<time>.png`, the folder is created if missing.
+    pub fn screenshot_path(
+        symbol: &str,
+        time: Timestamp,
+    ) -> Result<PathBuf, Box<dyn std::error::Error>> {
+        let home = dirs::home_dir().ok_or("No home directory.")?;
+        let dir = home.join(".scalper-rs").join("screenshots");
+        fs::create_dir_all(&dir)?;
+        Ok(dir.join(format!(
+            "{}-{}.png",
+            symbol,
+            time.format_utc("%Y%m%d-%H%M%S")
+        )))
+    }
+
     fn config_path() -> Result<PathBuf, Box<dyn std::error::Error>> {
         let home = dirs::home_dir().ok_or("No home directory.")?;
         Ok(home.join(".scalper-rs").join("config"))
     }
 }
+
+/// Returns the config file contents with the `[layout]` values replaced.
+fn with_layout(
+    contents: &str,
+    layout: &LayoutConfig,
+) -> Result<String, Box<dyn std::error::Error>> {
+    let mut document: toml_edit::DocumentMut = contents.parse()?;
+    let layout: toml_edit::DocumentMut = toml::to_string(layout)?.parse()?;
+    let table = document
+        .entry("layout")
+        .or_insert_with(toml_edit::table)
+        .as_table_like_mut()
+        .ok_or("The layout in the config file is not a table")?;
+    for (key, item) in layout.iter() {
+        let Some(mut value) = item.as_value().cloned() else {
+            continue;
+        };
+        // keeps the comment after the value
+        if let Some(previous) = table.get(key).and_then(|item| item.as_value()) {
+            *value.decor_mut() = previous.decor().clone();
+        }
+        table.insert(key, toml_edit::Item::Value(value));
+    }
+    Ok(document.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::with_layout;
+    use crate::models::LayoutConfig;
+
+    #[test]
+    fn test_with_layout_keeps_comments() {
+        let contents = "# API keys\nbinance_access_key = 'key'\n\n[layout] # in pixels\norders_width = 50 # narrow\n";
+        let layout = LayoutConfig {
+            orders_width: 80,
+            ..LayoutConfig::default()
+        };
+
+        let updated = with_layout(contents, &layout).unwrap();
+        assert!(
+            updated.starts_with("# API keys\nbinance_access_key = 'key'\n\n[layout] # in pixels\n")
+        );
+        assert!(updated.contains("orders_width = 80 # narrow\n"));
+        assert_eq!(
+            toml::from_str::<super::Config>(&updated).unwrap().layout,
+            layout
+        );
+    }
+}
@@ -0,0 +1,188 @@
+use super::timestamp::Timestamp;
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// A point on the chart, `time` is the open time of a candle.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Anchor {
+    pub time: Timestamp,
+    pub price: Decimal,
+}
+
+impl Anchor {
+    /// Moves the anchor by `dt` milliseconds and `dprice`, the time is clamped at zero.
+    fn shift(&mut self, dt: i64, dprice: Decimal) {
+        self.time =
+            Timestamp::from_milliseconds((self.time.milliseconds() as i64 + dt).max(0) as u64);
+        self.price += dprice;
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Drawing {
+    /// Horizontal support/resistance line.
+    Level {
+        price: Decimal,
+    },
+    TrendLine {
+        start: Anchor,
+        end: Anchor,
+    },
+    /// Shaded rectangle between two corners.
+    Zone {
+        start: Anchor,
+        end: Anchor,
+    },
+}
+
+impl Drawing {
+    pub fn shift(&mut self, dt: i64, dprice: Decimal) {
+        match self {
+            Drawing::Level { price } => *price += dprice,
+            Drawing::TrendLine { start, end } | Drawing::Zone { start, end } => {
+                start.shift(dt, dprice);
+                end.shift(dt, dprice);
+            }
+        }
+    }
+}
+
+/// Chart annotations of a symbol, stored in `$HOME/.scalper-rs/drawings/<exchange>-<symbol>.json`.
+pub struct Drawings {
+    path: Option<PathBuf>,
+    items: Vec<Drawing>,
+}
+
+impl Drawings {
+    /// Drawings that are not saved.
+    pub fn new() -> Self {
+        Self {
+            path: None,
+            items: Vec::new(),
+        }
+    }
+
+    pub fn load(exchange: &str, symbol: &str) -> Result<Self, Box<dyn std::error::Error>> {
+        let home = dirs::home_dir().ok_or("No home directory.")?;
+        let path = home
+            .join(".scalper-rs")
+            .join("drawings")
+            .join(format!("{}-{}.json", exchange, symbol));
+        Self::load_from(&path)
+    }
+
+    /// Reads the drawings file, a missing file means no drawings.
+    pub fn load_from(path: &Path) -> Result<Self, Box<dyn std::error::Error>> {
+        let items = match fs::read_to_string(path) {
+            Ok(contents) => serde_json::from_str(&contents)
+                .map_err(|e| format!("Invalid drawings file {}: {}", path.display(), e))?,
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => Vec::new(),
+            Err(err) => return Err(err.into()),
+        };
+        Ok(Self {
+            path: Some(path.to_path_buf()),
+            items,
+        })
+    }
+
+    pub fn save(&self) -> Result<(), Box<dyn std::error::Error>> {
+        let Some(path) = &self.path else {
+            return Ok(());
+        };
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(path, serde_json::to_string_pretty(&self.items)?)?;
+        Ok(())
+    }
+
+    pub fn items(&self) -> &[Drawing] {
+        &self.items
+    }
+
+    pub fn add(&mut self, drawing: Drawing) {
+        self.items.push(drawing);
+    }
+
+    pub fn remove(&mut self, index: usize) -> Option<Drawing> {
+        (index < self.items.len()).then(|| self.items.remove(index))
+    }
+
+    pub fn shift(&mut self, index: usize, dt: i64, dprice: Decimal) {
+        if let Some(drawing) = self.items.get_mut(index) {
+            drawing.shift(dt, dprice);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Anchor, Drawing, Drawings};
+    use crate::models::Timestamp;
+    use rust_decimal::Decimal;
+
+    fn anchor(seconds: u64, price: i64) -> Anchor {
+        Anchor {
+            time: Timestamp::from_seconds(seconds),
+            price: Decimal::from(price),
+        }
+    }
+
+    #[test]
+    fn test_shift() {
+        let mut level = Drawing::Level {
+            price: Decimal::from(100),
+        };
+        level.shift(60_000, Decimal::from(5));
+        assert_eq!(
+            level,
+            Drawing::Level {
+                price: Decimal::from(105)
+            }
+        );
+
+        let mut line = Drawing::TrendLine {
+            start: anchor(60, 100),
+            end: anchor(180, 110),
+        };
+        line.shift(-120_000, Decimal::from(-10));
+        assert_eq!(
+            line,
+            Drawing::TrendLine {
+                start: anchor(0, 90),
+                end: anchor(60, 100),
+            }
+        );
+    }
+
+    #[test]
+    fn test_save_and_load() {
+        let path =
+            std::env::temp_dir().join(format!("scalper-drawings-test-{}.json", std::process::id()));
+        std::fs::remove_file(&path).ok();
+
+        let mut drawings = Drawings::load_from(&path).unwrap();
+        assert!(drawings.items().is_empty());
+
+        drawings.add(Drawing::Level {
+            price: Decimal::from(100),
+        });
+        drawings.add(Drawing::Zone {
+            start: anchor(60, 100),
+            end: anchor(120, 90),
+        });
+        drawings.save().unwrap();
+
+        let mut loaded = Drawings::load_from(&path).unwrap();
+        assert_eq!(loaded.items(), drawings.items());
+
+        assert!(loaded.remove(0).is_some());
+        assert!(loaded.remove(5).is_none());
+        assert_eq!(loaded.items().len(), 1);
+
+        std::fs::remove_file(&path).ok();
+    }
+}
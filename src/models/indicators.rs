@@ -0,0 +1,509 @@
+use super::candles::{Candle, CandlesState};
+use super::color::Color;
+use super::timestamp::Timestamp;
+use rust_decimal::Decimal;
+use rust_decimal::prelude::ToPrimitive;
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+
+const SECONDS_PER_DAY: u64 = 24 * 60 * 60;
+
+/// An `[[indicators]]` config entry.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "lowercase")]
+pub enum IndicatorConfig {
+    /// Session (UTC day) VWAP with optional standard deviation bands.
+    Vwap {
+        #[serde(default)]
+        bands: Vec<f64>,
+        color: Color,
+    },
+    Ema {
+        period: usize,
+        color: Color,
+    },
+    Sma {
+        period: usize,
+        color: Color,
+    },
+    Bollinger {
+        #[serde(default = "default_bollinger_period")]
+        period: usize,
+        #[serde(default = "default_bollinger_std_dev")]
+        std_dev: f64,
+        color: Color,
+    },
+}
+
+fn default_bollinger_period() -> usize {
+    20
+}
+
+fn default_bollinger_std_dev() -> f64 {
+    2.0
+}
+
+impl IndicatorConfig {
+    pub fn color(&self) -> Color {
+        match self {
+            IndicatorConfig::Vwap { color, .. } => *color,
+            IndicatorConfig::Ema { color, .. } => *color,
+            IndicatorConfig::Sma { color, .. } => *color,
+            IndicatorConfig::Bollinger { color, .. } => *color,
+        }
+    }
+}
+
+/// Values of the indicator lines for a candle.
+#[derive(Debug, Clone)]
+pub struct IndicatorPoint {
+    pub open_time: Timestamp,
+    pub lines: Vec<f64>,
+}
+
+/// State accumulated from closed candles.
+enum IndicatorState {
+    Vwap {
+        session: u64,
+        price_volume: f64,
+        price2_volume: f64,
+        volume: f64,
+    },
+    Ema {
+        value: Option<f64>,
+    },
+    Window {
+        closes: VecDeque<f64>,
+    },
+}
+
+pub struct Indicator {
+    pub config: IndicatorConfig,
+    state: IndicatorState,
+    pending: Option<Candle>,
+    points: VecDeque<IndicatorPoint>,
+    capacity: usize,
+}
+
+impl Indicator {
+    pub fn new(config: IndicatorConfig, capacity: usize) -> Self {
+        let state = Self::initial_state(&config);
+        Self {
+            config,
+            state,
+            pending: None,
+            points: VecDeque::with_capacity(capacity),
+            capacity,
+        }
+    }
+
+    fn initial_state(config: &IndicatorConfig) -> IndicatorState {
+        match config {
+            IndicatorConfig::Vwap { .. } => IndicatorState::Vwap {
+                session: 0,
+                price_volume: 0.0,
+                price2_volume: 0.0,
+                volume: 0.0,
+            },
+            IndicatorConfig::Ema { .. } => IndicatorState::Ema { value: None },
+            IndicatorConfig::Sma { .. } | IndicatorConfig::Bollinger { .. } => {
+                IndicatorState::Window {
+                    closes: VecDeque::new(),
+                }
+            }
+        }
+    }
+
+    pub fn reset(&mut self) {
+        self.state = Self::initial_state(&self.config);
+        self.pending = None;
+        self.points.clear();
+    }
+
+    pub fn points(&self) -> &VecDeque<IndicatorPoint> {
+        &self.points
+    }
+
+    pub fn last_open_time(&self) -> Option<Timestamp> {
+        self.pending.map(|c| c.open_time)
+    }
+
+    /// Feeds a candle, the last candle can be fed repeatedly while it is being updated.
+    pub fn push(&mut self, candle: &Candle) {
+        if let Some(pending) = self.pending {
+            if candle.open_time < pending.open_time {
+                return;
+            }
+            if candle.open_time > pending.open_time {
+                self.commit(&pending);
+            }
+        }
+        self.pending = Some(*candle);
+
+        let lines = self.compute(candle);
+        if let Some(last) = self.points.back_mut()
+            && last.open_time == candle.open_time
+        {
+            match lines {
+                Some(lines) => last.lines = lines,
+                None => {
+                    self.points.pop_back();
+                }
+            }
+            return;
+        }
+        if let Some(lines) = lines {
+            if self.points.len() == self.capacity {
+                self.points.pop_front();
+            }
+            self.points.push_back(IndicatorPoint {
+                open_time: candle.open_time,
+                lines,
+            });
+        }
+    }
+
+    fn commit(&mut self, candle: &Candle) {
+        let close = candle.close.to_f64().unwrap_or(0.0);
+        match (&mut self.state, &self.config) {
+            (
+                IndicatorState::Vwap {
+                    session,
+                    price_volume,
+                    price2_volume,
+                    volume,
+                },
+                _,
+            ) => {
+                let candle_session = candle.open_time.seconds() / SECONDS_PER_DAY;
+                if candle_session != *session {
+                    *session = candle_session;
+                    *price_volume = 0.0;
+                    *price2_volume = 0.0;
+                    *volume = 0.0;
+                }
+                let (price, candle_volume) = typical_price_volume(candle);
+                *price_volume += price * candle_volume;
+                *price2_volume += price * price * candle_volume;
+                *volume += candle_volume;
+            }
+            (IndicatorState::Ema { value }, IndicatorConfig::Ema { period, .. }) => {
+                *value = Some(ema(*value, close, *period));
+            }
+            (IndicatorState::Window { closes }, IndicatorConfig::Sma { period, .. })
+            | (IndicatorState::Window { closes }, IndicatorConfig::Bollinger { period, .. }) => {
+                closes.push_back(close);
+                while closes.len() >= *period {
+                    closes.pop_front();
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn compute(&self, candle: &Candle) -> Option<Vec<f64>> {
+        let close = candle.close.to_f64()?;
+        match (&self.state, &self.config) {
+            (
+                IndicatorState::Vwap {
+                    session,
+                    price_volume,
+                    price2_volume,
+                    volume,
+                },
+                IndicatorConfig::Vwap { bands, .. },
+            ) => {
+                let (price, candle_volume) = typical_price_volume(candle);
+                let mut pv = price * candle_volume;
+                let mut p2v = price * price * candle_volume;
+                let mut v = candle_volume;
+                if candle.open_time.seconds() / SECONDS_PER_DAY == *session {
+                    pv += price_volume;
+                    p2v += price2_volume;
+                    v += volume;
+                }
+                if v <= 0.0 {
+                    return None;
+                }
+                let vwap = pv / v;
+                let std_dev = (p2v / v - vwap * vwap).max(0.0).sqrt();
+                let mut lines = vec![vwap];
+                for band in bands {
+                    lines.push(vwap + band * std_dev);
+                    lines.push(vwap - band * std_dev);
+                }
+                Some(lines)
+            }
+            (IndicatorState::Ema { value }, IndicatorConfig::Ema { period, .. }) => {
+                Some(vec![ema(*value, close, *period)])
+            }
+            (IndicatorState::Window { closes }, IndicatorConfig::Sma { period, .. }) => {
+                if closes.len() + 1 < *period {
+                    return None;
+                }
+                let sum: f64 = closes.iter().sum::<f64>() + close;
+                Some(vec![sum / *period as f64])
+            }
+            (
+                IndicatorState::Window { closes },
+                IndicatorConfig::Bollinger {
+                    period, std_dev, ..
+                },
+            ) => {
+                if closes.len() + 1 < *period {
+                    return None;
+                }
+                let n = *period as f64;
+                let mean = (closes.iter().sum::<f64>() + close) / n;
+                let variance = (closes.iter().map(|c| (c - mean).powi(2)).sum::<f64>()
+                    + (close - mean).powi(2))
+                    / n;
+                let deviation = variance.sqrt() * std_dev;
+                Some(vec![mean, mean + deviation, mean - deviation])
+            }
+            _ => None,
+        }
+    }
+}
+
+fn typical_price_volume(candle: &Candle) -> (f64, f64) {
+    let price = ((candle.high + candle.low + candle.close) / Decimal::from(3))
+        .to_f64()
+        .unwrap_or(0.0);
+    (price, candle.volume.to_f64().unwrap_or(0.0))
+}
+
+fn ema(previous: Option<f64>, close: f64, period: usize) -> f64 {
+    match previous {
+        Some(previous) => {
+            let alpha = 2.0 / (period as f64 + 1.0);
+            alpha * close + (1.0 - alpha) * previous
+        }
+        None => close,
+    }
+}
+
+/// Indicators declared in the config, computed incrementally over `CandlesState`.
+pub struct Indicators {
+    indicators: Vec<Indicator>,
+    generation: Option<u64>,
+    revision: Option<u64>,
+}
+
+impl Indicators {
+    pub fn new(configs: &[IndicatorConfig], capacity: usize) -> Self {
+        Self {
+            indicators: configs
+                .iter()
+                .map(|c| Indicator::new(c.clone(), capacity))
+                .collect(),
+            generation: None,
+            revision: None,
+        }
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &Indicator> {
+        self.indicators.iter()
+    }
+
+    /// Feeds new candles; recomputes everything if the candles buffer was repopulated.
+    /// Points are kept for as many candles as the buffer holds, it grows when older candles are loaded.
+    pub fn update(&mut self, candles_state: &CandlesState) {
+        if self.indicators.is_empty() || self.revision == Some(candles_state.revision()) {
+            return;
+        }
+        self.revision = Some(candles_state.revision());
+        if self.generation != Some(candles_state.generation()) {
+            for indicator in self.indicators.iter_mut() {
+                indicator.reset();
+                indicator.capacity = candles_state.capacity();
+            }
+            self.generation = Some(candles_state.generation());
+        }
+
+        for indicator in self.indicators.iter_mut() {
+            let candles: Vec<Candle> = match indicator.last_open_time() {
+                // only the candles from the one being updated
+                Some(last_open_time) => {
+                    let mut candles: Vec<Candle> = candles_state
+                        .iter()
+                        .rev()
+                        .take_while(|candle| candle.open_time >= last_open_time)
+                        .collect();
+                    candles.reverse();
+                    candles
+                }
+                None => candles_state.to_vec(),
+            };
+            for candle in candles.iter() {
+                indicator.push(candle);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{IndicatorConfig, Indicators};
+    use crate::models::{Candle, CandlesState, Color, Interval, Timestamp};
+    use rust_decimal::Decimal;
+
+    fn candle(minute: u64, close: i64, volume: i64) -> Candle {
+        Candle {
+            open_time: Timestamp::from_seconds(minute * 60),
+            open: Decimal::from(close),
+            high: Decimal::from(close),
+            low: Decimal::from(close),
+            close: Decimal::from(close),
+            volume: Decimal::from(volume),
+        }
+    }
+
+    fn color() -> Color {
+        Color::new(0, 0, 0, 255)
+    }
+
+    fn last_lines(indicators: &Indicators, index: usize) -> Vec<f64> {
+        let indicator = indicators.iter().nth(index).unwrap();
+        indicator.points().back().unwrap().lines.clone()
+    }
+
+    #[test]
+    fn test_sma_incremental() {
+        let mut candles = CandlesState::new(10, Interval::M1);
+        let mut indicators = Indicators::new(
+            &[IndicatorConfig::Sma {
+                period: 3,
+                color: color(),
+            }],
+            10,
+        );
+
+        candles.push(candle(0, 1, 1));
+        candles.push(candle(1, 2, 1));
+        indicators.update(&candles);
+        assert!(indicators.iter().next().unwrap().points().is_empty());
+
+        candles.push(candle(2, 3, 1));
+        indicators.update(&candles);
+        assert_eq!(last_lines(&indicators, 0), vec![2.0]);
+
+        // the last candle is updated in place
+        candles.push(candle(2, 6, 1));
+        indicators.update(&candles);
+        assert_eq!(last_lines(&indicators, 0), vec![3.0]);
+
+        candles.push(candle(3, 7, 1));
+        indicators.update(&candles);
+        assert_eq!(last_lines(&indicators, 0), vec![5.0]);
+        assert_eq!(indicators.iter().next().unwrap().points().len(), 2);
+    }
+
+    #[test]
+    fn test_ema() {
+        let mut candles = CandlesState::new(10, Interval::M1);
+        let mut indicators = Indicators::new(
+            &[IndicatorConfig::Ema {
+                period: 3,
+                color: color(),
+            }],
+            10,
+        );
+
+        candles.push(candle(0, 10, 1));
+        candles.push(candle(1, 20, 1));
+        indicators.update(&candles);
+
+        assert_eq!(last_lines(&indicators, 0), vec![15.0]);
+    }
+
+    #[test]
+    fn test_bollinger() {
+        let mut candles = CandlesState::new(10, Interval::M1);
+        let mut indicators = Indicators::new(
+            &[IndicatorConfig::Bollinger {
+                period: 2,
+                std_dev: 2.0,
+                color: color(),
+            }],
+            10,
+        );
+
+        candles.push(candle(0, 10, 1));
+        candles.push(candle(1, 20, 1));
+        indicators.update(&candles);
+
+        assert_eq!(last_lines(&indicators, 0), vec![15.0, 25.0, 5.0]);
+    }
+
+    #[test]
+    fn test_vwap_session() {
+        let mut candles = CandlesState::new(10, Interval::M1);
+        let mut indicators = Indicators::new(
+            &[IndicatorConfig::Vwap {
+                bands: vec![1.0],
+                color: color(),
+            }],
+            10,
+        );
+
+        candles.push(candle(0, 10, 1));
+        candles.push(candle(1, 20, 3));
+        indicators.update(&candles);
+        let lines = last_lines(&indicators, 0);
+        assert_eq!(lines[0], 17.5);
+        assert!(lines[1] > 17.5 && lines[2] < 17.5);
+
+        // the next day starts a new session
+        candles.push(candle(24 * 60, 30, 1));
+        indicators.update(&candles);
+        assert_eq!(last_lines(&indicators, 0), vec![30.0, 30.0, 30.0]);
+    }
+
+    #[test]
+    fn test_recompute_on_clear() {
+        let mut candles = CandlesState::new(10, Interval::M1);
+        let mut indicators = Indicators::new(
+            &[IndicatorConfig::Sma {
+                period: 1,
+                color: color(),
+            }],
+            10,
+        );
+
+        candles.push(candle(0, 10, 1));
+        candles.push(candle(1, 20, 1));
+        indicators.update(&candles);
+
+        candles.clear(Interval::M5);
+        candles.push(candle(0, 30, 1));
+        indicators.update(&candles);
+
+        let points = indicators.iter().next().unwrap().points();
+        assert_eq!(points.len(), 1);
+        assert_eq!(points[0].lines, vec![30.0]);
+    }
+
+    #[test]
+    fn test_points_grow_with_older_candles() {
+        let mut candles = CandlesState::new(2, Interval::M1);
+        let mut indicators = Indicators::new(
+            &[IndicatorConfig::Sma {
+                period: 1,
+                color: color(),
+            }],
+            2,
+        );
+
+        candles.push(candle(2, 30, 1));
+        candles.push(candle(3, 40, 1));
+        indicators.update(&candles);
+        assert_eq!(indicators.iter().next().unwrap().points().len(), 2);
+
+        candles.prepend(vec![candle(0, 10, 1), candle(1, 20, 1)]);
+        indicators.update(&candles);
+        let points = indicators.iter().next().unwrap().points();
+        assert_eq!(points.len(), 4);
+        assert_eq!(points[0].lines, vec![10.0]);
+    }
+}
@@ -1,5 +1,8 @@
-#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+use std::str::FromStr;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Default)]
 pub enum Interval {
+    #[default]
     M1,
     M5,
     M15,
@@ -48,6 +51,18 @@ impl Interval {
     }
 }
 
+impl FromStr for Interval {
+    type Err = String;
+
+    /// Parses the slug, e.g. `5m` or `1H`.
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        [Interval::M1, Interval::M5, Interval::M15, Interval::H1]
+            .into_iter()
+            .find(|interval| interval.slug().eq_ignore_ascii_case(value.trim()))
+            .ok_or_else(|| format!("Invalid interval: {}", value))
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::Interval;
@@ -59,4 +74,11 @@ mod tests {
         assert!(a < b);
         assert_eq!(Interval::M1.up(), Interval::M5);
     }
+
+    #[test]
+    fn parse_interval() {
+        assert_eq!("15m".parse::<Interval>(), Ok(Interval::M15));
+        assert_eq!("1H".parse::<Interval>(), Ok(Interval::H1));
+        assert!("2m".parse::<Interval>().is_err());
+    }
 }
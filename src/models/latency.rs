@@ -0,0 +1,205 @@
+use super::timestamp::Timestamp;
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+
+// placed orders without a response after this are forgotten (the response was lost)
+const ORDER_TIMEOUT_MS: u64 = 10_000;
+
+/// `[latency]` config section: thresholds of the status bar indicators, in milliseconds.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct LatencyConfig {
+    pub warning_ms: u64,
+    pub critical_ms: u64,
+    // order book and candles streams without messages for this long are stale, trades can be quiet
+    pub stale_warning_ms: u64,
+    pub stale_critical_ms: u64,
+}
+
+impl Default for LatencyConfig {
+    fn default() -> Self {
+        Self {
+            warning_ms: 300,
+            critical_ms: 1000,
+            stale_warning_ms: 3000,
+            stale_critical_ms: 10_000,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, PartialOrd)]
+pub enum Health {
+    Ok,
+    Warning,
+    Critical,
+}
+
+impl LatencyConfig {
+    pub fn health(&self, latency_ms: i64) -> Health {
+        if latency_ms >= self.critical_ms as i64 {
+            Health::Critical
+        } else if latency_ms >= self.warning_ms as i64 {
+            Health::Warning
+        } else {
+            Health::Ok
+        }
+    }
+
+    pub fn staleness(&self, silence_ms: u64) -> Health {
+        if silence_ms >= self.stale_critical_ms {
+            Health::Critical
+        } else if silence_ms >= self.stale_warning_ms {
+            Health::Warning
+        } else {
+            Health::Ok
+        }
+    }
+}
+
+/// Receive statistics of a market data stream, updated by the stream on every message.
+#[derive(Debug, Clone, Default)]
+pub struct StreamStats {
+    // local receive time minus the exchange event time of the last message,
+    // negative if the local clock is behind
+    latency_ms: Option<i64>,
+    last_received: Option<Timestamp>,
+    // messages received in the current second, and in the previous one
+    window_start: u64,
+    window_count: u32,
+    rate: u32,
+}
+
+impl StreamStats {
+    pub fn record(&mut self, event_time: Timestamp) {
+        self.record_at(event_time, Timestamp::now());
+    }
+
+    fn record_at(&mut self, event_time: Timestamp, now: Timestamp) {
+        self.latency_ms = Some(now.milliseconds() as i64 - event_time.milliseconds() as i64);
+        self.last_received = Some(now);
+
+        let second = now.seconds();
+        if second != self.window_start {
+            self.rate = if second == self.window_start + 1 {
+                self.window_count
+            } else {
+                0
+            };
+            self.window_start = second;
+            self.window_count = 0;
+        }
+        self.window_count += 1;
+    }
+
+    pub fn latency_ms(&self) -> Option<i64> {
+        self.latency_ms
+    }
+
+    /// Messages per second, counted over the last full second.
+    pub fn rate(&self, now: Timestamp) -> u32 {
+        match now.seconds() {
+            second if second == self.window_start => self.rate,
+            second if second == self.window_start + 1 => self.window_count,
+            _ => 0,
+        }
+    }
+
+    /// Time since the last message, None before the first one.
+    pub fn silence_ms(&self, now: Timestamp) -> Option<u64> {
+        self.last_received
+            .map(|received| now.milliseconds().saturating_sub(received.milliseconds()))
+    }
+}
+
+/// REST request budget of the exchange client, shown in the status bar.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct RateLimitStatus {
+    // usage of the most used limit
+    pub used_percent: u32,
+    // market data polls wait, the rest of the limit is kept for orders and cancels
+    pub throttled: bool,
+    // all requests are paused until then after a rate limit response
+    pub backoff_until: Option<Timestamp>,
+}
+
+/// Time from submitting an order to the exchange response with the new order.
+#[derive(Debug, Default)]
+pub struct OrderRoundTrip {
+    sent: VecDeque<Timestamp>,
+    last_ms: Option<u64>,
+}
+
+impl OrderRoundTrip {
+    pub fn sent(&mut self, now: Timestamp) {
+        self.sent.push_back(now);
+    }
+
+    /// Matches the response with the oldest submitted order.
+    pub fn received(&mut self, now: Timestamp) {
+        while let Some(sent) = self.sent.pop_front() {
+            let elapsed = now.milliseconds().saturating_sub(sent.milliseconds());
+            if elapsed <= ORDER_TIMEOUT_MS {
+                self.last_ms = Some(elapsed);
+                return;
+            }
+        }
+    }
+
+    /// The oldest submitted order was rejected, no response with the new order follows.
+    pub fn rejected(&mut self) {
+        self.sent.pop_front();
+    }
+
+    pub fn last_ms(&self) -> Option<u64> {
+        self.last_ms
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Health, LatencyConfig, OrderRoundTrip, StreamStats};
+    use crate::models::Timestamp;
+
+    #[test]
+    fn test_stream_stats() {
+        let ms = Timestamp::from_milliseconds;
+        let mut stats = StreamStats::default();
+        assert_eq!(stats.silence_ms(ms(1_000)), None);
+
+        stats.record_at(ms(1_000), ms(1_050));
+        stats.record_at(ms(1_100), ms(1_180));
+        stats.record_at(ms(1_900), ms(1_950));
+        assert_eq!(stats.latency_ms(), Some(50));
+        // the first second is not over yet
+        assert_eq!(stats.rate(ms(1_990)), 0);
+        assert_eq!(stats.rate(ms(2_010)), 3);
+
+        stats.record_at(ms(2_000), ms(2_100));
+        assert_eq!(stats.rate(ms(2_500)), 3);
+        assert_eq!(stats.silence_ms(ms(2_500)), Some(400));
+        assert_eq!(stats.rate(ms(5_000)), 0);
+
+        let config = LatencyConfig::default();
+        assert_eq!(config.health(100), Health::Ok);
+        assert_eq!(config.health(500), Health::Warning);
+        assert_eq!(config.staleness(20_000), Health::Critical);
+    }
+
+    #[test]
+    fn test_order_round_trip() {
+        let ms = Timestamp::from_milliseconds;
+        let mut round_trip = OrderRoundTrip::default();
+        round_trip.sent(ms(1_000));
+        round_trip.sent(ms(30_000));
+        round_trip.received(ms(30_120));
+
+        // the first order got no response
+        assert_eq!(round_trip.last_ms(), Some(120));
+
+        round_trip.sent(ms(40_000));
+        round_trip.rejected();
+        round_trip.sent(ms(41_000));
+        round_trip.received(ms(41_080));
+        assert_eq!(round_trip.last_ms(), Some(80));
+    }
+}
@@ -1,3 +1,5 @@
+use serde::{Deserialize, Serialize};
+
 #[derive(Copy, Clone, Debug)]
 pub struct Area {
     pub left: i32,
@@ -6,6 +8,81 @@ pub struct Area {
     pub height: i32,
 }
 
+impl Area {
+    pub fn contains(&self, x: i32, y: i32) -> bool {
+        x >= self.left && x < self.left + self.width && y >= self.top && y < self.top + self.height
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Panel {
+    Orders,
+    OrderBook,
+    OrderFlow,
+    BigTrades,
+}
+
+/// `[layout]` config section, panels not listed in `left_panels`/`right_panels` are hidden.
+/// Panels are listed from the chart outwards, `left_panels` wins if a panel is in both.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct LayoutConfig {
+    pub orders_width: i32,
+    pub order_book_width: i32,
+    pub order_flow_width: i32,
+    pub big_trades_width: i32,
+    pub status_height: i32,
+    pub volume_height: i32,
+    pub font_size: i32,
+    pub left_panels: Vec<Panel>,
+    pub right_panels: Vec<Panel>,
+}
+
+impl Default for LayoutConfig {
+    fn default() -> Self {
+        Self {
+            orders_width: 50,
+            order_book_width: 100,
+            order_flow_width: 100,
+            big_trades_width: 100,
+            status_height: 24,
+            volume_height: 80,
+            font_size: 14,
+            left_panels: Vec::new(),
+            right_panels: vec![
+                Panel::Orders,
+                Panel::OrderBook,
+                Panel::OrderFlow,
+                Panel::BigTrades,
+            ],
+        }
+    }
+}
+
+impl LayoutConfig {
+    pub const MIN_PANEL_WIDTH: i32 = 30;
+
+    pub fn width(&self, panel: Panel) -> i32 {
+        match panel {
+            Panel::Orders => self.orders_width,
+            Panel::OrderBook => self.order_book_width,
+            Panel::OrderFlow => self.order_flow_width,
+            Panel::BigTrades => self.big_trades_width,
+        }
+    }
+
+    pub fn set_width(&mut self, panel: Panel, width: i32) {
+        let width = width.max(Self::MIN_PANEL_WIDTH);
+        match panel {
+            Panel::Orders => self.orders_width = width,
+            Panel::OrderBook => self.order_book_width = width,
+            Panel::OrderFlow => self.order_flow_width = width,
+            Panel::BigTrades => self.big_trades_width = width,
+        }
+    }
+}
+
 pub struct Layout {
     pub width: i32,
     pub height: i32,
@@ -13,17 +90,20 @@ pub struct Layout {
     pub orders_area: Area,
     pub order_book_area: Area,
     pub order_flow_area: Area,
+    pub big_trades_area: Area,
     pub status_area: Area,
     pub volume_height: i32,
+    pub font_size: i32,
+    // visible panels with their borders facing the chart, which can be dragged
+    borders: Vec<(Panel, i32)>,
 }
 
 impl Layout {
-    pub fn new(width: i32, height: i32) -> Self {
-        let dom_width = 100;
-        let order_flow_width = 100;
-        let status_height = 24;
-        let orders_width = 50;
-        let volume_height = 80;
+    pub const MIN_CANDLES_WIDTH: i32 = 100;
+
+    pub fn new(width: i32, height: i32, config: &LayoutConfig) -> Self {
+        let status_height = config.status_height;
+        let panels_height = height - status_height;
 
         let status_area = Area {
             left: 0,
@@ -32,47 +112,216 @@ impl Layout {
             height: status_height,
         };
 
-        let candles_area = Area {
+        // hidden panels get an empty area
+        let mut areas = [Area {
             left: 0,
             top: 0,
-            width: width - dom_width - order_flow_width - orders_width,
-            height: height - status_height,
-        };
+            width: 0,
+            height: panels_height,
+        }; 4];
+        let mut borders = Vec::new();
 
-        let orders_area = Area {
-            left: width - dom_width - order_flow_width - orders_width,
-            top: 0,
-            width: orders_width,
-            height: height - status_height,
-        };
+        let mut left = 0;
+        for panel in config.left_panels.iter().rev() {
+            let panel_width = config.width(*panel);
+            areas[*panel as usize] = Area {
+                left,
+                top: 0,
+                width: panel_width,
+                height: panels_height,
+            };
+            left += panel_width;
+            borders.push((*panel, left));
+        }
 
-        let order_book_area = Area {
-            left: width - order_flow_width - dom_width,
-            top: 0,
-            width: dom_width,
-            height: height - status_height,
-        };
+        let mut right = width;
+        for panel in config.right_panels.iter().rev() {
+            if config.left_panels.contains(panel) {
+                continue;
+            }
+            let panel_width = config.width(*panel);
+            right -= panel_width;
+            areas[*panel as usize] = Area {
+                left: right,
+                top: 0,
+                width: panel_width,
+                height: panels_height,
+            };
+            borders.push((*panel, right));
+        }
 
-        let order_flow_area = Area {
-            left: width - order_flow_width,
+        let candles_area = Area {
+            left,
             top: 0,
-            width: order_flow_width,
-            height: height - status_height,
+            width: right - left,
+            height: panels_height,
         };
 
         Self {
             width,
             height,
             candles_area,
-            orders_area,
-            order_book_area,
-            order_flow_area,
+            orders_area: areas[Panel::Orders as usize],
+            order_book_area: areas[Panel::OrderBook as usize],
+            order_flow_area: areas[Panel::OrderFlow as usize],
+            big_trades_area: areas[Panel::BigTrades as usize],
             status_area,
-            volume_height,
+            volume_height: config.volume_height,
+            font_size: config.font_size,
+            borders,
         }
     }
 
+    /// Returns the panel whose chart-facing border is within a few pixels of `x`.
+    pub fn border_at(&self, x: i32) -> Option<Panel> {
+        self.borders
+            .iter()
+            .find(|(_, border)| (x - border).abs() <= 3)
+            .map(|(panel, _)| *panel)
+    }
+
+    /// Width of `panel` after dragging its chart-facing border to `x`.
+    /// The chart keeps at least `MIN_CANDLES_WIDTH`.
+    pub fn dragged_width(&self, panel: Panel, x: i32) -> i32 {
+        let area = self.area(panel);
+        let width = if area.left + area.width <= self.candles_area.left {
+            x - area.left
+        } else {
+            area.left + area.width - x
+        };
+        width.min(area.width + self.candles_area.width - Self::MIN_CANDLES_WIDTH)
+    }
+
+    pub fn area(&self, panel: Panel) -> Area {
+        match panel {
+            Panel::Orders => self.orders_area,
+            Panel::OrderBook => self.order_book_area,
+            Panel::OrderFlow => self.order_flow_area,
+            Panel::BigTrades => self.big_trades_area,
+        }
+    }
+
+    /// Horizontal span of the chart and the price aligned panels.
+    pub fn price_span(&self) -> (i32, i32) {
+        [self.orders_area, self.order_book_area, self.order_flow_area]
+            .iter()
+            .filter(|area| area.width > 0)
+            .fold(
+                (
+                    self.candles_area.left,
+                    self.candles_area.left + self.candles_area.width,
+                ),
+                |(left, right), area| (left.min(area.left), right.max(area.left + area.width)),
+            )
+    }
+
     pub fn center_px(&self) -> i32 {
-        (self.candles_area.height - 80) / 2
+        (self.candles_area.height - self.volume_height) / 2
+    }
+
+    /// Splits the window into `count` panes, row by row.
+    /// The last row is stretched when it has fewer panes.
+    pub fn grid(width: i32, height: i32, count: usize) -> Vec<Area> {
+        let count = count.max(1);
+        let cols = (count as f64).sqrt().ceil() as usize;
+        let rows = count.div_ceil(cols);
+
+        let mut areas = Vec::with_capacity(count);
+        for row in 0..rows {
+            let row_cols = if row == rows - 1 {
+                count - cols * (rows - 1)
+            } else {
+                cols
+            };
+            let top = height * row as i32 / rows as i32;
+            let bottom = height * (row as i32 + 1) / rows as i32;
+            for col in 0..row_cols {
+                let left = width * col as i32 / row_cols as i32;
+                let right = width * (col as i32 + 1) / row_cols as i32;
+                areas.push(Area {
+                    left,
+                    top,
+                    width: right - left,
+                    height: bottom - top,
+                });
+            }
+        }
+        areas
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Layout, LayoutConfig, Panel};
+
+    #[test]
+    fn test_new() {
+        let layout = Layout::new(1000, 600, &LayoutConfig::default());
+        assert_eq!(
+            (layout.candles_area.left, layout.candles_area.width),
+            (0, 650)
+        );
+        assert_eq!(layout.orders_area.left, 650);
+        assert_eq!(layout.big_trades_area.left, 900);
+        assert_eq!(layout.candles_area.height, 576);
+        assert_eq!(layout.price_span(), (0, 900));
+
+        let config = LayoutConfig {
+            left_panels: vec![Panel::OrderBook],
+            right_panels: vec![Panel::Orders, Panel::OrderBook],
+            ..LayoutConfig::default()
+        };
+        let layout = Layout::new(1000, 600, &config);
+        assert_eq!(layout.order_book_area.left, 0);
+        assert_eq!(
+            (layout.candles_area.left, layout.candles_area.width),
+            (100, 850)
+        );
+        assert_eq!(layout.orders_area.left, 950);
+        assert_eq!(layout.order_flow_area.width, 0);
+        assert_eq!(layout.big_trades_area.width, 0);
+    }
+
+    #[test]
+    fn test_border_drag() {
+        let config = LayoutConfig {
+            left_panels: vec![Panel::OrderBook],
+            right_panels: vec![Panel::Orders, Panel::OrderFlow, Panel::BigTrades],
+            ..LayoutConfig::default()
+        };
+        let layout = Layout::new(1000, 600, &config);
+
+        assert_eq!(layout.border_at(101), Some(Panel::OrderBook));
+        assert_eq!(layout.border_at(752), Some(Panel::Orders));
+        assert_eq!(layout.border_at(400), None);
+
+        assert_eq!(layout.dragged_width(Panel::OrderBook, 150), 150);
+        assert_eq!(layout.dragged_width(Panel::BigTrades, 850), 150);
+        assert_eq!(layout.dragged_width(Panel::Orders, 0), 600);
+    }
+
+    #[test]
+    fn test_grid() {
+        let areas = Layout::grid(1000, 600, 1);
+        assert_eq!(areas.len(), 1);
+        assert_eq!((areas[0].width, areas[0].height), (1000, 600));
+
+        let areas = Layout::grid(1000, 600, 3);
+        assert_eq!(areas.len(), 3);
+        assert_eq!(
+            (areas[0].left, areas[0].width, areas[0].height),
+            (0, 500, 300)
+        );
+        assert_eq!((areas[1].left, areas[1].top), (500, 0));
+        // the last row has a single pane over the full width
+        assert_eq!(
+            (areas[2].left, areas[2].top, areas[2].width),
+            (0, 300, 1000)
+        );
+
+        let areas = Layout::grid(1001, 600, 4);
+        assert_eq!(areas[1].left + areas[1].width, 1001);
+        assert!(areas[3].contains(1000, 599));
+        assert!(!areas[0].contains(500, 300));
     }
 }
@@ -0,0 +1,165 @@
+use super::logs::{Log, LogLevel};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::fs::{self, File, OpenOptions};
+use std::io::Write;
+use std::path::PathBuf;
+
+const FILE_PREFIX: &str = "scalper-";
+const FILE_EXTENSION: &str = "jsonl";
+
+/// `[log_file]` config section.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct LogFileConfig {
+    pub enabled: bool,
+    // daily files older than this are deleted
+    pub retention_days: u32,
+}
+
+impl Default for LogFileConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            retention_days: 14,
+        }
+    }
+}
+
+/// Writes logs as JSON lines to `scalper-<YYYY-MM-DD>.jsonl` files, a new file is started every UTC day.
+pub struct LogFile {
+    dir: PathBuf,
+    retention_days: u32,
+    // the day of the open file
+    day: String,
+    file: Option<File>,
+}
+
+impl LogFile {
+    /// Logs to `$HOME/.scalper-rs/logs/`.
+    pub fn open(config: &LogFileConfig) -> Result<Self, Box<dyn std::error::Error>> {
+        let home = dirs::home_dir().ok_or("No home directory.")?;
+        Ok(Self::open_in(
+            home.join(".scalper-rs").join("logs"),
+            config.retention_days,
+        ))
+    }
+
+    pub fn open_in(dir: PathBuf, retention_days: u32) -> Self {
+        Self {
+            dir,
+            retention_days,
+            day: String::new(),
+            file: None,
+        }
+    }
+
+    pub fn write(&mut self, log: &Log) -> Result<(), Box<dyn std::error::Error>> {
+        let day = log.created_at.format_utc("%Y-%m-%d");
+        if self.file.is_none() || day != self.day {
+            fs::create_dir_all(&self.dir)?;
+            let path = self
+                .dir
+                .join(format!("{}{}.{}", FILE_PREFIX, day, FILE_EXTENSION));
+            self.file = Some(OpenOptions::new().create(true).append(true).open(path)?);
+            self.day = day;
+            self.remove_old_files()?;
+        }
+
+        let (level, tag) = match &log.level {
+            LogLevel::Info => ("info", None),
+            LogLevel::Warning(tag, _) => ("warning", Some(tag)),
+            LogLevel::Error(tag) => ("error", Some(tag)),
+            LogLevel::Recovered(tag) => ("recovered", Some(tag)),
+        };
+        let mut line = json!({
+            "timestamp": log.created_at.format_utc("%Y-%m-%dT%H:%M:%S%.3fZ"),
+            "level": level,
+            "message": log.message,
+            "source": format!("{}:{}", log.location.file(), log.location.line()),
+        });
+        if let Some(tag) = tag {
+            line["tag"] = json!(tag);
+        }
+        if let Some(component) = &log.component {
+            line["component"] = json!(component);
+        }
+        if let Some(order_id) = &log.order_id {
+            line["order_id"] = json!(order_id);
+        }
+
+        if let Some(file) = self.file.as_mut() {
+            writeln!(file, "{}", line)?;
+        }
+        Ok(())
+    }
+
+    /// Deletes the daily files older than `retention_days`, other files are kept.
+    fn remove_old_files(&self) -> Result<(), Box<dyn std::error::Error>> {
+        let today = chrono::NaiveDate::parse_from_str(&self.day, "%Y-%m-%d")?;
+        for entry in fs::read_dir(&self.dir)? {
+            let path = entry?.path();
+            let Some(day) = path
+                .file_stem()
+                .and_then(|stem| stem.to_str())
+                .and_then(|stem| stem.strip_prefix(FILE_PREFIX))
+                .and_then(|day| chrono::NaiveDate::parse_from_str(day, "%Y-%m-%d").ok())
+            else {
+                continue;
+            };
+            if path.extension().is_some_and(|ext| ext == FILE_EXTENSION)
+                && (today - day).num_days() > self.retention_days as i64
+            {
+                fs::remove_file(&path)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::LogFile;
+    use crate::models::{Log, LogLevel, Timestamp};
+    use std::fs;
+
+    #[test]
+    fn test_rotation_and_retention() {
+        let dir = std::env::temp_dir().join(format!("scalper-logs-test-{}", std::process::id()));
+        fs::remove_dir_all(&dir).ok();
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(dir.join("scalper-2026-01-01.jsonl"), "").unwrap();
+        fs::write(dir.join("notes.txt"), "").unwrap();
+
+        let mut log_file = LogFile::open_in(dir.clone(), 7);
+
+        let mut log = Log::new(LogLevel::Info, "filled".to_string(), None).with_order_id("42");
+        log.created_at = Timestamp::parse_utc("2026-01-05T23:59:59").unwrap();
+        log_file.write(&log).unwrap();
+
+        let mut log = Log::new(
+            LogLevel::Warning("CONN".to_string(), None),
+            "reconnecting".to_string(),
+            None,
+        );
+        log.created_at = Timestamp::parse_utc("2026-01-10T00:00:01").unwrap();
+        log_file.write(&log).unwrap();
+
+        let first = fs::read_to_string(dir.join("scalper-2026-01-05.jsonl")).unwrap();
+        let line: serde_json::Value = serde_json::from_str(first.trim()).unwrap();
+        assert_eq!(line["level"], "info");
+        assert_eq!(line["message"], "filled");
+        assert_eq!(line["order_id"], "42");
+        assert_eq!(line["timestamp"], "2026-01-05T23:59:59.000Z");
+        assert!(line["source"].as_str().unwrap().contains("log_file.rs"));
+
+        let second = fs::read_to_string(dir.join("scalper-2026-01-10.jsonl")).unwrap();
+        assert!(second.contains(r#""tag":"CONN""#));
+
+        // older than 7 days
+        assert!(!dir.join("scalper-2026-01-01.jsonl").exists());
+        assert!(dir.join("notes.txt").exists());
+
+        fs::remove_dir_all(&dir).ok();
+    }
+}
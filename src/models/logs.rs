@@ -1,21 +1,37 @@
-use super::sound::Sound;
+use super::log_file::LogFile;
+use super::notifications::NotificationEvent;
+use super::sound::{Sound, SoundPlayer};
 use super::timestamp::Timestamp;
+use crate::utils::Notifier;
 use console::{Term, style};
 use std::collections::VecDeque;
+use std::panic::Location;
 use std::sync::mpsc::Receiver;
 
+// logs kept for the log console
+const HISTORY_SIZE: usize = 200;
+
 #[derive(Debug, Clone)]
 pub enum LogLevel {
     Info,
     Warning(String, Option<usize>), // message, show for n seconds
     Error(String),                  // message
+    Recovered(String),              // message of the resolved error
 }
 
 #[derive(Debug, Clone, PartialEq)]
 pub enum Status {
     Ok,
     Warning(String),
-    Critical(String),
+    Critical(String, usize, Timestamp), // the oldest problem, active problems, since
+}
+
+/// An active error, errors with the same message and component are one problem.
+#[derive(Debug, Clone)]
+struct Problem {
+    message: String,
+    component: Option<String>,
+    since: Timestamp,
 }
 
 #[derive(Debug, Clone)]
@@ -23,73 +39,155 @@ pub struct Log {
     pub level: LogLevel,
     pub message: String,
     pub sound: Option<Sound>,
+    // delivered to the notification sinks routed for the event
+    pub event: Option<NotificationEvent>,
+    // the order the log is about
+    pub order_id: Option<String>,
+    // the component reporting errors and recoveries, e.g. `BTCUSDT orders stream`
+    pub component: Option<String>,
+    // where the log was created, written to the log file as its source
+    pub location: &'static Location<'static>,
     pub created_at: Timestamp,
 }
 
 impl Log {
+    #[track_caller]
     pub fn new(level: LogLevel, message: String, sound: Option<Sound>) -> Self {
         Log {
             level,
             message,
             sound,
+            event: None,
+            order_id: None,
+            component: None,
+            location: Location::caller(),
             created_at: Timestamp::now(),
         }
     }
+
+    pub fn with_event(mut self, event: NotificationEvent) -> Self {
+        self.event = Some(event);
+        self
+    }
+
+    pub fn with_order_id(mut self, order_id: impl Into<String>) -> Self {
+        self.order_id = Some(order_id.into());
+        self
+    }
+
+    pub fn with_component(mut self, component: impl Into<String>) -> Self {
+        self.component = Some(component.into());
+        self
+    }
 }
 
 pub struct LogManager {
     receiver: Receiver<Log>,
     term: Term,
     warnings_queue: VecDeque<(String, Timestamp)>,
-    status: Status,
-    with_sound: bool,
+    problems: Vec<Problem>,
+    sound_player: Option<SoundPlayer>,
+    notifier: Option<Notifier>,
+    log_file: Option<LogFile>,
+    history: VecDeque<Log>,
 }
 
 impl LogManager {
-    pub fn new(receiver: Receiver<Log>, term: Term, with_sound: bool) -> Self {
+    pub fn new(receiver: Receiver<Log>, term: Term) -> Self {
         LogManager {
             receiver,
             term,
             warnings_queue: VecDeque::new(),
-            status: Status::Ok,
-            with_sound,
+            problems: Vec::new(),
+            sound_player: None,
+            notifier: None,
+            log_file: None,
+            history: VecDeque::with_capacity(HISTORY_SIZE),
         }
     }
 
-    pub fn set_with_sound(&mut self, with_sound: bool) {
-        self.with_sound = with_sound;
+    pub fn set_notifier(&mut self, notifier: Option<Notifier>) {
+        self.notifier = notifier;
     }
 
-    pub fn log_info(&self, message: &str) {
-        let _ = self.term.write_line(&format!(
-            "{} {} {}",
-            style("[INFO]").green(),
-            Timestamp::now().to_utc_string(),
-            message
+    pub fn set_sound_player(&mut self, sound_player: Option<SoundPlayer>) {
+        self.sound_player = sound_player;
+    }
+
+    pub fn set_log_file(&mut self, log_file: Option<LogFile>) {
+        self.log_file = log_file;
+    }
+
+    #[track_caller]
+    pub fn log_info(&mut self, message: &str) {
+        self.write(&Log::new(LogLevel::Info, message.to_string(), None));
+    }
+
+    #[track_caller]
+    pub fn log_warning(&mut self, message: &str) {
+        self.write(&Log::new(
+            LogLevel::Warning("LOG".to_string(), None),
+            message.to_string(),
+            None,
         ));
     }
 
-    pub fn log_warning(&self, message: &str) {
-        let _ = self.term.write_line(&format!(
-            "{} {} {}",
-            style("[WARNING]").yellow(),
-            Timestamp::now().to_utc_string(),
-            message
+    #[track_caller]
+    pub fn log_error(&mut self, message: &str) {
+        self.write(&Log::new(
+            LogLevel::Error("LOG".to_string()),
+            message.to_string(),
+            None,
         ));
     }
 
-    pub fn log_error(&self, message: &str) {
+    /// The last logs, oldest first.
+    pub fn history(&self) -> &VecDeque<Log> {
+        &self.history
+    }
+
+    /// Clears all active problems, returns false if there were none.
+    pub fn acknowledge(&mut self) -> bool {
+        if self.problems.is_empty() {
+            return false;
+        }
+        let messages: Vec<String> = self.problems.drain(..).map(|p| p.message).collect();
+        self.log_info(&format!("Acknowledged {}", messages.join(", ")));
+        true
+    }
+
+    /// Prints the log to the terminal, appends it to the log file and keeps it for the log console.
+    fn write(&mut self, log: &Log) {
+        if self.history.len() == HISTORY_SIZE {
+            self.history.pop_front();
+        }
+        self.history.push_back(log.clone());
+
+        let level = match log.level {
+            LogLevel::Info => style("[INFO]").green(),
+            LogLevel::Warning(..) => style("[WARNING]").yellow(),
+            LogLevel::Error(_) => style("[ERROR]").red(),
+            LogLevel::Recovered(_) => style("[RECOVERED]").green(),
+        };
         let _ = self.term.write_line(&format!(
             "{} {} {}",
-            style("[ERROR]").red(),
-            Timestamp::now().to_utc_string(),
-            message
+            level,
+            log.created_at.to_utc_string(),
+            log.message
         ));
+
+        if let Some(log_file) = self.log_file.as_mut()
+            && let Err(e) = log_file.write(log)
+        {
+            // reported once, the terminal keeps the logs
+            self.log_file = None;
+            self.log_warning(&format!("Log file is disabled: {}", e));
+        }
     }
 
     pub fn status(&mut self) -> Status {
-        if let Status::Critical(_message) = &self.status {
-            return self.status.clone();
+        if let Some(problem) = self.problems.first() {
+            return Status::Critical(problem.message.clone(), self.problems.len(), problem.since);
         }
         if let Some((message, remove_at)) = self.warnings_queue.back() {
             let resp = Status::Warning(message.clone());
@@ -103,13 +201,13 @@ impl LogManager {
 
     pub fn consume(&mut self) {
         while let Ok(alert) = self.receiver.try_recv() {
+            self.write(&alert);
+            // a problem that is already active is not announced again
+            let mut repeated = false;
             match alert.level {
-                LogLevel::Info => {
-                    self.log_info(&alert.message);
-                }
+                LogLevel::Info => {}
                 LogLevel::Warning(message, show_for) => {
                     let show_for = show_for.unwrap_or(2);
-                    self.log_warning(&alert.message);
                     let until_ts = if let Some((_, ts)) = self.warnings_queue.front() {
                         Timestamp::from_seconds(ts.seconds() + show_for as u64)
                     } else {
@@ -118,20 +216,148 @@ impl LogManager {
                     self.warnings_queue.push_front((message, until_ts));
                 }
                 LogLevel::Error(message) => {
-                    self.log_error(&alert.message);
-                    self.status = Status::Critical(message);
+                    repeated = self
+                        .problems
+                        .iter()
+                        .any(|p| p.message == message && p.component == alert.component);
+                    if !repeated {
+                        self.problems.push(Problem {
+                            message,
+                            component: alert.component,
+                            since: alert.created_at,
+                        });
+                    }
                 }
-            }
-            if self.with_sound {
-                if let Some(sound) = alert.sound {
-                    let sound_clone = sound.clone();
-                    std::thread::spawn(move || {
-                        if let Err(e) = sound_clone.play() {
-                            println!("Sound error: {:?}", e);
-                        }
-                    });
+                LogLevel::Recovered(message) => {
+                    self.problems
+                        .retain(|p| p.message != message || p.component != alert.component);
                 }
             }
+            if repeated {
+                continue;
+            }
+            if let (Some(event), Some(notifier)) = (alert.event, &self.notifier) {
+                notifier.notify(event, &alert.message);
+            }
+            if let (Some(sound), Some(sound_player)) = (alert.sound, &self.sound_player) {
+                sound_player.play(sound);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{HISTORY_SIZE, Log, LogLevel, LogManager, Status};
+    use console::Term;
+    use std::sync::mpsc;
+
+    /// Discards the console output, so the test output stays readable.
+    #[cfg(unix)]
+    fn quiet_term() -> Term {
+        let null = std::fs::File::options()
+            .read(true)
+            .write(true)
+            .open("/dev/null")
+            .unwrap();
+        Term::read_write_pair(null.try_clone().unwrap(), null)
+    }
+
+    #[cfg(not(unix))]
+    fn quiet_term() -> Term {
+        Term::stderr()
+    }
+
+    #[test]
+    fn test_acknowledge_critical() {
+        let (sender, receiver) = mpsc::channel();
+        let mut logs_manager = LogManager::new(receiver, quiet_term());
+        assert!(!logs_manager.acknowledge());
+
+        sender
+            .send(Log::new(
+                LogLevel::Error("CONN".to_string()),
+                "connection reset".to_string(),
+                None,
+            ))
+            .unwrap();
+        logs_manager.consume();
+        assert!(matches!(logs_manager.status(), Status::Critical(_, 1, _)));
+
+        assert!(logs_manager.acknowledge());
+        assert_eq!(logs_manager.status(), Status::Ok);
+        assert_eq!(
+            logs_manager.history().back().unwrap().message,
+            "Acknowledged CONN"
+        );
+    }
+
+    #[test]
+    fn test_recovery() {
+        let (sender, receiver) = mpsc::channel();
+        let mut logs_manager = LogManager::new(receiver, quiet_term());
+        let error = |component: &str| {
+            Log::new(
+                LogLevel::Error("CONN".to_string()),
+                "connection reset".to_string(),
+                None,
+            )
+            .with_component(component)
+        };
+
+        sender.send(error("BTCUSDT market stream")).unwrap();
+        sender.send(error("ETHUSDT market stream")).unwrap();
+        // the same problem again
+        sender.send(error("BTCUSDT market stream")).unwrap();
+        logs_manager.consume();
+        let Status::Critical(message, count, since) = logs_manager.status() else {
+            panic!("expected a critical status");
+        };
+        assert_eq!(message, "CONN");
+        assert_eq!(count, 2);
+
+        sender
+            .send(
+                Log::new(
+                    LogLevel::Recovered("CONN".to_string()),
+                    "reconnected".to_string(),
+                    None,
+                )
+                .with_component("BTCUSDT market stream"),
+            )
+            .unwrap();
+        logs_manager.consume();
+        assert_eq!(
+            logs_manager.status(),
+            Status::Critical("CONN".to_string(), 1, since)
+        );
+
+        sender
+            .send(
+                Log::new(
+                    LogLevel::Recovered("CONN".to_string()),
+                    "reconnected".to_string(),
+                    None,
+                )
+                .with_component("ETHUSDT market stream"),
+            )
+            .unwrap();
+        logs_manager.consume();
+        assert_eq!(logs_manager.status(), Status::Ok);
+    }
+
+    #[test]
+    fn test_history_size() {
+        let (sender, receiver) = mpsc::channel();
+        let mut logs_manager = LogManager::new(receiver, quiet_term());
+        for i in 0..HISTORY_SIZE + 5 {
+            sender
+                .send(Log::new(LogLevel::Info, i.to_string(), None))
+                .unwrap();
         }
+        logs_manager.consume();
+
+        assert_eq!(logs_manager.history().len(), HISTORY_SIZE);
+        assert_eq!(logs_manager.history().front().unwrap().message, "5");
     }
 }
@@ -0,0 +1,123 @@
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// Events that can be delivered to notification sinks.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum NotificationEvent {
+    AlertTriggered,
+    OrderFilled,
+    StopLoss,
+    ConnectionLost,
+}
+
+impl NotificationEvent {
+    pub fn slug(&self) -> &'static str {
+        match self {
+            NotificationEvent::AlertTriggered => "alert_triggered",
+            NotificationEvent::OrderFilled => "order_filled",
+            NotificationEvent::StopLoss => "stop_loss",
+            NotificationEvent::ConnectionLost => "connection_lost",
+        }
+    }
+}
+
+impl fmt::Display for NotificationEvent {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let title = match self {
+            NotificationEvent::AlertTriggered => "Alert triggered",
+            NotificationEvent::OrderFilled => "Order filled",
+            NotificationEvent::StopLoss => "Stop-loss triggered",
+            NotificationEvent::ConnectionLost => "Connection lost",
+        };
+        write!(f, "{}", title)
+    }
+}
+
+/// JSON body of a webhook request.
+#[derive(Debug, Clone, Copy, PartialEq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum WebhookFormat {
+    /// `{"event", "title", "message", "time"}`
+    #[default]
+    Generic,
+    /// `{"text"}`
+    Slack,
+    /// `{"content"}`
+    Discord,
+    /// `{"chat_id", "text"}` for the `sendMessage` method
+    Telegram,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum NotificationSink {
+    /// Freedesktop notification (`notify-send`), Notification Center on macOS.
+    Desktop,
+    Webhook {
+        url: String,
+        #[serde(default)]
+        format: WebhookFormat,
+        // Telegram only
+        chat_id: Option<String>,
+    },
+    /// Runs the program with the arguments, the event is passed in `SCALPER_EVENT`,
+    /// `SCALPER_TITLE` and `SCALPER_MESSAGE` environment variables.
+    Command { command: Vec<String> },
+}
+
+/// `[[notifications]]` config section, a sink with the events it receives (all events if empty).
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct NotificationConfig {
+    #[serde(flatten)]
+    pub sink: NotificationSink,
+    #[serde(default)]
+    pub events: Vec<NotificationEvent>,
+}
+
+impl NotificationConfig {
+    pub fn accepts(&self, event: NotificationEvent) -> bool {
+        self.events.is_empty() || self.events.contains(&event)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{NotificationConfig, NotificationEvent, NotificationSink, WebhookFormat};
+
+    #[test]
+    fn test_parse_notifications() {
+        #[derive(serde::Deserialize)]
+        struct Section {
+            notifications: Vec<NotificationConfig>,
+        }
+
+        let section: Section = toml::from_str(
+            r#"
+            [[notifications]]
+            type = "desktop"
+
+            [[notifications]]
+            type = "webhook"
+            url = "https://example.com/hook"
+            format = "telegram"
+            chat_id = "42"
+            events = ["order_filled", "stop_loss"]
+            "#,
+        )
+        .unwrap();
+
+        assert_eq!(section.notifications[0].sink, NotificationSink::Desktop);
+        assert!(section.notifications[0].accepts(NotificationEvent::ConnectionLost));
+        assert_eq!(
+            section.notifications[1].sink,
+            NotificationSink::Webhook {
+                url: "https://example.com/hook".to_string(),
+                format: WebhookFormat::Telegram,
+                chat_id: Some("42".to_string()),
+            }
+        );
+        assert!(section.notifications[1].accepts(NotificationEvent::StopLoss));
+        assert!(!section.notifications[1].accepts(NotificationEvent::AlertTriggered));
+    }
+}
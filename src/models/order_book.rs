@@ -1,3 +1,4 @@
+use super::latency::StreamStats;
 use super::timestamp::Timestamp;
 use rust_decimal::Decimal;
 use std::collections::BTreeMap;
@@ -8,6 +9,7 @@ pub struct OrderBookState {
     pub asks: BTreeMap<Decimal, Decimal>,
     pub updated: Timestamp,
     pub online: bool,
+    pub stats: StreamStats,
 }
 
 impl OrderBookState {
@@ -17,6 +19,7 @@ impl OrderBookState {
             asks: BTreeMap::new(),
             updated: Timestamp::now(),
             online: false,
+            stats: StreamStats::default(),
         }
     }
 
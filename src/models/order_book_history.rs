@@ -0,0 +1,143 @@
+use super::order_book::OrderBookState;
+use super::timestamp::Timestamp;
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OrderBookSnapshot {
+    pub time: Timestamp,
+    pub bids: Vec<(Decimal, Decimal)>,
+    pub asks: Vec<(Decimal, Decimal)>,
+}
+
+impl OrderBookSnapshot {
+    pub fn levels(&self) -> impl Iterator<Item = &(Decimal, Decimal)> {
+        self.bids.iter().chain(self.asks.iter())
+    }
+}
+
+/// Time-sampled order book depth, used to draw the liquidity heatmap.
+/// Memory is bounded by `capacity` snapshots of at most `depth` levels per side.
+pub struct OrderBookHistory {
+    snapshots: VecDeque<OrderBookSnapshot>,
+    capacity: usize,
+    depth: usize,
+    sample_interval_ms: u64,
+    last_sample: Timestamp,
+}
+
+impl OrderBookHistory {
+    pub fn new(capacity: usize, depth: usize, sample_interval_ms: u64) -> Self {
+        Self {
+            snapshots: VecDeque::with_capacity(capacity),
+            capacity,
+            depth,
+            sample_interval_ms,
+            last_sample: Timestamp::from(0),
+        }
+    }
+
+    /// Stores a snapshot of the book if at least `sample_interval_ms` passed since the last one.
+    pub fn sample(&mut self, order_book: &OrderBookState, now: Timestamp) -> bool {
+        if now.milliseconds() < self.last_sample.milliseconds() + self.sample_interval_ms {
+            return false;
+        }
+        if order_book.bids.is_empty() && order_book.asks.is_empty() {
+            return false;
+        }
+
+        let bids = order_book
+            .bids
+            .iter()
+            .rev()
+            .take(self.depth)
+            .map(|(p, q)| (*p, *q))
+            .collect();
+        let asks = order_book
+            .asks
+            .iter()
+            .take(self.depth)
+            .map(|(p, q)| (*p, *q))
+            .collect();
+
+        if self.snapshots.len() == self.capacity {
+            self.snapshots.pop_front();
+        }
+        self.snapshots.push_back(OrderBookSnapshot {
+            time: now,
+            bids,
+            asks,
+        });
+        self.last_sample = now;
+
+        true
+    }
+
+    pub fn snapshots(&self) -> &VecDeque<OrderBookSnapshot> {
+        &self.snapshots
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{OrderBookHistory, Timestamp};
+    use crate::models::OrderBookState;
+    use rust_decimal::Decimal;
+
+    fn order_book() -> OrderBookState {
+        let mut order_book = OrderBookState::new();
+        order_book.init_snapshot(
+            vec![
+                (Decimal::from(97), Decimal::from(1)),
+                (Decimal::from(98), Decimal::from(2)),
+                (Decimal::from(99), Decimal::from(3)),
+            ],
+            vec![
+                (Decimal::from(100), Decimal::from(4)),
+                (Decimal::from(101), Decimal::from(5)),
+                (Decimal::from(102), Decimal::from(6)),
+            ],
+        );
+        order_book
+    }
+
+    #[test]
+    fn test_sample_interval() {
+        let mut history = OrderBookHistory::new(10, 10, 250);
+        let order_book = order_book();
+
+        assert!(history.sample(&order_book, Timestamp::from(1000)));
+        assert!(!history.sample(&order_book, Timestamp::from(1100)));
+        assert!(history.sample(&order_book, Timestamp::from(1250)));
+        assert_eq!(history.snapshots().len(), 2);
+    }
+
+    #[test]
+    fn test_sample_bounded() {
+        let mut history = OrderBookHistory::new(2, 2, 250);
+        let order_book = order_book();
+
+        for i in 1..=3 {
+            history.sample(&order_book, Timestamp::from(i * 1000));
+        }
+
+        assert_eq!(history.snapshots().len(), 2);
+        let last = history.snapshots().back().unwrap();
+        assert_eq!(last.time, Timestamp::from(3000));
+        assert_eq!(
+            last.bids,
+            vec![
+                (Decimal::from(99), Decimal::from(3)),
+                (Decimal::from(98), Decimal::from(2))
+            ]
+        );
+        assert_eq!(
+            last.asks,
+            vec![
+                (Decimal::from(100), Decimal::from(4)),
+                (Decimal::from(101), Decimal::from(5))
+            ]
+        );
+    }
+}
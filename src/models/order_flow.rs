@@ -1,13 +1,35 @@
+use super::latency::StreamStats;
+use super::orders::OrderSide;
 use super::timestamp::Timestamp;
 use rust_decimal::Decimal;
-use std::collections::BTreeMap;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, VecDeque};
 use std::sync::{Arc, RwLock};
 
+// trades not taken by the consumer are dropped beyond this limit
+const TRADES_LIMIT: usize = 10_000;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Trade {
+    pub time: Timestamp,
+    pub price: Decimal,
+    pub quantity: Decimal,
+    pub side: OrderSide,
+}
+
+impl Trade {
+    pub fn notional(&self) -> Decimal {
+        self.price * self.quantity
+    }
+}
+
 pub struct OrderFlowState {
     pub buys: BTreeMap<Decimal, Decimal>,
     pub sells: BTreeMap<Decimal, Decimal>,
+    trades: VecDeque<Trade>,
     pub updated: Timestamp,
     pub online: bool,
+    pub stats: StreamStats,
 }
 
 impl OrderFlowState {
@@ -15,8 +37,10 @@ impl OrderFlowState {
         Self {
             buys: BTreeMap::new(),
             sells: BTreeMap::new(),
+            trades: VecDeque::new(),
             updated: Timestamp::now(),
             online: false,
+            stats: StreamStats::default(),
         }
     }
 
@@ -34,6 +58,22 @@ impl OrderFlowState {
         );
     }
 
+    /// Accumulates the trade volume and keeps the trade until it is taken with `take_trades`.
+    pub fn add_trade(&mut self, trade: Trade) {
+        match trade.side {
+            OrderSide::Buy => self.buy(trade.price, trade.quantity),
+            OrderSide::Sell => self.sell(trade.price, trade.quantity),
+        }
+        if self.trades.len() == TRADES_LIMIT {
+            self.trades.pop_front();
+        }
+        self.trades.push_back(trade);
+    }
+
+    pub fn take_trades(&mut self) -> Vec<Trade> {
+        self.trades.drain(..).collect()
+    }
+
     pub fn get_balance(&self) -> Decimal {
         let total_buy: Decimal = self.buys.values().cloned().sum();
         let total_sell: Decimal = self.sells.values().cloned().sum();
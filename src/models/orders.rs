@@ -1,8 +1,10 @@
 use crate::models::Timestamp;
 use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
 use std::fmt::Display;
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
 pub enum OrderSide {
     Buy,
     Sell,
@@ -19,9 +21,13 @@ pub enum OrderType {
 pub enum OrderStatus {
     Pending,
     Filled,
+    // cancelled or expired, possibly after a partial fill
+    Canceled,
+    // a new order the exchange refused, it is not kept
+    Rejected,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct NewOrder {
     pub order_type: OrderType,
     pub order_side: OrderSide,
@@ -72,6 +78,23 @@ impl Order {
             is_update,
         }
     }
+
+    /// Reports a new order refused by the exchange, it has no id.
+    pub fn rejected(new_order: NewOrder) -> Self {
+        Self::new(
+            String::new(),
+            new_order.order_type,
+            new_order.order_side,
+            OrderStatus::Rejected,
+            new_order.quantity,
+            Decimal::ZERO,
+            new_order.price.unwrap_or_default(),
+            Decimal::ZERO,
+            Decimal::ZERO,
+            Timestamp::now(),
+            false,
+        )
+    }
 }
 
 impl Display for Order {
@@ -103,6 +126,9 @@ impl Orders {
     }
 
     pub fn consume(&mut self, order: Order) -> bool {
+        if order.order_status == OrderStatus::Rejected {
+            return false;
+        }
         let is_filled = order.order_status == OrderStatus::Filled;
         if let Some(pos) = self.orders.iter().position(|o| o.id == order.id) {
             if self.orders[pos].order_status == OrderStatus::Pending {
@@ -181,7 +207,7 @@ impl Orders {
         self.orders
             .iter()
             .filter(|o| {
-                o.order_status == OrderStatus::Filled && o.executed_quantity > Decimal::ZERO
+                o.order_status != OrderStatus::Pending && o.executed_quantity > Decimal::ZERO
             })
             .max_by_key(|o| o.timestamp)
     }
@@ -0,0 +1,311 @@
+use super::order_book_history::OrderBookSnapshot;
+use super::order_flow::Trade;
+use super::timestamp::Timestamp;
+use serde::{Deserialize, Serialize};
+use std::fs::{self, File, OpenOptions};
+use std::io::{BufRead, BufReader, BufWriter, Write};
+use std::path::{Path, PathBuf};
+
+const FILE_EXTENSION: &str = "jsonl";
+// the order book is recorded at most this often
+const BOOK_INTERVAL_MS: u64 = 1000;
+const SECONDS_PER_DAY: u64 = 24 * 60 * 60;
+
+/// `[recording]` config section.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RecordingConfig {
+    pub enabled: bool,
+    // daily files older than this are deleted
+    pub retention_days: u32,
+}
+
+impl Default for RecordingConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            retention_days: 3,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "lowercase")]
+enum Record {
+    Book(OrderBookSnapshot),
+    Trade(Trade),
+}
+
+/// Records the order book and the trades of a symbol as JSON lines to `<exchange>-<symbol>-<YYYY-MM-DD>.jsonl`,
+/// a new file is started every UTC day. The headless mode replays them with `replay_session`.
+pub struct SessionRecorder {
+    dir: PathBuf,
+    prefix: String,
+    retention_days: u32,
+    // the day of the open file
+    day: String,
+    file: Option<BufWriter<File>>,
+    last_book: Timestamp,
+}
+
+impl SessionRecorder {
+    /// Records to `$HOME/.scalper-rs/sessions/`.
+    pub fn open(
+        config: &RecordingConfig,
+        exchange: &str,
+        symbol: &str,
+    ) -> Result<Self, Box<dyn std::error::Error>> {
+        Ok(Self::open_in(
+            sessions_dir()?,
+            exchange,
+            symbol,
+            config.retention_days,
+        ))
+    }
+
+    pub fn open_in(dir: PathBuf, exchange: &str, symbol: &str, retention_days: u32) -> Self {
+        Self {
+            dir,
+            prefix: file_prefix(exchange, symbol),
+            retention_days,
+            day: String::new(),
+            file: None,
+            last_book: Timestamp::from(0),
+        }
+    }
+
+    /// Records the snapshot unless the last one is younger than `BOOK_INTERVAL_MS`.
+    /// Buffered trades are flushed with it.
+    pub fn record_book(
+        &mut self,
+        snapshot: &OrderBookSnapshot,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        if snapshot.time.milliseconds() < self.last_book.milliseconds() + BOOK_INTERVAL_MS {
+            return Ok(());
+        }
+        self.last_book = snapshot.time;
+        self.write(snapshot.time, &Record::Book(snapshot.clone()))?;
+        if let Some(file) = self.file.as_mut() {
+            file.flush()?;
+        }
+        Ok(())
+    }
+
+    pub fn record_trade(&mut self, trade: &Trade) -> Result<(), Box<dyn std::error::Error>> {
+        self.write(trade.time, &Record::Trade(trade.clone()))
+    }
+
+    fn write(
+        &mut self,
+        time: Timestamp,
+        record: &Record,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let day = time.format_utc("%Y-%m-%d");
+        if self.file.is_none() || day != self.day {
+            if let Some(mut file) = self.file.take() {
+                file.flush()?;
+            }
+            fs::create_dir_all(&self.dir)?;
+            let path = self.dir.join(file_name(&self.prefix, &day));
+            let file = OpenOptions::new().create(true).append(true).open(path)?;
+            self.file = Some(BufWriter::new(file));
+            self.day = day;
+            self.remove_old_files()?;
+        }
+
+        if let Some(file) = self.file.as_mut() {
+            writeln!(file, "{}", serde_json::to_string(record)?)?;
+        }
+        Ok(())
+    }
+
+    /// Deletes the daily files of the symbol older than `retention_days`.
+    fn remove_old_files(&self) -> Result<(), Box<dyn std::error::Error>> {
+        let today = chrono::NaiveDate::parse_from_str(&self.day, "%Y-%m-%d")?;
+        for entry in fs::read_dir(&self.dir)? {
+            let path = entry?.path();
+            let Some(day) = path
+                .file_stem()
+                .and_then(|stem| stem.to_str())
+                .and_then(|stem| stem.strip_prefix(&self.prefix))
+                .and_then(|day| chrono::NaiveDate::parse_from_str(day, "%Y-%m-%d").ok())
+            else {
+                continue;
+            };
+            if path.extension().is_some_and(|ext| ext == FILE_EXTENSION)
+                && (today - day).num_days() > self.retention_days as i64
+            {
+                fs::remove_file(&path)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Drop for SessionRecorder {
+    fn drop(&mut self) {
+        if let Some(file) = self.file.as_mut() {
+            file.flush().ok();
+        }
+    }
+}
+
+/// Reads the recorded order book snapshots and trades between `from` and `at` in time order.
+/// Returns false if nothing was recorded in that time.
+pub fn replay_session(
+    exchange: &str,
+    symbol: &str,
+    from: Timestamp,
+    at: Timestamp,
+    on_book: impl FnMut(OrderBookSnapshot),
+    on_trade: impl FnMut(Trade),
+) -> Result<bool, Box<dyn std::error::Error>> {
+    replay_session_in(
+        &sessions_dir()?,
+        exchange,
+        symbol,
+        from,
+        at,
+        on_book,
+        on_trade,
+    )
+}
+
+fn replay_session_in(
+    dir: &Path,
+    exchange: &str,
+    symbol: &str,
+    from: Timestamp,
+    at: Timestamp,
+    mut on_book: impl FnMut(OrderBookSnapshot),
+    mut on_trade: impl FnMut(Trade),
+) -> Result<bool, Box<dyn std::error::Error>> {
+    let prefix = file_prefix(exchange, symbol);
+    let mut found = false;
+    let mut day_start = from.seconds() / SECONDS_PER_DAY * SECONDS_PER_DAY;
+    while day_start <= at.seconds() {
+        let day = Timestamp::from_seconds(day_start).format_utc("%Y-%m-%d");
+        day_start += SECONDS_PER_DAY;
+        let file = match File::open(dir.join(file_name(&prefix, &day))) {
+            Ok(file) => file,
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => continue,
+            Err(err) => return Err(err.into()),
+        };
+        for line in BufReader::new(file).lines() {
+            // a line can be cut off when the app was killed while writing
+            let Ok(record) = serde_json::from_str::<Record>(&line?) else {
+                continue;
+            };
+            match record {
+                Record::Book(snapshot) if snapshot.time >= from && snapshot.time <= at => {
+                    found = true;
+                    on_book(snapshot);
+                }
+                Record::Trade(trade) if trade.time >= from && trade.time <= at => {
+                    found = true;
+                    on_trade(trade);
+                }
+                _ => {}
+            }
+        }
+    }
+    Ok(found)
+}
+
+fn sessions_dir() -> Result<PathBuf, Box<dyn std::error::Error>> {
+    let home = dirs::home_dir().ok_or("No home directory.")?;
+    Ok(home.join(".scalper-rs").join("sessions"))
+}
+
+fn file_prefix(exchange: &str, symbol: &str) -> String {
+    format!("{}-{}-", exchange, symbol)
+}
+
+fn file_name(prefix: &str, day: &str) -> String {
+    format!("{}{}.{}", prefix, day, FILE_EXTENSION)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{SessionRecorder, replay_session_in};
+    use crate::models::order_book_history::OrderBookSnapshot;
+    use crate::models::{OrderSide, Timestamp, Trade};
+    use rust_decimal::Decimal;
+    use std::fs;
+
+    fn snapshot(time: &str, bid: i64) -> OrderBookSnapshot {
+        OrderBookSnapshot {
+            time: Timestamp::parse_utc(time).unwrap(),
+            bids: vec![(Decimal::from(bid), Decimal::ONE)],
+            asks: vec![(Decimal::from(bid + 1), Decimal::ONE)],
+        }
+    }
+
+    fn trade(time: &str, price: i64) -> Trade {
+        Trade {
+            time: Timestamp::parse_utc(time).unwrap(),
+            price: Decimal::from(price),
+            quantity: Decimal::ONE,
+            side: OrderSide::Buy,
+        }
+    }
+
+    #[test]
+    fn test_record_and_replay() {
+        let dir =
+            std::env::temp_dir().join(format!("scalper-sessions-test-{}", std::process::id()));
+        fs::remove_dir_all(&dir).ok();
+
+        {
+            let mut recorder = SessionRecorder::open_in(dir.clone(), "binance", "BTCUSDT", 3);
+            recorder
+                .record_book(&snapshot("2026-01-05T23:59:59", 100))
+                .unwrap();
+            recorder
+                .record_trade(&trade("2026-01-05T23:59:59", 101))
+                .unwrap();
+            // younger than a second, not recorded
+            recorder
+                .record_book(&snapshot("2026-01-05T23:59:59", 90))
+                .unwrap();
+            recorder
+                .record_book(&snapshot("2026-01-06T00:00:01", 102))
+                .unwrap();
+            recorder
+                .record_trade(&trade("2026-01-06T00:00:02", 103))
+                .unwrap();
+        }
+        assert!(dir.join("binance-BTCUSDT-2026-01-05.jsonl").exists());
+
+        let mut books = Vec::new();
+        let mut trades = Vec::new();
+        let found = replay_session_in(
+            &dir,
+            "binance",
+            "BTCUSDT",
+            Timestamp::parse_utc("2026-01-05T23:00:00").unwrap(),
+            Timestamp::parse_utc("2026-01-06T00:00:01").unwrap(),
+            |snapshot| books.push(snapshot.bids[0].0),
+            |trade| trades.push(trade.price),
+        )
+        .unwrap();
+        assert!(found);
+        assert_eq!(books, vec![Decimal::from(100), Decimal::from(102)]);
+        // the last trade is after `at`
+        assert_eq!(trades, vec![Decimal::from(101)]);
+
+        let found = replay_session_in(
+            &dir,
+            "binance",
+            "ETHUSDT",
+            Timestamp::parse_utc("2026-01-05T23:00:00").unwrap(),
+            Timestamp::parse_utc("2026-01-06T00:00:01").unwrap(),
+            |_| {},
+            |_| {},
+        )
+        .unwrap();
+        assert!(!found);
+
+        fs::remove_dir_all(&dir).ok();
+    }
+}
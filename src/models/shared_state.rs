@@ -1,7 +1,9 @@
-use super::candles::SharedCandlesState;
-use super::open_interest::SharedOpenInterestState;
-use super::order_book::SharedOrderBookState;
-use super::order_flow::SharedOrderFlowState;
+use super::candles::{CandlesState, SharedCandlesState};
+use super::interval::Interval;
+use super::open_interest::{OpenInterestState, SharedOpenInterestState};
+use super::order_book::{OrderBookState, SharedOrderBookState};
+use super::order_flow::{OrderFlowState, SharedOrderFlowState};
+use std::sync::{Arc, RwLock};
 
 pub struct SharedState {
     pub candles: SharedCandlesState,
@@ -9,3 +11,14 @@ pub struct SharedState {
     pub open_interest: SharedOpenInterestState,
     pub order_flow: SharedOrderFlowState,
 }
+
+impl SharedState {
+    pub fn new(candles_capacity: usize, interval: Interval) -> Self {
+        Self {
+            candles: Arc::new(RwLock::new(CandlesState::new(candles_capacity, interval))),
+            order_book: Arc::new(RwLock::new(OrderBookState::new())),
+            open_interest: Arc::new(RwLock::new(OpenInterestState::new())),
+            order_flow: Arc::new(RwLock::new(OrderFlowState::new())),
+        }
+    }
+}
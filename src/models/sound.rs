@@ -1,36 +1,267 @@
-use rodio::{OutputStream, Sink, Source, source::SineWave};
+use super::logs::{Log, LogLevel};
+use super::tape_sound::Click;
+use rodio::source::{EmptyCallback, SineWave};
+use rodio::{Decoder, OutputStream, Sink, Source};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::io::Cursor;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::mpsc::{self, Sender};
 use std::time::Duration;
 
-#[derive(Debug, Clone)]
+// sounds queued beyond this are dropped, so a burst of fills does not keep playing for seconds
+const MAX_QUEUED: usize = 4;
+// tape clicks are dropped rather than delayed
+const MAX_QUEUED_CLICKS: usize = 2;
+const CLICK_MS: u64 = 15;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum Sound {
     Alert,
     OrderFilled,
+    PartialFill,
+    StopLoss,
+    OrderRejected,
+    Disconnected,
+    LargeTrade,
 }
 
 impl Sound {
-    pub fn play(&self) -> Result<(), Box<dyn std::error::Error>> {
+    const ALL: [Sound; 7] = [
+        Sound::Alert,
+        Sound::OrderFilled,
+        Sound::PartialFill,
+        Sound::StopLoss,
+        Sound::OrderRejected,
+        Sound::Disconnected,
+        Sound::LargeTrade,
+    ];
+
+    /// Built-in tones used when there is no sound file for the event.
+    fn frequencies(&self) -> &'static [f32] {
         match self {
-            Sound::OrderFilled => self.play_frequencies(&[523.25, 659.25]),
-            Sound::Alert => self.play_frequencies(&[523.25]),
+            Sound::OrderFilled => &[523.25, 659.25],
+            Sound::PartialFill => &[523.25],
+            Sound::Alert => &[523.25],
+            Sound::StopLoss => &[659.25, 523.25, 392.0],
+            Sound::OrderRejected => &[311.13, 311.13],
+            Sound::Disconnected => &[440.0, 329.63],
+            Sound::LargeTrade => &[880.0],
+        }
+    }
+}
+
+/// `[sounds]` config section: volume and WAV/OGG files replacing the built-in tones.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SoundConfig {
+    // 0.0 - 1.0
+    pub volume: f32,
+    pub alert: Option<PathBuf>,
+    pub order_filled: Option<PathBuf>,
+    pub partial_fill: Option<PathBuf>,
+    pub stop_loss: Option<PathBuf>,
+    pub order_rejected: Option<PathBuf>,
+    pub disconnected: Option<PathBuf>,
+    pub large_trade: Option<PathBuf>,
+}
+
+impl Default for SoundConfig {
+    fn default() -> Self {
+        Self {
+            volume: 1.0,
+            alert: None,
+            order_filled: None,
+            partial_fill: None,
+            stop_loss: None,
+            order_rejected: None,
+            disconnected: None,
+            large_trade: None,
+        }
+    }
+}
+
+impl SoundConfig {
+    fn file(&self, sound: Sound) -> Option<&PathBuf> {
+        match sound {
+            Sound::Alert => self.alert.as_ref(),
+            Sound::OrderFilled => self.order_filled.as_ref(),
+            Sound::PartialFill => self.partial_fill.as_ref(),
+            Sound::StopLoss => self.stop_loss.as_ref(),
+            Sound::OrderRejected => self.order_rejected.as_ref(),
+            Sound::Disconnected => self.disconnected.as_ref(),
+            Sound::LargeTrade => self.large_trade.as_ref(),
         }
     }
+}
+
+enum Playback {
+    Sound(Sound),
+    Click(Click),
+}
+
+/// Plays sounds one after another on a single audio thread, tape clicks have their own sink on the same output.
+/// Without an audio device the sounds are dropped after one warning.
+#[derive(Clone)]
+pub struct SoundPlayer {
+    sender: Sender<Playback>,
+}
+
+impl SoundPlayer {
+    pub fn start(config: &SoundConfig, logs_sender: Sender<Log>) -> Self {
+        let files = load_files(config, &logs_sender);
+        let volume = config.volume.clamp(0.0, 1.0);
+
+        let (sender, receiver) = mpsc::channel::<Playback>();
+        std::thread::spawn(move || {
+            // the stream has to live in the thread that plays, it is not Send
+            let output = OutputStream::try_default()
+                .map_err(|e| e.to_string())
+                .and_then(|(stream, handle)| {
+                    Sink::try_new(&handle)
+                        .and_then(|sink| Ok((stream, sink, Sink::try_new(&handle)?)))
+                        .map_err(|e| e.to_string())
+                });
+            let (_stream, sink, tape_sink) = match output {
+                Ok(output) => output,
+                Err(e) => {
+                    logs_sender
+                        .send(Log::new(
+                            LogLevel::Warning("SOUND".to_string(), Some(5)),
+                            format!("No audio output, sounds are disabled: {}", e),
+                            None,
+                        ))
+                        .ok();
+                    for _ in receiver {}
+                    return;
+                }
+            };
+            sink.set_volume(volume);
+            // sounds appended to `sink` and not finished yet, `sink.len()` counts each tone and gap
+            let queued = Arc::new(AtomicUsize::new(0));
+
+            for playback in receiver {
+                match playback {
+                    Playback::Sound(sound) => {
+                        if queued.load(Ordering::Relaxed) >= MAX_QUEUED {
+                            continue;
+                        }
+                        match files
+                            .get(&sound)
+                            .and_then(|bytes| Decoder::new(Cursor::new(bytes.clone())).ok())
+                        {
+                            Some(decoder) => sink.append(decoder),
+                            None => append_tones(&sink, sound.frequencies()),
+                        }
+                        queued.fetch_add(1, Ordering::Relaxed);
+                        sink.append(finished_callback(queued.clone()));
+                    }
+                    Playback::Click(click) => {
+                        if tape_sink.len() >= MAX_QUEUED_CLICKS {
+                            continue;
+                        }
+                        let tone = SineWave::new(click.frequency)
+                            .take_duration(Duration::from_millis(CLICK_MS))
+                            .fade_in(Duration::from_millis(2))
+                            .amplify(click.amplitude * 0.3);
+                        tape_sink.append(tone);
+                    }
+                }
+            }
+        });
+
+        Self { sender }
+    }
 
-    fn play_frequencies(&self, frequencies: &[f32]) -> Result<(), Box<dyn std::error::Error>> {
-        let (_stream, stream_handle) = OutputStream::try_default()?;
-        let sink = Sink::try_new(&stream_handle)?;
+    pub fn play(&self, sound: Sound) {
+        self.sender.send(Playback::Sound(sound)).ok();
+    }
 
-        for freq in frequencies.iter() {
-            let tone = SineWave::new(*freq)
-                .take_duration(Duration::from_millis(120))
-                .amplify(0.18);
-            sink.append(tone);
+    pub fn click(&self, click: Click) {
+        self.sender.send(Playback::Click(click)).ok();
+    }
+}
+
+/// Reads the configured files, files that can not be read or decoded are replaced by the built-in tones.
+fn load_files(config: &SoundConfig, logs_sender: &Sender<Log>) -> HashMap<Sound, Arc<[u8]>> {
+    let mut files = HashMap::new();
+    for sound in Sound::ALL {
+        let Some(path) = config.file(sound) else {
+            continue;
+        };
+        let result = fs::read(path)
+            .map_err(|e| e.to_string())
+            .map(Arc::<[u8]>::from)
+            .and_then(|bytes| match Decoder::new(Cursor::new(bytes.clone())) {
+                Ok(_) => Ok(bytes),
+                Err(e) => Err(e.to_string()),
+            });
+        match result {
+            Ok(bytes) => {
+                files.insert(sound, bytes);
+            }
+            Err(e) => {
+                logs_sender
+                    .send(Log::new(
+                        LogLevel::Warning("SOUND".to_string(), Some(5)),
+                        format!("Failed to load sound {}: {}", path.display(), e),
+                        None,
+                    ))
+                    .ok();
+            }
+        }
+    }
+    files
+}
 
-            // Small gap between notes
-            let gap = SineWave::new(0.0).take_duration(Duration::from_millis(30));
-            sink.append(gap);
+/// An empty source that decrements `queued` when the sink reaches it.
+fn finished_callback(queued: Arc<AtomicUsize>) -> EmptyCallback<f32> {
+    // the callback runs on every poll of the source, only the first one counts
+    let done = AtomicBool::new(false);
+    EmptyCallback::new(Box::new(move || {
+        if !done.swap(true, Ordering::Relaxed) {
+            queued.fetch_sub(1, Ordering::Relaxed);
         }
+    }))
+}
+
+fn append_tones(sink: &Sink, frequencies: &[f32]) {
+    for freq in frequencies.iter() {
+        let tone = SineWave::new(*freq)
+            .take_duration(Duration::from_millis(120))
+            .amplify(0.18);
+        sink.append(tone);
+
+        // Small gap between notes
+        let gap = SineWave::new(0.0).take_duration(Duration::from_millis(30));
+        sink.append(gap);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Sound, SoundConfig};
+    use std::path::PathBuf;
+
+    #[test]
+    fn test_sound_config() {
+        let config: SoundConfig = toml::from_str(
+            r#"
+            volume = 0.5
+            order_filled = "/sounds/fill.ogg"
+            "#,
+        )
+        .unwrap();
 
-        sink.sleep_until_end();
-        Ok(())
+        assert_eq!(config.volume, 0.5);
+        assert_eq!(
+            config.file(Sound::OrderFilled),
+            Some(&PathBuf::from("/sounds/fill.ogg"))
+        );
+        assert_eq!(config.file(Sound::Alert), None);
+        assert_eq!(SoundConfig::default().volume, 1.0);
     }
 }
@@ -0,0 +1,173 @@
+/// Fuzzy search over the exchange symbols, backs the symbol switcher overlay.
+pub struct SymbolSearch {
+    symbols: Vec<String>,
+    query: String,
+    selected: usize,
+    limit: usize,
+}
+
+impl SymbolSearch {
+    pub fn new(symbols: Vec<String>, limit: usize) -> Self {
+        Self {
+            symbols,
+            query: String::new(),
+            selected: 0,
+            limit,
+        }
+    }
+
+    pub fn query(&self) -> &str {
+        &self.query
+    }
+
+    pub fn push(&mut self, c: char) {
+        self.query.push(c.to_ascii_uppercase());
+        self.selected = 0;
+    }
+
+    pub fn pop(&mut self) {
+        self.query.pop();
+        self.selected = 0;
+    }
+
+    /// Returns up to `limit` best matches, the best first.
+    pub fn results(&self) -> Vec<&str> {
+        let query = normalize(&self.query);
+        let mut matches: Vec<(usize, &str)> = self
+            .symbols
+            .iter()
+            .filter_map(|symbol| {
+                fuzzy_score(&query, &normalize(symbol)).map(|s| (s, symbol.as_str()))
+            })
+            .collect();
+        matches.sort_by(|a, b| {
+            a.0.cmp(&b.0)
+                .then(a.1.len().cmp(&b.1.len()))
+                .then(a.1.cmp(b.1))
+        });
+        matches
+            .into_iter()
+            .take(self.limit)
+            .map(|(_, symbol)| symbol)
+            .collect()
+    }
+
+    pub fn selected_index(&self) -> usize {
+        self.selected
+    }
+
+    pub fn selected(&self) -> Option<&str> {
+        self.results().get(self.selected).copied()
+    }
+
+    pub fn select_next(&mut self) {
+        if self.selected + 1 < self.results().len() {
+            self.selected += 1;
+        }
+    }
+
+    pub fn select_prev(&mut self) {
+        self.selected = self.selected.saturating_sub(1);
+    }
+}
+
+// separators are ignored, so "btcusdt" finds "BTC_USDT"
+fn normalize(value: &str) -> String {
+    value
+        .chars()
+        .filter(|c| c.is_ascii_alphanumeric())
+        .map(|c| c.to_ascii_uppercase())
+        .collect()
+}
+
+/// Lower is better, `None` if the query is not a subsequence of the symbol.
+fn fuzzy_score(query: &str, symbol: &str) -> Option<usize> {
+    if query.is_empty() {
+        return Some(0);
+    }
+    if symbol == query {
+        return Some(0);
+    }
+    if symbol.starts_with(query) {
+        return Some(1);
+    }
+    if let Some(pos) = symbol.find(query) {
+        return Some(10 + pos);
+    }
+
+    // subsequence, penalized by the gaps between matched characters
+    let mut gaps = 0;
+    let mut last: Option<usize> = None;
+    let mut chars = symbol.char_indices();
+    for q in query.chars() {
+        let (i, _) = chars.find(|(_, c)| *c == q)?;
+        if let Some(last) = last {
+            gaps += i - last - 1;
+        }
+        last = Some(i);
+    }
+
+    Some(100 + gaps)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{SymbolSearch, fuzzy_score};
+
+    fn search() -> SymbolSearch {
+        SymbolSearch::new(
+            vec![
+                "ETHUSDT".to_string(),
+                "BTCUSDT".to_string(),
+                "BTCDOMUSDT".to_string(),
+                "BTC_USDT".to_string(),
+                "SOLUSDT".to_string(),
+            ],
+            3,
+        )
+    }
+
+    #[test]
+    fn test_fuzzy_score() {
+        assert_eq!(fuzzy_score("BTCUSDT", "BTCUSDT"), Some(0));
+        assert_eq!(fuzzy_score("BTC", "BTCUSDT"), Some(1));
+        assert_eq!(fuzzy_score("USDT", "BTCUSDT"), Some(13));
+        assert_eq!(fuzzy_score("BTCU", "BTCDOMUSDT"), Some(103));
+        assert_eq!(fuzzy_score("XRP", "BTCUSDT"), None);
+    }
+
+    #[test]
+    fn test_results() {
+        let mut search = search();
+        for c in "btcu".chars() {
+            search.push(c);
+        }
+
+        assert_eq!(search.query(), "BTCU");
+        assert_eq!(search.results(), vec!["BTCUSDT", "BTC_USDT", "BTCDOMUSDT"]);
+
+        search.pop();
+        search.push('d');
+        search.push('o');
+        assert_eq!(search.results(), vec!["BTCDOMUSDT"]);
+    }
+
+    #[test]
+    fn test_selection() {
+        let mut search = search();
+        search.push('u');
+
+        assert_eq!(search.results().len(), 3);
+        search.select_prev();
+        assert_eq!(search.selected_index(), 0);
+        search.select_next();
+        search.select_next();
+        search.select_next();
+        assert_eq!(search.selected_index(), 2);
+        assert_eq!(search.selected(), Some(search.results()[2]));
+
+        // the selection resets with the query
+        search.push('s');
+        assert_eq!(search.selected_index(), 0);
+    }
+}
@@ -0,0 +1,143 @@
+use super::order_flow::Trade;
+use super::orders::OrderSide;
+use rust_decimal::Decimal;
+use rust_decimal::prelude::ToPrimitive;
+use serde::{Deserialize, Serialize};
+
+// pitch of buy and sell clicks, in Hz
+const BUY_FREQUENCY: f32 = 1320.0;
+const SELL_FREQUENCY: f32 = 660.0;
+// weight of a trade in the rolling average size
+const AVERAGE_WEIGHT: f64 = 0.05;
+
+/// `[tape_sound]` config section.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TapeSoundConfig {
+    pub enabled: bool,
+    // trades below this notional (in quote) are silent
+    pub min_notional: Decimal,
+    pub max_clicks_per_second: u32,
+    // 0.0 - 1.0, the loudest click
+    pub volume: f32,
+}
+
+impl Default for TapeSoundConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            min_notional: Decimal::from(1000),
+            max_clicks_per_second: 20,
+            volume: 0.5,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Click {
+    pub frequency: f32,
+    // 0.0 - 1.0
+    pub amplitude: f32,
+}
+
+/// Turns trades into clicks: the pitch is the side, the loudness is the size relative to the rolling average.
+pub struct TapeSound {
+    config: TapeSoundConfig,
+    average_notional: Option<f64>,
+    // start of the current second and the clicks played in it
+    window_start: u64,
+    window_clicks: u32,
+}
+
+impl TapeSound {
+    pub fn new(config: TapeSoundConfig) -> Self {
+        Self {
+            config,
+            average_notional: None,
+            window_start: 0,
+            window_clicks: 0,
+        }
+    }
+
+    pub fn enabled(&self) -> bool {
+        self.config.enabled
+    }
+
+    pub fn consume(&mut self, trade: &Trade) -> Option<Click> {
+        let notional = trade.notional();
+        if notional < self.config.min_notional {
+            return None;
+        }
+        let notional = notional.to_f64().unwrap_or(0.0);
+        let average = match self.average_notional {
+            Some(average) => average + (notional - average) * AVERAGE_WEIGHT,
+            None => notional,
+        };
+        self.average_notional = Some(average);
+
+        let now = trade.time.milliseconds();
+        if now >= self.window_start + 1000 {
+            self.window_start = now;
+            self.window_clicks = 0;
+        }
+        if self.window_clicks >= self.config.max_clicks_per_second {
+            return None;
+        }
+        self.window_clicks += 1;
+
+        // an average trade is played at a quarter of the volume
+        let relative = if average > 0.0 {
+            notional / average
+        } else {
+            1.0
+        };
+        let amplitude = ((relative / 4.0).sqrt() as f32).clamp(0.1, 1.0) * self.config.volume;
+
+        Some(Click {
+            frequency: match trade.side {
+                OrderSide::Buy => BUY_FREQUENCY,
+                OrderSide::Sell => SELL_FREQUENCY,
+            },
+            amplitude: amplitude.clamp(0.0, 1.0),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{BUY_FREQUENCY, SELL_FREQUENCY, TapeSound, TapeSoundConfig};
+    use crate::models::{OrderSide, Timestamp, Trade};
+    use rust_decimal::Decimal;
+
+    fn trade(ms: u64, quantity: i64, side: OrderSide) -> Trade {
+        Trade {
+            time: Timestamp::from_milliseconds(ms),
+            price: Decimal::from(100),
+            quantity: Decimal::from(quantity),
+            side,
+        }
+    }
+
+    #[test]
+    fn test_clicks() {
+        let mut tape = TapeSound::new(TapeSoundConfig {
+            enabled: true,
+            min_notional: Decimal::from(1000),
+            max_clicks_per_second: 2,
+            volume: 1.0,
+        });
+
+        // below the minimum size
+        assert!(tape.consume(&trade(0, 5, OrderSide::Buy)).is_none());
+
+        let buy = tape.consume(&trade(0, 10, OrderSide::Buy)).unwrap();
+        assert_eq!(buy.frequency, BUY_FREQUENCY);
+        let big_sell = tape.consume(&trade(10, 100, OrderSide::Sell)).unwrap();
+        assert_eq!(big_sell.frequency, SELL_FREQUENCY);
+        assert!(big_sell.amplitude > buy.amplitude);
+
+        // rate limited within the second
+        assert!(tape.consume(&trade(20, 10, OrderSide::Buy)).is_none());
+        assert!(tape.consume(&trade(1000, 10, OrderSide::Buy)).is_some());
+    }
+}
@@ -1,4 +1,4 @@
-use chrono::{TimeZone, Utc};
+use chrono::{DateTime, NaiveDateTime, TimeZone, Utc};
 use serde::{Deserialize, Serialize};
 use std::fmt;
 use std::time::{SystemTime, UNIX_EPOCH};
@@ -31,8 +31,33 @@ impl Timestamp {
     }
 
     pub fn to_utc_string(&self) -> String {
+        self.format_utc("%Y-%m-%dT%H:%M:%S")
+    }
+
+    /// Parses unix seconds, RFC 3339 or `YYYY-MM-DDTHH:MM[:SS]` in UTC.
+    pub fn parse_utc(value: &str) -> Result<Self, String> {
+        let value = value.trim();
+        if let Ok(seconds) = value.parse::<u64>() {
+            return Ok(Self::from_seconds(seconds));
+        }
+        if let Ok(dt) = DateTime::parse_from_rfc3339(value) {
+            return Ok(Timestamp(dt.timestamp_millis() as u64));
+        }
+        [
+            "%Y-%m-%dT%H:%M:%S",
+            "%Y-%m-%d %H:%M:%S",
+            "%Y-%m-%dT%H:%M",
+            "%Y-%m-%d %H:%M",
+        ]
+        .iter()
+        .find_map(|format| NaiveDateTime::parse_from_str(value, format).ok())
+        .map(|dt| Timestamp(dt.and_utc().timestamp_millis() as u64))
+        .ok_or_else(|| format!("Invalid time: {}", value))
+    }
+
+    pub fn format_utc(&self, format: &str) -> String {
         let dt = Utc.timestamp_millis_opt(self.0 as i64).unwrap();
-        dt.format("%Y-%m-%dT%H:%M:%S").to_string()
+        dt.format(format).to_string()
     }
 }
 
@@ -76,4 +101,14 @@ mod tests {
         let value: u64 = ts.into();
         assert_eq!(value, 1625079600000);
     }
+
+    #[test]
+    fn test_parse_utc() {
+        let ts = Timestamp::from_seconds(1625079600);
+        assert_eq!(Timestamp::parse_utc("1625079600"), Ok(ts));
+        assert_eq!(Timestamp::parse_utc("2021-06-30T19:00:00Z"), Ok(ts));
+        assert_eq!(Timestamp::parse_utc("2021-06-30T21:00:00+02:00"), Ok(ts));
+        assert_eq!(Timestamp::parse_utc("2021-06-30 19:00"), Ok(ts));
+        assert!(Timestamp::parse_utc("yesterday").is_err());
+    }
 }
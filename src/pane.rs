@@ -0,0 +1,822 @@
+use crate::exchanges::{Exchange, ExchangeFactory};
+use crate::models::{
+    Alert, AlertCondition, AlertContext, Alerts, Anchor, Area, BigTrades, Candle, ColorSchema,
+    Config, Drawing, Drawings, Indicators, Interval, Log, LogLevel, NewOrder, NotificationEvent,
+    Order, OrderBookHistory, OrderRoundTrip, OrderSide, OrderStatus, OrderType, Orders,
+    SessionRecorder, SharedState, Sound, SoundPlayer, Status, Symbol, TapeSound, Timestamp,
+    replay_session,
+};
+use crate::renderer::Renderer;
+use crate::trader::Trader;
+use crate::utils::load_font;
+use rust_decimal::Decimal;
+use std::path::PathBuf;
+use std::sync::mpsc::{self, Receiver, Sender};
+
+enum DrawingDrag {
+    // a trend line, or a zone, from `start` to the last mouse position
+    New {
+        start: Anchor,
+        end: Anchor,
+        zone: bool,
+    },
+    // moves the drawing with the mouse, `from` is the last mouse position
+    Move {
+        index: usize,
+        from: Anchor,
+    },
+}
+
+/// A chart with its own exchange streams, trader and renderer.
+/// Several panes share the window in the watch mode, one of them is active for trading.
+pub struct Pane {
+    pub exchange: Box<dyn Exchange>,
+    pub symbol: Symbol,
+    pub shared_state: SharedState,
+    pub interval: Interval,
+    pub trader: Trader,
+    pub renderer: Renderer,
+    pub alerts: Alerts,
+    pub order_book_history: OrderBookHistory,
+    pub indicators: Indicators,
+    pub big_trades: BigTrades,
+    pub tape_sound: TapeSound,
+    pub area: Area,
+    pub sl_triggered: bool,
+    pub drag_x: Option<i32>,
+    pub drawings: Drawings,
+    drawing_drag: Option<DrawingDrag>,
+    requested_candles_until: Option<Timestamp>,
+    orders_receiver: Receiver<Order>,
+    order_round_trip: OrderRoundTrip,
+    recorder: Option<SessionRecorder>,
+}
+
+impl Pane {
+    pub fn start(
+        config: &Config,
+        symbol: &str,
+        interval: Interval,
+        area: Area,
+        logs_sender: Sender<Log>,
+    ) -> Result<Self, Box<dyn std::error::Error>> {
+        Self::create(config, symbol, interval, area, logs_sender, None)
+    }
+
+    /// Renders the chart at `at` and saves it to `config.output` or to the screenshots folder.
+    /// Candles come from the exchange history, the order book and the order flow are replayed
+    /// from the session recorded while the chart was open (`[recording]`).
+    pub fn render_snapshot(
+        config: &Config,
+        symbol: &str,
+        at: Timestamp,
+        area: Area,
+        logs_sender: Sender<Log>,
+    ) -> Result<PathBuf, Box<dyn std::error::Error>> {
+        let mut pane = Self::create(
+            config,
+            symbol,
+            config.interval,
+            area,
+            logs_sender.clone(),
+            Some(at),
+        )?;
+        pane.indicators
+            .update(&pane.shared_state.candles.read().unwrap());
+        if !pane.replay_session(&config.exchange, at)? {
+            logs_sender
+                .send(Log::new(
+                    LogLevel::Warning("RECORDING".to_string(), None),
+                    format!(
+                        "No session of {} was recorded at {} UTC, the order book and the order flow are empty",
+                        pane.symbol.slug,
+                        at.format_utc("%Y-%m-%d %H:%M:%S")
+                    ),
+                    None,
+                ))
+                .ok();
+        }
+        pane.render(Status::Ok, false, true);
+
+        let annotation = config.screenshot_header.then(|| {
+            format!(
+                "{}  {}  {}  {} UTC",
+                pane.symbol.slug,
+                pane.exchange.name(),
+                pane.interval.slug(),
+                at.format_utc("%Y-%m-%d %H:%M:%S")
+            )
+        });
+        let path = match config.output.clone() {
+            Some(path) => path,
+            None => Config::screenshot_path(&pane.symbol.slug, at)?,
+        };
+        pane.renderer.save_png(&path, annotation.as_deref())?;
+        Ok(path)
+    }
+
+    /// Fills the order book, the heatmap, the order flow and the large trades from the recorded session,
+    /// from the first loaded candle until `at`. Returns false if nothing was recorded.
+    fn replay_session(
+        &mut self,
+        exchange: &str,
+        at: Timestamp,
+    ) -> Result<bool, Box<dyn std::error::Error>> {
+        let from = self
+            .shared_state
+            .candles
+            .read()
+            .unwrap()
+            .first()
+            .map_or(at, |candle| candle.open_time);
+        let found = replay_session(
+            exchange,
+            &self.symbol.slug,
+            from,
+            at,
+            |snapshot| {
+                let mut order_book = self.shared_state.order_book.write().unwrap();
+                order_book.init_snapshot(snapshot.bids, snapshot.asks);
+                self.order_book_history.sample(&order_book, snapshot.time);
+            },
+            |trade| {
+                let mut order_flow = self.shared_state.order_flow.write().unwrap();
+                match trade.side {
+                    OrderSide::Buy => order_flow.buy(trade.price, trade.quantity),
+                    OrderSide::Sell => order_flow.sell(trade.price, trade.quantity),
+                }
+                self.big_trades.consume(trade);
+            },
+        )?;
+
+        let order_book = self.shared_state.order_book.read().unwrap();
+        self.trader.set_bid_ask(order_book.bid(), order_book.ask());
+        Ok(found)
+    }
+
+    fn create(
+        config: &Config,
+        symbol: &str,
+        interval: Interval,
+        area: Area,
+        logs_sender: Sender<Log>,
+        snapshot_at: Option<Timestamp>,
+    ) -> Result<Self, Box<dyn std::error::Error>> {
+        let (orders_sender, orders_receiver) = mpsc::channel();
+
+        let mut exchange = ExchangeFactory::create(
+            config.exchange.as_str(),
+            symbol.to_string(),
+            200,
+            config,
+            logs_sender.clone(),
+            orders_sender,
+        )?;
+        let (symbol, shared_state) = match snapshot_at {
+            Some(at) => exchange.snapshot(interval, at)?,
+            None => exchange.start(interval)?,
+        };
+
+        let (font, font_message) =
+            load_font(config.font_family.as_deref(), config.font_path.as_deref());
+        if let Some(message) = font_message {
+            logs_sender
+                .send(Log::new(
+                    LogLevel::Warning("FONT".to_string(), Some(10)),
+                    message,
+                    None,
+                ))
+                .ok();
+        }
+        let drawings = Drawings::load(&config.exchange, &symbol.slug).unwrap_or_else(|e| {
+            logs_sender
+                .send(Log::new(
+                    LogLevel::Warning("DRAWINGS".to_string(), Some(10)),
+                    format!("Failed to load drawings: {}", e),
+                    None,
+                ))
+                .ok();
+            Drawings::new()
+        });
+
+        let alerts = Alerts::load(&config.exchange, &symbol.slug).unwrap_or_else(|e| {
+            logs_sender
+                .send(Log::new(
+                    LogLevel::Warning("ALERTS".to_string(), Some(10)),
+                    format!("Failed to load alerts: {}", e),
+                    None,
+                ))
+                .ok();
+            Alerts::new()
+        });
+
+        let mut renderer = Renderer::new(
+            area.width as usize,
+            area.height as usize,
+            symbol.tick_size,
+            ColorSchema::load(&config.theme)?,
+            font,
+            config,
+            exchange.capabilities(),
+        );
+        renderer.set_drawings(drawings.items().to_vec());
+
+        let trader = Trader::new(
+            symbol.clone(),
+            Orders::new(),
+            [
+                config.lot_mult_1.unwrap(),
+                config.lot_mult_2.unwrap(),
+                config.lot_mult_3.unwrap(),
+                config.lot_mult_4.unwrap(),
+            ],
+            config.lot_size.unwrap(),
+            config.sl_pnl,
+            exchange.capabilities(),
+        );
+
+        let indicators = Indicators::new(
+            &config.indicators,
+            shared_state.candles.read().unwrap().capacity(),
+        );
+
+        let recorder = if config.recording.enabled && snapshot_at.is_none() {
+            match SessionRecorder::open(&config.recording, &config.exchange, &symbol.slug) {
+                Ok(recorder) => Some(recorder),
+                Err(e) => {
+                    logs_sender
+                        .send(Log::new(
+                            LogLevel::Warning("RECORDING".to_string(), Some(10)),
+                            format!("Failed to start the session recording: {}", e),
+                            None,
+                        ))
+                        .ok();
+                    None
+                }
+            }
+        } else {
+            None
+        };
+
+        Ok(Self {
+            exchange,
+            shared_state,
+            interval,
+            trader,
+            renderer,
+            alerts,
+            // 10 minutes of 250ms samples
+            order_book_history: OrderBookHistory::new(2400, 100, 250),
+            indicators,
+            big_trades: BigTrades::new(config.big_trade_threshold_for(&symbol.slug), 5, 100),
+            tape_sound: TapeSound::new(config.tape_sound.clone()),
+            symbol,
+            area,
+            sl_triggered: false,
+            drag_x: None,
+            drawings,
+            drawing_drag: None,
+            requested_candles_until: None,
+            orders_receiver,
+            order_round_trip: OrderRoundTrip::default(),
+            recorder,
+        })
+    }
+
+    /// Applies the next order update, returns true if there was one.
+    pub fn consume_orders(&mut self, logs_sender: &Sender<Log>) -> bool {
+        match self.orders_receiver.try_recv() {
+            Ok(value) if value.order_status == OrderStatus::Rejected => {
+                self.order_round_trip.rejected();
+                true
+            }
+            Ok(value) => {
+                let order_str = value.to_string();
+                let order_id = value.id.clone();
+                // the exchange response to a submitted order
+                if !value.is_update
+                    && !self
+                        .trader
+                        .get_open_orders()
+                        .iter()
+                        .any(|o| o.id == value.id)
+                {
+                    self.order_round_trip.received(Timestamp::now());
+                }
+                // a cancelled order can be partially filled before the cancel
+                let partial_fill =
+                    value.order_status != OrderStatus::Filled
+                        && self.trader.get_open_orders().iter().any(|o| {
+                            o.id == value.id && o.executed_quantity < value.executed_quantity
+                        });
+                let executed = value.executed_quantity > Decimal::ZERO;
+                let filled = self.trader.consume_order(value);
+                if partial_fill {
+                    logs_sender
+                        .send(
+                            Log::new(
+                                LogLevel::Info,
+                                format!("partially filled: {}", order_str),
+                                Some(Sound::PartialFill),
+                            )
+                            .with_order_id(&order_id),
+                        )
+                        .ok();
+                }
+                if filled {
+                    let mut log = Log::new(LogLevel::Info, order_str, Some(Sound::OrderFilled))
+                        .with_order_id(order_id);
+                    if executed {
+                        log = log.with_event(NotificationEvent::OrderFilled);
+                    }
+                    logs_sender.send(log).ok();
+                }
+                true
+            }
+            Err(mpsc::TryRecvError::Empty) => false,
+            Err(mpsc::TryRecvError::Disconnected) => false,
+        }
+    }
+
+    /// Scans alerts and updates the data derived from the shared state.
+    /// Trades are played as clicks on `tape_player` when the tape sound is enabled.
+    /// Returns true if the pane should be redrawn.
+    pub fn update(
+        &mut self,
+        config: &Config,
+        logs_sender: &Sender<Log>,
+        tape_player: Option<&SoundPlayer>,
+    ) -> bool {
+        let mut force_redraw = false;
+
+        match self.alerts.check_file(Timestamp::now()) {
+            Ok(reloaded) => force_redraw |= reloaded,
+            Err(e) => Self::alerts_warning(logs_sender, format!("Failed to reload alerts: {}", e)),
+        }
+
+        if let (Some(bid), Some(ask)) = (self.trader.bid, self.trader.ask) {
+            let context = self.alert_context(bid, ask);
+            let triggered = self.alerts.scan(&context);
+            for alert in triggered.iter() {
+                logs_sender
+                    .send(
+                        Log::new(
+                            LogLevel::Info,
+                            format!("{}: alert triggered: {}", self.symbol.slug, alert.condition),
+                            Some(Sound::Alert),
+                        )
+                        .with_event(NotificationEvent::AlertTriggered),
+                    )
+                    .unwrap();
+                force_redraw = true;
+            }
+            if triggered.iter().any(|alert| !alert.repeat) {
+                self.save_alerts(logs_sender);
+            }
+        }
+
+        {
+            let order_book = self.shared_state.order_book.read().unwrap();
+            self.trader.set_bid_ask(order_book.bid(), order_book.ask());
+            if self
+                .order_book_history
+                .sample(&order_book, Timestamp::now())
+                && let Some(snapshot) = self.order_book_history.snapshots().back()
+            {
+                Self::record(&mut self.recorder, logs_sender, |recorder| {
+                    recorder.record_book(snapshot)
+                });
+            }
+        }
+
+        self.indicators
+            .update(&self.shared_state.candles.read().unwrap());
+
+        for trade in self.shared_state.order_flow.write().unwrap().take_trades() {
+            Self::record(&mut self.recorder, logs_sender, |recorder| {
+                recorder.record_trade(&trade)
+            });
+            if let Some(player) = tape_player
+                && self.tape_sound.enabled()
+                && let Some(click) = self.tape_sound.consume(&trade)
+            {
+                player.click(click);
+            }
+            if let Some(trade) = self.big_trades.consume(trade) {
+                logs_sender
+                    .send(Log::new(
+                        LogLevel::Info,
+                        format!(
+                            "{}: large {:?} trade: {} at {}",
+                            self.symbol.slug, trade.side, trade.quantity, trade.price
+                        ),
+                        if config.big_trade_sound {
+                            Some(Sound::LargeTrade)
+                        } else {
+                            None
+                        },
+                    ))
+                    .ok();
+            }
+        }
+
+        force_redraw
+    }
+
+    /// Writes to the session recording, which is stopped after a failed write so the error is logged once.
+    fn record(
+        recorder: &mut Option<SessionRecorder>,
+        logs_sender: &Sender<Log>,
+        write: impl FnOnce(&mut SessionRecorder) -> Result<(), Box<dyn std::error::Error>>,
+    ) {
+        let Some(active) = recorder.as_mut() else {
+            return;
+        };
+        if let Err(e) = write(active) {
+            *recorder = None;
+            logs_sender
+                .send(Log::new(
+                    LogLevel::Warning("RECORDING".to_string(), Some(10)),
+                    format!("Session recording stopped: {}", e),
+                    None,
+                ))
+                .ok();
+        }
+    }
+
+    fn alert_context(&self, bid: Decimal, ask: Decimal) -> AlertContext {
+        // volume spikes are compared to the average of this many closed candles
+        const AVERAGE_VOLUME_CANDLES: usize = 20;
+
+        // the closed candles to average and the current one
+        let mut candles: Vec<Candle> = self
+            .shared_state
+            .candles
+            .read()
+            .unwrap()
+            .iter()
+            .rev()
+            .take(AVERAGE_VOLUME_CANDLES + 1)
+            .collect();
+        candles.reverse();
+        let current_candle = candles.last().copied();
+        let closed = &candles[..candles.len().saturating_sub(1)];
+        let average = &closed[closed.len().saturating_sub(AVERAGE_VOLUME_CANDLES)..];
+        let average_volume = (!average.is_empty()).then(|| {
+            average.iter().map(|c| c.volume).sum::<Decimal>() / Decimal::from(average.len())
+        });
+        let open_interest = current_candle.and_then(|candle| {
+            self.shared_state
+                .open_interest
+                .read()
+                .unwrap()
+                .get(&candle.open_time)
+        });
+
+        AlertContext {
+            time: Timestamp::now(),
+            bid,
+            ask,
+            tick_size: self.symbol.tick_size,
+            last_closed_candle: closed.last().copied(),
+            current_candle,
+            average_volume,
+            open_interest,
+        }
+    }
+
+    pub fn add_alert(&mut self, alert: Alert, logs_sender: &Sender<Log>) {
+        self.reload_alerts(logs_sender);
+        self.alerts.add_alert(alert);
+        self.save_alerts(logs_sender);
+    }
+
+    pub fn clear_alerts(&mut self, logs_sender: &Sender<Log>) {
+        self.alerts.clear();
+        self.save_alerts(logs_sender);
+    }
+
+    /// Picks up edits of the alerts file, so they are not overwritten by the next save.
+    fn reload_alerts(&mut self, logs_sender: &Sender<Log>) {
+        if let Err(e) = self.alerts.reload() {
+            Self::alerts_warning(logs_sender, format!("Failed to reload alerts: {}", e));
+        }
+    }
+
+    fn save_alerts(&mut self, logs_sender: &Sender<Log>) {
+        if let Err(e) = self.alerts.save() {
+            Self::alerts_warning(logs_sender, format!("Failed to save alerts: {}", e));
+        }
+    }
+
+    fn alerts_warning(logs_sender: &Sender<Log>, message: String) {
+        logs_sender
+            .send(Log::new(
+                LogLevel::Warning("ALERTS".to_string(), Some(5)),
+                message,
+                None,
+            ))
+            .ok();
+    }
+
+    /// Requests older candles once the view gets close to the oldest loaded one.
+    pub fn load_older_candles(&mut self) {
+        let candles = self.shared_state.candles.read().unwrap();
+        if self.renderer.needs_older_candles(&candles)
+            && let Some(first) = candles.first()
+            && self.requested_candles_until != Some(first.open_time)
+        {
+            self.exchange
+                .load_candles(candles.interval, first.open_time);
+            self.requested_candles_until = Some(first.open_time);
+        }
+    }
+
+    pub fn set_interval(&mut self, interval: Interval) {
+        if interval != self.interval {
+            self.interval = interval;
+            self.exchange.set_interval(interval);
+            self.renderer.snap_to_live();
+        }
+    }
+
+    /// Converts a window position to the pane layout pixels.
+    pub fn local_pos(&self, x: f32, y: f32) -> (i32, i32) {
+        (
+            self.renderer.to_layout_px(x - self.area.left as f32),
+            self.renderer.to_layout_px(y - self.area.top as f32),
+        )
+    }
+
+    pub fn set_area(&mut self, area: Area) {
+        self.area = area;
+        self.renderer
+            .set_size(area.width as usize, area.height as usize);
+    }
+
+    /// Starts a trend line (or a zone) at the pixel, returns false outside of the chart.
+    /// Releasing the mouse without moving to another candle adds a level instead of a line.
+    pub fn start_drawing(&mut self, x: i32, y: i32, zone: bool) -> bool {
+        let candles = self.shared_state.candles.read().unwrap();
+        match self.renderer.px_to_anchor(x, y, &candles) {
+            Some(start) => {
+                self.drawing_drag = Some(DrawingDrag::New {
+                    start,
+                    end: start,
+                    zone,
+                });
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Starts moving the drawing under the pixel, returns false if there is none.
+    pub fn start_moving_drawing(&mut self, x: i32, y: i32) -> bool {
+        let candles = self.shared_state.candles.read().unwrap();
+        let Some(index) = self.renderer.drawing_at(x, y, &candles) else {
+            return false;
+        };
+        let Some(from) = self.renderer.px_to_anchor(x, y, &candles) else {
+            return false;
+        };
+        self.drawing_drag = Some(DrawingDrag::Move { index, from });
+        true
+    }
+
+    pub fn is_drawing(&self) -> bool {
+        self.drawing_drag.is_some()
+    }
+
+    /// Follows the mouse while a drawing is being created or moved.
+    pub fn drag_drawing(&mut self, x: i32, y: i32) {
+        let Some(drag) = self.drawing_drag.as_mut() else {
+            return;
+        };
+        let Some(to) = self
+            .renderer
+            .px_to_anchor(x, y, &self.shared_state.candles.read().unwrap())
+        else {
+            return;
+        };
+
+        let mut drawings = self.drawings.items().to_vec();
+        match drag {
+            DrawingDrag::New { start, end, zone } => {
+                *end = to;
+                drawings.extend(new_drawing(*start, to, *zone));
+            }
+            DrawingDrag::Move { index, from } => {
+                let dt = to.time.milliseconds() as i64 - from.time.milliseconds() as i64;
+                self.drawings.shift(*index, dt, to.price - from.price);
+                *from = to;
+                drawings = self.drawings.items().to_vec();
+            }
+        }
+        self.renderer.set_drawings(drawings);
+    }
+
+    /// Adds the drawing being created or keeps the moved one, and saves the drawings.
+    pub fn finish_drawing(&mut self, logs_sender: &Sender<Log>) {
+        let Some(drag) = self.drawing_drag.take() else {
+            return;
+        };
+        if let DrawingDrag::New { start, end, zone } = drag
+            && let Some(drawing) = new_drawing(start, end, zone)
+        {
+            self.drawings.add(drawing);
+        }
+        self.save_drawings(logs_sender);
+    }
+
+    /// Deletes the drawing under the pixel, returns false if there is none.
+    pub fn delete_drawing_at(&mut self, x: i32, y: i32, logs_sender: &Sender<Log>) -> bool {
+        let index = self
+            .renderer
+            .drawing_at(x, y, &self.shared_state.candles.read().unwrap());
+        match index.and_then(|index| self.drawings.remove(index)) {
+            Some(_) => {
+                self.save_drawings(logs_sender);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Adds an alert at the level under the pixel, returns false if there is no level.
+    /// A repeating alert triggers on every cross of the level, otherwise once the price reaches it.
+    pub fn level_to_alert(
+        &mut self,
+        x: i32,
+        y: i32,
+        repeat: bool,
+        logs_sender: &Sender<Log>,
+    ) -> bool {
+        let Some(bid) = self.trader.bid else {
+            return false;
+        };
+        let index = self
+            .renderer
+            .drawing_at(x, y, &self.shared_state.candles.read().unwrap());
+        let Some(Drawing::Level { price }) = index.map(|index| self.drawings.items()[index]) else {
+            return false;
+        };
+        let alert = if repeat {
+            Alert::new(AlertCondition::Cross { price }, true)
+        } else {
+            Alert::price(price, bid)
+        };
+        self.add_alert(alert, logs_sender);
+        true
+    }
+
+    fn save_drawings(&mut self, logs_sender: &Sender<Log>) {
+        self.renderer.set_drawings(self.drawings.items().to_vec());
+        if let Err(e) = self.drawings.save() {
+            logs_sender
+                .send(Log::new(
+                    LogLevel::Warning("DRAWINGS".to_string(), Some(5)),
+                    format!("Failed to save drawings: {}", e),
+                    None,
+                ))
+                .ok();
+        }
+    }
+
+    /// Flattens the position and cancels open orders when the loss hits `sl_pnl`.
+    pub fn check_stop_loss(&mut self, config: &Config, logs_sender: &Sender<Log>) -> bool {
+        if self.trader.bid.is_none() || self.sl_triggered {
+            return false;
+        }
+        let Some(sl_pnl) = config.sl_pnl else {
+            return false;
+        };
+        if self.trader.get_pnl() >= -sl_pnl.abs() {
+            return false;
+        }
+
+        self.sl_triggered = true;
+
+        self.trader.flat();
+        self.consume_orders(logs_sender);
+        self.cancel_all_orders();
+        self.consume_orders(logs_sender);
+        self.trader.flat();
+
+        logs_sender
+            .send(
+                Log::new(
+                    LogLevel::Error("SL".to_string()),
+                    format!(
+                        "{}: stop-loss triggered at pnl: {:.2}",
+                        self.symbol.slug,
+                        self.trader.get_pnl()
+                    ),
+                    Some(Sound::StopLoss),
+                )
+                .with_event(NotificationEvent::StopLoss),
+            )
+            .unwrap();
+
+        true
+    }
+
+    /// Submits the order, the time to the exchange response is shown in the status bar.
+    pub fn place_order(&mut self, new_order: NewOrder) {
+        self.order_round_trip.sent(Timestamp::now());
+        self.exchange.place_order(new_order);
+    }
+
+    /// Cancels the open orders, with one request where the exchange supports it.
+    pub fn cancel_all_orders(&self) {
+        let orders: Vec<Order> = self.trader.get_open_orders().into_iter().cloned().collect();
+        if !orders.is_empty() {
+            self.exchange.cancel_all_orders(orders);
+        }
+    }
+
+    /// Moves the open limit order closest to the pixel to the price at the pixel,
+    /// returns false if there is no limit order to move.
+    pub fn move_order(&mut self, y: i32) -> bool {
+        if !self.exchange.capabilities().modify_orders {
+            return false;
+        }
+        let price = self.renderer.px_to_price(y);
+        let Some(order) = self
+            .trader
+            .get_open_orders()
+            .into_iter()
+            .filter(|o| o.order_type == OrderType::Limit)
+            .min_by_key(|o| (o.price - price).abs())
+            .cloned()
+        else {
+            return false;
+        };
+        if price <= Decimal::ZERO || order.price == price {
+            return false;
+        }
+        self.exchange.modify_order(order, price);
+        true
+    }
+
+    pub fn render(&mut self, status: Status, locked: bool, force_redraw: bool) {
+        self.renderer
+            .set_order_round_trip(self.order_round_trip.last_ms());
+        self.renderer.set_rate_limit(self.exchange.rate_limit());
+        self.renderer.render(
+            &self.shared_state,
+            &self.trader,
+            &self.order_book_history,
+            &self.big_trades,
+            &self.indicators,
+            status,
+            self.interval,
+            &self.alerts,
+            locked,
+            force_redraw,
+        );
+    }
+
+    /// Saves the chart to the screenshots folder, returns the file path.
+    pub fn screenshot(&self, config: &Config) -> Result<PathBuf, Box<dyn std::error::Error>> {
+        let now = Timestamp::now();
+        let annotation = config.screenshot_header.then(|| {
+            format!(
+                "{}  {}  {}  PnL {:.2}  {} UTC",
+                self.symbol.slug,
+                self.exchange.name(),
+                self.interval.slug(),
+                self.trader.get_pnl(),
+                now.format_utc("%Y-%m-%d %H:%M:%S")
+            )
+        });
+
+        let path = Config::screenshot_path(&self.symbol.slug, now)?;
+        self.renderer.save_png(&path, annotation.as_deref())?;
+        Ok(path)
+    }
+
+    /// Flattens the position and cancels open orders, used on shutdown.
+    pub fn cleanup(&mut self, logs_sender: &Sender<Log>) {
+        self.trader.flat();
+        self.consume_orders(logs_sender);
+        self.cancel_all_orders();
+        self.consume_orders(logs_sender);
+        self.trader.flat();
+    }
+
+    pub fn stop(&mut self) {
+        self.exchange.stop();
+    }
+}
+
+/// A click adds a level, a drag adds a trend line or a zone, an empty zone is skipped.
+fn new_drawing(start: Anchor, end: Anchor, zone: bool) -> Option<Drawing> {
+    if zone {
+        (start.time != end.time && start.price != end.price).then_some(Drawing::Zone { start, end })
+    } else if start.time == end.time {
+        Some(Drawing::Level { price: start.price })
+    } else {
+        Some(Drawing::TrendLine { start, end })
+    }
+}
@@ -1,6 +1,8 @@
 use crate::models::{
-    Alerts, CandlesState, ColorSchema, Interval, Layout, OpenInterestState, OrderBookState,
-    OrderFlowState, OrderSide, SharedState, Status, Timestamp,
+    Alerts, Anchor, BigTrades, Candle, CandlesState, Capabilities, ColorSchema, Config, Drawing,
+    Health, Indicators, Interval, LatencyConfig, Layout, LayoutConfig, Log, LogLevel,
+    NotificationEvent, OpenInterestState, OrderBookHistory, OrderBookState, OrderFlowState,
+    OrderSide, RateLimitStatus, SharedState, Sound, Status, SymbolSearch, Timestamp,
 };
 use crate::trader::Trader;
 use chrono::Utc;
@@ -8,20 +10,32 @@ use f64_fixed::to_fixed_string;
 use font_kit::font::Font;
 use raqote::{
     DrawOptions, DrawTarget, LineCap, LineJoin, PathBuilder, Point, SolidSource, Source,
-    StrokeStyle,
+    StrokeStyle, Transform,
 };
 use rust_decimal::Decimal;
-use rust_decimal::prelude::{FromStr, ToPrimitive};
+use rust_decimal::prelude::{FromPrimitive, FromStr, ToPrimitive};
+use std::collections::VecDeque;
 use std::convert::Into;
+use std::path::Path;
 
 const PX_PER_TICK_CHOICES: [&str; 17] = [
     "0.01", "0.02", "0.05", "0.1", "0.2", "0.5", "1", "3", "5", "7", "9", "11", "13", "15", "17",
     "19", "21",
 ];
 
+const CANDLE_WIDTH_CHOICES: [i32; 8] = [3, 5, 7, 9, 11, 15, 21, 29];
+
+// time axis label steps, in minutes
+const TIME_AXIS_STEPS: [u32; 11] = [1, 5, 15, 30, 60, 120, 240, 360, 720, 1440, 10080];
+
+// (x, width, [(y, quantity)])
+type HeatmapColumn = (i32, i32, Vec<(i32, Decimal)>);
+
 pub struct Renderer {
     dt: DrawTarget,
     layout: Layout,
+    layout_config: LayoutConfig,
+    ui_scale: f32,
     font: Font,
     book_entry_range: Decimal,
     center_px: usize,
@@ -35,21 +49,47 @@ pub struct Renderer {
     force_redraw: bool,
     balance: [Option<Decimal>; 100],
     balance_ts: Timestamp,
+    show_heatmap: bool,
+    big_trades_updated: Timestamp,
+    candle_width: i32,
+    // open time of the rightmost visible candle, None follows the live candle
+    view_end: Option<Timestamp>,
+    cursor: Option<(i32, i32)>,
+    // frame without the crosshair, restored before the next render
+    clean_frame: Option<Vec<u32>>,
+    // frames the active pane when several share the window
+    highlighted: bool,
+    drawings: Vec<Drawing>,
+    latency_config: LatencyConfig,
+    order_round_trip_ms: Option<u64>,
+    rate_limit: RateLimitStatus,
+    // order UI and the open interest are hidden when not supported
+    capabilities: Capabilities,
 }
 
 impl Renderer {
+    /// `width` and `height` are in physical pixels, the frame is drawn in pixels scaled by `ui_scale`.
     pub fn new(
         width: usize,
         height: usize,
         tick_size: Decimal,
         color_schema: ColorSchema,
         font: Font,
+        config: &Config,
+        capabilities: Capabilities,
     ) -> Self {
-        let layout = Layout::new(width as i32, height as i32);
+        let ui_scale = config.ui_scale();
+        let layout = Layout::new(
+            logical_px(width as i32, ui_scale),
+            logical_px(height as i32, ui_scale),
+            &config.layout,
+        );
         let center_px = layout.center_px() as usize;
         Self {
-            dt: DrawTarget::new(width as i32, height as i32),
+            dt: scaled_draw_target(width as i32, height as i32, ui_scale),
             layout,
+            layout_config: config.layout.clone(),
+            ui_scale,
             font,
             book_entry_range: Decimal::ZERO,
             center_px,
@@ -63,21 +103,88 @@ impl Renderer {
             force_redraw: true,
             balance: [None; 100],
             balance_ts: Timestamp::now(),
+            show_heatmap: config.heatmap,
+            big_trades_updated: Timestamp::from(0),
+            candle_width: 15,
+            view_end: None,
+            cursor: None,
+            clean_frame: None,
+            highlighted: false,
+            drawings: Vec::new(),
+            latency_config: config.latency.clone(),
+            order_round_trip_ms: None,
+            rate_limit: RateLimitStatus::default(),
+            capabilities,
         }
     }
 
     pub fn set_size(&mut self, width: usize, height: usize) {
         let width = width as i32;
         let height = height as i32;
-        if self.layout.width != width || self.layout.height != height {
-            self.layout = Layout::new(width, height);
+        if self.dt.width() != width || self.dt.height() != height {
+            self.layout = Layout::new(
+                logical_px(width, self.ui_scale),
+                logical_px(height, self.ui_scale),
+                &self.layout_config,
+            );
             self.force_redraw = true;
-            self.dt = DrawTarget::new(width, height);
+            self.dt = scaled_draw_target(width, height, self.ui_scale);
+            self.clean_frame = None;
             self.center_px = self.layout.center_px() as usize;
             self.center_price = Decimal::ZERO;
         }
     }
 
+    /// Converts physical pixels (e.g. the mouse position) to the layout pixels.
+    pub fn to_layout_px(&self, px: f32) -> i32 {
+        (px / self.ui_scale) as i32
+    }
+
+    pub fn layout(&self) -> &Layout {
+        &self.layout
+    }
+
+    pub fn set_layout_config(&mut self, layout_config: &LayoutConfig) {
+        if self.layout_config != *layout_config {
+            self.layout_config = layout_config.clone();
+            self.layout = Layout::new(self.layout.width, self.layout.height, layout_config);
+            self.force_redraw = true;
+            self.clean_frame = None;
+            self.center_px = self.layout.center_px() as usize;
+        }
+    }
+
+    // raqote misplaces glyphs under a scale transform,
+    // so text is drawn untransformed at the scaled position and size
+    fn draw_text(
+        &mut self,
+        point_size: f32,
+        text: &str,
+        point: Point,
+        src: &Source,
+        options: &DrawOptions,
+    ) {
+        let transform = *self.dt.get_transform();
+        self.dt.set_transform(&Transform::identity());
+        self.dt.draw_text(
+            &self.font,
+            point_size * self.ui_scale,
+            text,
+            Point::new(point.x * self.ui_scale, point.y * self.ui_scale),
+            src,
+            options,
+        );
+        self.dt.set_transform(&transform);
+    }
+
+    fn font_size(&self) -> f32 {
+        (self.layout.font_size * 72 / 96) as f32
+    }
+
+    fn large_font_size(&self) -> f32 {
+        ((self.layout.font_size + 2) * 72 / 96) as f32
+    }
+
     pub fn scale_in(&mut self) {
         if let Some(pos) = PX_PER_TICK_CHOICES
             .iter()
@@ -102,6 +209,113 @@ impl Renderer {
         }
     }
 
+    pub fn toggle_heatmap(&mut self) {
+        self.show_heatmap = !self.show_heatmap;
+        self.force_redraw = true;
+    }
+
+    pub fn zoom_in(&mut self) {
+        if let Some(pos) = CANDLE_WIDTH_CHOICES
+            .iter()
+            .position(|&x| x == self.candle_width)
+            && pos + 1 < CANDLE_WIDTH_CHOICES.len()
+        {
+            self.candle_width = CANDLE_WIDTH_CHOICES[pos + 1];
+            self.force_redraw = true;
+        }
+    }
+
+    pub fn zoom_out(&mut self) {
+        if let Some(pos) = CANDLE_WIDTH_CHOICES
+            .iter()
+            .position(|&x| x == self.candle_width)
+            && pos > 0
+        {
+            self.candle_width = CANDLE_WIDTH_CHOICES[pos - 1];
+            self.force_redraw = true;
+        }
+    }
+
+    pub fn set_cursor(&mut self, cursor: Option<(i32, i32)>) {
+        self.cursor = cursor;
+    }
+
+    pub fn set_color_schema(&mut self, color_schema: ColorSchema) {
+        self.color_schema = color_schema;
+        self.force_redraw = true;
+    }
+
+    pub fn set_highlighted(&mut self, highlighted: bool) {
+        if self.highlighted != highlighted {
+            self.highlighted = highlighted;
+            self.force_redraw = true;
+        }
+    }
+
+    /// Time from submitting the last order to the exchange response, shown in the status bar.
+    pub fn set_order_round_trip(&mut self, round_trip_ms: Option<u64>) {
+        self.order_round_trip_ms = round_trip_ms;
+    }
+
+    pub fn set_rate_limit(&mut self, rate_limit: RateLimitStatus) {
+        self.rate_limit = rate_limit;
+    }
+
+    /// Levels, trend lines and zones drawn over the candles, including the one being created.
+    pub fn set_drawings(&mut self, drawings: Vec<Drawing>) {
+        if self.drawings != drawings {
+            self.drawings = drawings;
+            self.force_redraw = true;
+        }
+    }
+
+    pub fn candle_width(&self) -> i32 {
+        self.candle_width
+    }
+
+    /// Moves the view by `candles` back in time, or forward if negative.
+    /// Reaching the last candle switches back to the live view.
+    pub fn pan(&mut self, candles: i32, candles_state: &CandlesState) {
+        let candles_vec = candles_state.to_vec();
+        if candles_vec.is_empty() {
+            return;
+        }
+        let last_index = candles_vec.len() as i32 - 1;
+        let end_index = self.view_end_index(&candles_vec) as i32;
+        let index = (end_index - candles).clamp(0, last_index);
+        self.view_end = if index == last_index {
+            None
+        } else {
+            Some(candles_vec[index as usize].open_time)
+        };
+        self.force_redraw = true;
+    }
+
+    pub fn snap_to_live(&mut self) {
+        self.view_end = None;
+        self.force_redraw = true;
+    }
+
+    /// Returns true when less than a screen of candles is loaded to the left of the view.
+    pub fn needs_older_candles(&self, candles_state: &CandlesState) -> bool {
+        let candles_vec = candles_state.to_vec();
+        if candles_vec.is_empty() {
+            return false;
+        }
+        let visible = (self.layout.candles_area.width / self.candle_width) as usize;
+        self.view_end_index(&candles_vec) + 1 < visible * 2
+    }
+
+    fn view_end_index(&self, candles: &[Candle]) -> usize {
+        match self.view_end {
+            Some(end) => candles
+                .iter()
+                .rposition(|c| c.open_time <= end)
+                .unwrap_or(0),
+            None => candles.len() - 1,
+        }
+    }
+
     pub fn price_to_px(&self, price: Decimal) -> i32 {
         (self.center_px as i32)
             + ((self.center_price - price) / self.tick_size * self.px_per_tick)
@@ -119,6 +333,9 @@ impl Renderer {
         &mut self,
         shared_state: &SharedState,
         trader: &Trader,
+        order_book_history: &OrderBookHistory,
+        big_trades: &BigTrades,
+        indicators: &Indicators,
         status: Status,
         interval: Interval,
         alerts: &Alerts,
@@ -129,6 +346,10 @@ impl Renderer {
             self.force_redraw = true;
         }
 
+        if let Some(clean_frame) = self.clean_frame.take() {
+            self.dt.get_data_mut().copy_from_slice(&clean_frame);
+        }
+
         let price;
         if let Some(last_candle) = shared_state.candles.read().unwrap().last() {
             price = last_candle.close;
@@ -149,17 +370,31 @@ impl Renderer {
         let scale_step = self.scale_step();
 
         if self.candles_updated != candles_updated || self.force_redraw {
-            self.draw_orders(trader, price, scale_step, alerts);
+            if self.layout.orders_area.width > 0 {
+                self.draw_orders(trader, price, scale_step, alerts);
+            }
             self.draw_candles(
                 &shared_state.candles.read().unwrap(),
                 &shared_state.open_interest.read().unwrap(),
+                order_book_history,
+                big_trades,
+                indicators,
                 scale_step,
                 &interval,
             );
             self.candles_updated = candles_updated;
         }
 
-        if self.order_book_updated != order_book_updated || self.force_redraw {
+        if self.layout.big_trades_area.width > 0
+            && (self.big_trades_updated != big_trades.updated || self.force_redraw)
+        {
+            self.draw_big_trades(big_trades);
+            self.big_trades_updated = big_trades.updated;
+        }
+
+        if self.layout.order_book_area.width > 0
+            && (self.order_book_updated != order_book_updated || self.force_redraw)
+        {
             self.draw_order_book(&shared_state.order_book.read().unwrap());
             self.order_book_updated = order_book_updated;
         }
@@ -175,17 +410,426 @@ impl Renderer {
                     self.balance[0] = Some(balance);
                     self.balance_ts = order_flow_updated;
                 }
-                self.draw_order_flow(&order_flow_state);
-                self.draw_order_flow_balance(balance);
+                if self.layout.order_flow_area.width > 0 {
+                    self.draw_order_flow(&order_flow_state);
+                    self.draw_order_flow_balance(balance);
+                }
             }
             self.order_flow_updated = order_flow_updated;
         }
 
-        self.draw_status(&status, trader);
+        self.draw_status(&status, trader, shared_state);
+
+        if self.highlighted {
+            self.draw_highlight();
+        }
+
+        if let Some((x, y)) = self.cursor {
+            self.clean_frame = Some(self.dt.get_data().to_vec());
+            self.draw_crosshair(x, y, price, shared_state);
+        }
 
         self.force_redraw = false;
     }
 
+    fn draw_highlight(&mut self) {
+        let mut pb = PathBuilder::new();
+        pb.rect(
+            1.0,
+            1.0,
+            (self.layout.width - 2) as f32,
+            (self.layout.height - 2) as f32,
+        );
+        self.dt.stroke(
+            &pb.finish(),
+            &Source::Solid(self.color_schema.crosshair.into()),
+            &StrokeStyle {
+                width: 2.0,
+                ..StrokeStyle::default()
+            },
+            &DrawOptions::new(),
+        );
+    }
+
+    /// Writes the last rendered frame without overlays (crosshair, symbol switcher) to a PNG file.
+    /// The annotation is drawn in a header line above the chart.
+    pub fn save_png(&self, path: &Path, annotation: Option<&str>) -> Result<(), String> {
+        let frame = self
+            .clean_frame
+            .as_deref()
+            .unwrap_or_else(|| self.dt.get_data());
+        let width = self.dt.width();
+        let height = self.dt.height();
+        let header_height = if annotation.is_some() {
+            (self.layout.status_area.height as f32 * self.ui_scale) as i32
+        } else {
+            0
+        };
+
+        let mut dt = DrawTarget::new(width, height + header_height);
+        dt.get_data_mut()[(header_height * width) as usize..].copy_from_slice(frame);
+
+        if let Some(annotation) = annotation {
+            dt.fill_rect(
+                0.,
+                0.,
+                width as f32,
+                header_height as f32,
+                &Source::Solid(self.color_schema.status_bar_background.into()),
+                &DrawOptions::new(),
+            );
+            dt.draw_text(
+                &self.font,
+                self.large_font_size() * self.ui_scale,
+                annotation,
+                Point::new(
+                    8. * self.ui_scale,
+                    (header_height / 2) as f32 + 5. * self.ui_scale,
+                ),
+                &Source::Solid(self.color_schema.text_light.into()),
+                &DrawOptions::new(),
+            );
+        }
+
+        dt.write_png(path).map_err(|e| e.to_string())
+    }
+
+    /// Draws the symbol switcher over the rendered frame.
+    pub fn draw_symbol_search(&mut self, symbol_search: &SymbolSearch) {
+        if self.clean_frame.is_none() {
+            self.clean_frame = Some(self.dt.get_data().to_vec());
+        }
+
+        let area = self.layout.candles_area;
+        let line_height = 18;
+        let results = symbol_search.results();
+        let width = 240;
+        let height = (results.len() as i32 + 1) * line_height + 12;
+        let left = area.left + (area.width - width) / 2;
+        let top = area.top + 60;
+
+        self.dt.fill_rect(
+            (left - 1) as f32,
+            (top - 1) as f32,
+            (width + 2) as f32,
+            (height + 2) as f32,
+            &Source::Solid(self.color_schema.border.into()),
+            &DrawOptions::new(),
+        );
+        self.dt.fill_rect(
+            left as f32,
+            top as f32,
+            width as f32,
+            height as f32,
+            &Source::Solid(self.color_schema.status_bar_background.into()),
+            &DrawOptions::new(),
+        );
+
+        self.draw_text(
+            self.large_font_size(),
+            &format!("Symbol: {}_", symbol_search.query()),
+            Point::new((left + 8) as f32, (top + line_height) as f32),
+            &Source::Solid(self.color_schema.text_light.into()),
+            &DrawOptions::new(),
+        );
+
+        for (i, symbol) in results.iter().enumerate() {
+            let y = top + (i as i32 + 2) * line_height;
+            if i == symbol_search.selected_index() {
+                self.dt.fill_rect(
+                    left as f32,
+                    (y - line_height + 5) as f32,
+                    width as f32,
+                    line_height as f32,
+                    &Source::Solid(self.color_schema.border.into()),
+                    &DrawOptions::new(),
+                );
+            }
+            self.draw_text(
+                self.large_font_size(),
+                symbol,
+                Point::new((left + 8) as f32, y as f32),
+                &Source::Solid(self.color_schema.text_light.into()),
+                &DrawOptions::new(),
+            );
+        }
+    }
+
+    /// Draws the last logs over the bottom of the chart, the newest at the bottom.
+    pub fn draw_log_console(&mut self, logs: &VecDeque<Log>, status: &Status) {
+        if self.clean_frame.is_none() {
+            self.clean_frame = Some(self.dt.get_data().to_vec());
+        }
+
+        let area = self.layout.candles_area;
+        let line_height = 16;
+        let max_lines = ((area.height / 2 - 12) / line_height - 1).max(0) as usize;
+        let lines = logs.len().min(max_lines);
+        let height = (lines as i32 + 1) * line_height + 12;
+        let top = area.top + area.height - height;
+
+        self.dt.fill_rect(
+            area.left as f32,
+            (top - 1) as f32,
+            area.width as f32,
+            (height + 1) as f32,
+            &Source::Solid(self.color_schema.border.into()),
+            &DrawOptions::new(),
+        );
+        self.dt.fill_rect(
+            area.left as f32,
+            top as f32,
+            area.width as f32,
+            height as f32,
+            &Source::Solid(self.color_schema.status_bar_background.into()),
+            &DrawOptions::new(),
+        );
+
+        let title = match status {
+            Status::Critical(message, ..) => format!("Logs | {} | Enter: acknowledge", message),
+            _ => "Logs".to_string(),
+        };
+        self.draw_text(
+            self.font_size(),
+            &title,
+            Point::new((area.left + 8) as f32, (top + line_height) as f32),
+            &Source::Solid(self.color_schema.text_light.into()),
+            &DrawOptions::new(),
+        );
+
+        for (i, log) in logs.iter().skip(logs.len() - lines).enumerate() {
+            let color = if log.event == Some(NotificationEvent::OrderFilled) {
+                self.color_schema.text_positive_pnl
+            } else if log.sound == Some(Sound::OrderRejected) {
+                self.color_schema.text_error
+            } else {
+                match log.level {
+                    LogLevel::Info => self.color_schema.text_light,
+                    LogLevel::Warning(..) => self.color_schema.status_warning,
+                    LogLevel::Error(_) => self.color_schema.text_error,
+                    LogLevel::Recovered(_) => self.color_schema.text_positive_pnl,
+                }
+            };
+            let y = top + (i as i32 + 2) * line_height;
+            self.draw_text(
+                self.font_size(),
+                &format!("{} {}", log.created_at.format_utc("%H:%M:%S"), log.message),
+                Point::new((area.left + 8) as f32, y as f32),
+                &Source::Solid(color.into()),
+                &DrawOptions::new(),
+            );
+        }
+    }
+
+    fn draw_crosshair(&mut self, x: i32, y: i32, last_price: Decimal, shared_state: &SharedState) {
+        let area = self.layout.candles_area;
+        let (left, right) = self.layout.price_span();
+        let bottom = area.top + area.height - self.layout.volume_height - 24;
+        if x < left || x >= right || y < area.top || y >= bottom {
+            return;
+        }
+
+        let color: SolidSource = self.color_schema.crosshair.into();
+        let style = StrokeStyle {
+            width: 1.0,
+            dash_array: vec![4., 4.],
+            ..Default::default()
+        };
+
+        let mut pb = PathBuilder::new();
+        pb.move_to(left as f32, y as f32);
+        pb.line_to(right as f32, y as f32);
+        if area.contains(x, y) {
+            pb.move_to(x as f32, area.top as f32);
+            pb.line_to(x as f32, (area.top + area.height) as f32);
+        }
+        let path = pb.finish();
+        self.dt
+            .stroke(&path, &Source::Solid(color), &style, &DrawOptions::new());
+
+        let price = self.px_to_price(y);
+        let ticks = ((price - last_price) / self.tick_size)
+            .round()
+            .to_i64()
+            .unwrap_or(0);
+        let percent = if last_price.is_zero() {
+            0.0
+        } else {
+            ((price - last_price) / last_price * Decimal::from(100))
+                .to_f64()
+                .unwrap_or(0.0)
+        };
+
+        // price label over the orders column
+        let orders_area = self.layout.orders_area;
+        if orders_area.width > 0 {
+            self.dt.fill_rect(
+                orders_area.left as f32,
+                (y - 8) as f32,
+                orders_area.width as f32,
+                16.,
+                &Source::Solid(color),
+                &DrawOptions::new(),
+            );
+            self.draw_text(
+                self.font_size(),
+                &to_fixed_string(price.to_f64().unwrap(), 8),
+                Point::new((orders_area.left + 4) as f32, (y + 4) as f32),
+                &Source::Solid(self.color_schema.background.into()),
+                &DrawOptions::new(),
+            );
+        }
+
+        let mut lines = vec![format!(
+            "{}  {:+} ticks  {:+.2}%",
+            to_fixed_string(price.to_f64().unwrap(), 8),
+            ticks,
+            percent
+        )];
+
+        // one pixel row can cover several ticks
+        let (low, high) = self.price_range_at_px(y);
+        {
+            let order_book = shared_state.order_book.read().unwrap();
+            let bids: Decimal = order_book.bids.range(low..=high).map(|(_, q)| *q).sum();
+            let asks: Decimal = order_book.asks.range(low..=high).map(|(_, q)| *q).sum();
+            let order_flow = shared_state.order_flow.read().unwrap();
+            let buys: Decimal = order_flow.buys.range(low..=high).map(|(_, q)| *q).sum();
+            let sells: Decimal = order_flow.sells.range(low..=high).map(|(_, q)| *q).sum();
+            lines.push(format!(
+                "Book {}/{}  Traded {}/{}",
+                bids.normalize(),
+                asks.normalize(),
+                buys.normalize(),
+                sells.normalize()
+            ));
+        }
+
+        if let Some(candle) = self.candle_at_x(x, &shared_state.candles.read().unwrap()) {
+            lines.push(format!(
+                "{}  O {}  H {}  L {}  C {}",
+                candle.open_time.format_utc("%d %b %H:%M"),
+                to_fixed_string(candle.open.to_f64().unwrap(), 8),
+                to_fixed_string(candle.high.to_f64().unwrap(), 8),
+                to_fixed_string(candle.low.to_f64().unwrap(), 8),
+                to_fixed_string(candle.close.to_f64().unwrap(), 8),
+            ));
+            let mut volume_line = format!("V {}", format_notional(candle.volume));
+            if self.capabilities.open_interest
+                && let Some(oi) = shared_state
+                    .open_interest
+                    .read()
+                    .unwrap()
+                    .get(&candle.open_time)
+            {
+                volume_line.push_str(&format!("  OI {}", format_notional(oi)));
+            }
+            lines.push(volume_line);
+        }
+
+        let line_height = 16;
+        self.dt.fill_rect(
+            (area.left + 4) as f32,
+            (area.top + 4) as f32,
+            320.,
+            (lines.len() as i32 * line_height + 6) as f32,
+            &Source::Solid(self.color_schema.status_bar_background.into()),
+            &DrawOptions::new(),
+        );
+        for (i, line) in lines.iter().enumerate() {
+            self.draw_text(
+                self.font_size(),
+                line,
+                Point::new(
+                    (area.left + 8) as f32,
+                    (area.top + 4 + (i as i32 + 1) * line_height) as f32,
+                ),
+                &Source::Solid(self.color_schema.text_light.into()),
+                &DrawOptions::new(),
+            );
+        }
+    }
+
+    /// Returns the lowest and highest prices drawn at the `px` row.
+    fn price_range_at_px(&self, px: i32) -> (Decimal, Decimal) {
+        let high = self.px_to_price(px);
+        let low = self.px_to_price(px + 1) + self.tick_size;
+        (low.min(high), high)
+    }
+
+    /// Returns the chart point under the pixel, the time is snapped to the candle open time.
+    pub fn px_to_anchor(&self, x: i32, y: i32, candles_state: &CandlesState) -> Option<Anchor> {
+        let area = self.layout.candles_area;
+        if x < area.left || x >= area.left + area.width || y < area.top || y >= area.height {
+            return None;
+        }
+        let candles = candles_state.to_vec();
+        if candles.is_empty() {
+            return None;
+        }
+        let last_candle = candles[self.view_end_index(&candles)];
+        let interval_ms = candles_state.interval.seconds() as i64 * 1000;
+        // left edge of the last candle slot
+        let last_x = area.left + area.width - 15 - self.candle_width / 2;
+        let slots = (x - last_x).div_euclid(self.candle_width) as i64;
+        let time = last_candle.open_time.milliseconds() as i64 + slots * interval_ms;
+        Some(Anchor {
+            time: Timestamp::from_milliseconds(time.max(0) as u64),
+            price: self.px_to_price(y),
+        })
+    }
+
+    /// Returns the index of the drawing under the pixel, the topmost one if several overlap.
+    pub fn drawing_at(&self, x: i32, y: i32, candles_state: &CandlesState) -> Option<usize> {
+        const TOLERANCE: f32 = 4.0;
+
+        let area = self.layout.candles_area;
+        if x < area.left || x >= area.left + area.width || y < area.top || y >= area.height {
+            return None;
+        }
+        let candles = candles_state.to_vec();
+        if candles.is_empty() {
+            return None;
+        }
+        let last_candle = candles[self.view_end_index(&candles)];
+        let interval = candles_state.interval;
+        let (px, py) = (x as f32, y as f32);
+
+        self.drawings.iter().rposition(|drawing| match *drawing {
+            Drawing::Level { price } => (self.price_to_px(price) as f32 - py).abs() <= TOLERANCE,
+            Drawing::TrendLine { start, end } => {
+                let (x1, y1) = self.anchor_to_px(start, &last_candle, &interval);
+                let (x2, y2) = self.anchor_to_px(end, &last_candle, &interval);
+                distance_to_segment((px, py), (x1 as f32, y1 as f32), (x2 as f32, y2 as f32))
+                    <= TOLERANCE
+            }
+            Drawing::Zone { start, end } => {
+                let (x1, y1) = self.anchor_to_px(start, &last_candle, &interval);
+                let (x2, y2) = self.anchor_to_px(end, &last_candle, &interval);
+                x >= x1.min(x2) && x <= x1.max(x2) && y >= y1.min(y2) && y <= y1.max(y2)
+            }
+        })
+    }
+
+    /// Returns the visible candle drawn at `x`.
+    fn candle_at_x(&self, x: i32, candles_state: &CandlesState) -> Option<Candle> {
+        let area = self.layout.candles_area;
+        if x >= area.left + area.width {
+            return None;
+        }
+        let candles = candles_state.to_vec();
+        if candles.is_empty() {
+            return None;
+        }
+        let i = (area.left + area.width - 15 - x + self.candle_width / 2)
+            .div_euclid(self.candle_width) as usize;
+        let end_index = self.view_end_index(&candles);
+        if i > end_index {
+            return None;
+        }
+        candles.get(end_index - i).copied()
+    }
+
     fn draw_orders(
         &mut self,
         trader: &Trader,
@@ -207,9 +851,8 @@ impl Renderer {
         // scale
         let tick_price = (self.center_price / scale_step).floor() * scale_step;
 
-        self.dt.draw_text(
-            &self.font,
-            (14 * 72 / 96) as f32,
+        self.draw_text(
+            self.font_size(),
             &to_fixed_string(tick_price.to_f64().unwrap(), 8),
             Point::new(
                 (area.left + 4) as f32,
@@ -221,9 +864,8 @@ impl Renderer {
 
         for i in 1..5 {
             let tp = tick_price + scale_step * Decimal::from(i);
-            self.dt.draw_text(
-                &self.font,
-                (14 * 72 / 96) as f32,
+            self.draw_text(
+                self.font_size(),
                 &to_fixed_string(tp.to_f64().unwrap(), 8),
                 Point::new((area.left + 4) as f32, self.price_to_px(tp) as f32 + 4_f32),
                 &Source::Solid(self.color_schema.text_light.into()),
@@ -233,9 +875,8 @@ impl Renderer {
 
         for i in 1..5 {
             let tp = tick_price - scale_step * Decimal::from(i);
-            self.dt.draw_text(
-                &self.font,
-                (14 * 72 / 96) as f32,
+            self.draw_text(
+                self.font_size(),
                 &to_fixed_string(tp.to_f64().unwrap(), 8),
                 Point::new((area.left + 4) as f32, self.price_to_px(tp) as f32 + 4_f32),
                 &Source::Solid(self.color_schema.text_light.into()),
@@ -291,8 +932,12 @@ impl Renderer {
             );
         }
 
-        for price_alert in alerts.alerts.iter() {
-            let y = self.price_to_px(price_alert.price);
+        for price in alerts
+            .alerts
+            .iter()
+            .filter_map(|alert| alert.condition.price())
+        {
+            let y = self.price_to_px(price);
 
             let mut pb = PathBuilder::new();
             pb.move_to((area.left + area.width / 2) as f32 - 1_f32, y as f32);
@@ -338,8 +983,11 @@ impl Renderer {
             );
         }
 
-        if let Some(alert) = alerts.last_triggered {
-            let y = self.price_to_px(alert.price);
+        if let Some(price) = alerts
+            .last_triggered
+            .and_then(|alert| alert.condition.price())
+        {
+            let y = self.price_to_px(price);
             let mut pb = PathBuilder::new();
             pb.move_to(area.left as f32 + 3_f32, y as f32);
             pb.line_to((area.left + area.width) as f32 - 1_f32, y as f32);
@@ -379,7 +1027,7 @@ impl Renderer {
         }
     }
 
-    fn draw_status(&mut self, status: &Status, trader: &Trader) {
+    fn draw_status(&mut self, status: &Status, trader: &Trader, shared_state: &SharedState) {
         let area = self.layout.status_area;
 
         self.dt.fill_rect(
@@ -394,12 +1042,12 @@ impl Renderer {
         let status_text = match status {
             Status::Ok => "OK".to_string(),
             Status::Warning(msg) => msg.to_string(),
-            Status::Critical(msg) => msg.to_string(),
+            Status::Critical(msg, ..) => msg.to_string(),
         };
         let status_color = match status {
             Status::Ok => self.color_schema.status_ok,
             Status::Warning(_) => self.color_schema.status_warning,
-            Status::Critical(_) => self.color_schema.status_critical,
+            Status::Critical(..) => self.color_schema.status_critical,
         };
 
         self.dt.fill_rect(
@@ -410,8 +1058,7 @@ impl Renderer {
             &Source::Solid(status_color.into()),
             &DrawOptions::new(),
         );
-        self.dt.draw_text(
-            &self.font,
+        self.draw_text(
             ((area.height - 4) * 72 / 96) as f32,
             &format!("{:^6}", status_text),
             Point::new(
@@ -430,17 +1077,30 @@ impl Renderer {
             "".to_string()
         };
 
-        let left_text = format!(
-            "<{} X {}> {} LOTS | {} ORDERS | PNL {} {}",
-            trader.size_quote.to_string(),
-            trader.get_size_multiplier().to_string(),
-            trader.get_lots(),
-            trader.get_open_orders().len(),
-            pnl,
-            commission,
-        );
-        self.dt.draw_text(
-            &self.font,
+        let problems = match status {
+            Status::Critical(_, count, since) => format!(
+                "{} PROBLEM{} {} | ",
+                count,
+                if *count > 1 { "S" } else { "" },
+                format_duration(Timestamp::now().seconds().saturating_sub(since.seconds()))
+            ),
+            _ => "".to_string(),
+        };
+        let left_text = if self.capabilities.trading {
+            format!(
+                "{}<{} X {}> {} LOTS | {} ORDERS | PNL {} {}",
+                problems,
+                trader.size_quote,
+                trader.get_size_multiplier(),
+                trader.get_lots(),
+                trader.get_open_orders().len(),
+                pnl,
+                commission,
+            )
+        } else {
+            format!("{}VIEW ONLY", problems)
+        };
+        self.draw_text(
             ((area.height - 4) * 72 / 96) as f32,
             &left_text,
             Point::new(
@@ -451,9 +1111,39 @@ impl Renderer {
             &DrawOptions::new(),
         );
 
+        // right-aligned before the clock, the font is monospace
+        let font_size = ((area.height - 4) * 72 / 96) as f32;
+        let char_width = font_size * 0.6;
+        let mut segments = self.health_segments(shared_state);
+        let min_x = (area.left + 60) as f32 + (left_text.len() + 2) as f32 * char_width;
+        let mut x;
+        loop {
+            let chars: usize = segments.iter().map(|(text, _)| text.len() + 2).sum();
+            x = (area.left + area.width - 80) as f32 - chars as f32 * char_width;
+            // narrow panes drop the stream indicators first, the round trip is kept
+            if x >= min_x || segments.len() <= 1 {
+                break;
+            }
+            segments.remove(0);
+        }
+        for (text, health) in segments {
+            let color = match health {
+                Health::Ok => self.color_schema.text_light,
+                Health::Warning => self.color_schema.status_warning,
+                Health::Critical => self.color_schema.text_error,
+            };
+            self.draw_text(
+                font_size,
+                &text,
+                Point::new(x, (area.top + area.height / 2 + 5) as f32),
+                &Source::Solid(color.into()),
+                &DrawOptions::new(),
+            );
+            x += (text.len() + 2) as f32 * char_width;
+        }
+
         let now = Utc::now();
-        self.dt.draw_text(
-            &self.font,
+        self.draw_text(
             ((area.height - 4) * 72 / 96) as f32,
             &now.format("%H:%M:%S").to_string(),
             Point::new(
@@ -465,6 +1155,82 @@ impl Renderer {
         );
     }
 
+    /// Status bar indicators: latency and message rate of the streams, time since the last message when the
+    /// order book or candles go quiet, and the last order round trip.
+    fn health_segments(&self, shared_state: &SharedState) -> Vec<(String, Health)> {
+        let now = Timestamp::now();
+        let config = &self.latency_config;
+        let stats = [
+            (
+                "OB",
+                shared_state.order_book.read().unwrap().stats.clone(),
+                true,
+            ),
+            (
+                "TRD",
+                shared_state.order_flow.read().unwrap().stats.clone(),
+                false,
+            ),
+            (
+                "CDL",
+                shared_state.candles.read().unwrap().stats.clone(),
+                true,
+            ),
+        ];
+
+        let mut segments: Vec<(String, Health)> = stats
+            .into_iter()
+            .map(|(label, stats, continuous)| {
+                let (Some(latency), Some(silence)) = (stats.latency_ms(), stats.silence_ms(now))
+                else {
+                    return (format!("{} --", label), Health::Ok);
+                };
+                let staleness = if continuous {
+                    config.staleness(silence)
+                } else {
+                    Health::Ok
+                };
+                if staleness != Health::Ok {
+                    return (
+                        format!("{} {:.1}s", label, silence as f64 / 1000.0),
+                        staleness,
+                    );
+                }
+                (
+                    format!("{} {}ms {}/s", label, latency, stats.rate(now)),
+                    config.health(latency),
+                )
+            })
+            .collect();
+
+        if let Some(round_trip) = self.order_round_trip_ms {
+            segments.push((
+                format!(
+                    "RTT{} {}ms",
+                    if self.capabilities.ws_orders {
+                        " WS"
+                    } else {
+                        ""
+                    },
+                    round_trip
+                ),
+                config.health(round_trip as i64),
+            ));
+        }
+
+        // shown when market data polls are held back or the exchange paused requests
+        if let Some(until) = self.rate_limit.backoff_until {
+            let seconds = until.milliseconds().saturating_sub(now.milliseconds()) / 1000 + 1;
+            segments.push((format!("RATE LIMITED {}s", seconds), Health::Critical));
+        } else if self.rate_limit.throttled {
+            segments.push((
+                format!("RATE {}%", self.rate_limit.used_percent),
+                Health::Warning,
+            ));
+        }
+        segments
+    }
+
     fn draw_order_flow(&mut self, order_flow_state: &OrderFlowState) {
         let area = self.layout.order_flow_area;
 
@@ -798,6 +1564,9 @@ impl Renderer {
         &mut self,
         candles_state: &CandlesState,
         open_interest_state: &OpenInterestState,
+        order_book_history: &OrderBookHistory,
+        big_trades: &BigTrades,
+        indicators: &Indicators,
         scale_step: Decimal,
         interval: &Interval,
     ) {
@@ -818,11 +1587,21 @@ impl Renderer {
 
         let current_price = candles.last().unwrap().close;
 
-        let candle_width = 15;
-        let body_width = 11;
+        // candles up to the rightmost visible one
+        let candles = &candles[..=self.view_end_index(&candles)];
+        let last_candle = candles.last().unwrap();
+
+        let candle_width = self.candle_width;
+        let body_width = (candle_width * 3 / 4).max(1);
         let volume_height = self.layout.volume_height;
         let limit = area.width / candle_width;
 
+        self.draw_grid(last_candle, scale_step, interval, candle_width);
+
+        if self.show_heatmap {
+            self.draw_heatmap(order_book_history, last_candle, interval, candle_width);
+        }
+
         for (i, candle) in candles.iter().rev().enumerate().take(limit as usize) {
             let x = area.width + area.left - (i as i32) * candle_width - 15;
 
@@ -872,6 +1651,12 @@ impl Renderer {
                 .fill(&path, &Source::Solid(color), &DrawOptions::new());
         }
 
+        self.draw_indicators(indicators, last_candle, interval, candle_width);
+
+        self.draw_big_trade_bubbles(big_trades, last_candle, interval, candle_width);
+
+        self.draw_drawings(last_candle, interval);
+
         let mut max_volume = Decimal::ZERO;
         let mut max_oi = Decimal::ZERO;
         let mut min_oi = Decimal::MAX;
@@ -911,7 +1696,10 @@ impl Renderer {
                     .unwrap_or(0)
                     .max(1);
 
-                let oi_height = if max_oi > Decimal::ZERO && !oi_diff.is_zero() {
+                let oi_height = if self.capabilities.open_interest
+                    && max_oi > Decimal::ZERO
+                    && !oi_diff.is_zero()
+                {
                     (((open_interest_state
                         .get(&candle.open_time)
                         .unwrap_or(Decimal::ZERO)
@@ -926,6 +1714,7 @@ impl Renderer {
 
                 let bar_top = (area.top + area.height) - bar_height;
                 let bar_left = x - (body_width / 2);
+                let bar_width = (body_width / 3).max(1);
 
                 let vol_color: SolidSource = if candle.is_bullish() {
                     self.color_schema.bullish_candle.into()
@@ -934,7 +1723,12 @@ impl Renderer {
                 };
 
                 let mut pb = PathBuilder::new();
-                pb.rect(bar_left as f32, bar_top as f32, 3., bar_height as f32);
+                pb.rect(
+                    bar_left as f32,
+                    bar_top as f32,
+                    bar_width as f32,
+                    bar_height as f32,
+                );
                 let path = pb.finish();
                 self.dt
                     .fill(&path, &Source::Solid(vol_color), &DrawOptions::new());
@@ -942,7 +1736,12 @@ impl Renderer {
                 if oi_height > 0 {
                     let oi_top = (area.top + area.height) - oi_height;
                     let mut pb = PathBuilder::new();
-                    pb.rect((bar_left + 6) as f32, oi_top as f32, 3., oi_height as f32);
+                    pb.rect(
+                        (bar_left + bar_width * 2) as f32,
+                        oi_top as f32,
+                        bar_width as f32,
+                        oi_height as f32,
+                    );
                     let path = pb.finish();
                     self.dt.fill(
                         &path,
@@ -1089,9 +1888,10 @@ impl Renderer {
             &DrawOptions::new(),
         );
 
-        self.dt.draw_text(
-            &self.font,
-            (16 * 72 / 96) as f32,
+        self.draw_time_axis(last_candle, interval, candle_width);
+
+        self.draw_text(
+            self.large_font_size(),
             &interval.slug().to_string(),
             Point::new(
                 (area.left + 8) as f32,
@@ -1101,9 +1901,8 @@ impl Renderer {
             &DrawOptions::new(),
         );
 
-        self.dt.draw_text(
-            &self.font,
-            (16 * 72 / 96) as f32,
+        self.draw_text(
+            self.large_font_size(),
             &to_fixed_string(current_price.to_f64().unwrap(), 8),
             Point::new(
                 (area.left + area.width - 55) as f32,
@@ -1114,6 +1913,406 @@ impl Renderer {
         );
     }
 
+    /// Draws horizontal lines at the price scale steps and vertical lines at the time axis labels.
+    fn draw_grid(
+        &mut self,
+        last_candle: &Candle,
+        scale_step: Decimal,
+        interval: &Interval,
+        candle_width: i32,
+    ) {
+        let area = self.layout.candles_area;
+        let bottom = area.top + area.height - self.layout.volume_height - 24;
+        let color: SolidSource = self.color_schema.grid.into();
+
+        let tick_price = (self.center_price / scale_step).floor() * scale_step;
+        for i in -8..=8 {
+            let y = self.price_to_px(tick_price + scale_step * Decimal::from(i));
+            if y < area.top || y >= bottom {
+                continue;
+            }
+            self.dt.fill_rect(
+                area.left as f32,
+                y as f32,
+                area.width as f32,
+                1.,
+                &Source::Solid(color),
+                &DrawOptions::new(),
+            );
+        }
+
+        for (x, _) in self.time_axis_labels(last_candle, interval, candle_width) {
+            self.dt.fill_rect(
+                x as f32,
+                area.top as f32,
+                1.,
+                (bottom - area.top) as f32,
+                &Source::Solid(color),
+                &DrawOptions::new(),
+            );
+        }
+    }
+
+    fn draw_time_axis(&mut self, last_candle: &Candle, interval: &Interval, candle_width: i32) {
+        let area = self.layout.candles_area;
+        let y = area.top + area.height - self.layout.volume_height - 10;
+
+        for (x, label) in self.time_axis_labels(last_candle, interval, candle_width) {
+            // keep clear of the interval and price labels
+            if x < area.left + 40 || x > area.left + area.width - 80 {
+                continue;
+            }
+            self.draw_text(
+                self.font_size(),
+                &label,
+                Point::new((x - 14) as f32, y as f32),
+                &Source::Solid(self.color_schema.text_light.into()),
+                &DrawOptions::new(),
+            );
+        }
+    }
+
+    /// Returns x of the candle centers that get a time label, with the label.
+    /// The step is picked so labels are at least 70px apart and stay put while panning.
+    fn time_axis_labels(
+        &self,
+        last_candle: &Candle,
+        interval: &Interval,
+        candle_width: i32,
+    ) -> Vec<(i32, String)> {
+        let area = self.layout.candles_area;
+        let step_minutes = TIME_AXIS_STEPS
+            .iter()
+            .copied()
+            .find(|&m| {
+                m >= interval.minutes() && (m / interval.minutes()) as i32 * candle_width >= 70
+            })
+            .unwrap_or(TIME_AXIS_STEPS[TIME_AXIS_STEPS.len() - 1]);
+        let step_ms = step_minutes as u64 * 60_000;
+
+        let mut labels = Vec::new();
+        let mut time = last_candle.open_time.milliseconds() / step_ms * step_ms;
+        loop {
+            let x = self.time_to_x(
+                Timestamp::from_milliseconds(time),
+                last_candle,
+                interval,
+                candle_width,
+            ) + candle_width / 2;
+            if x < area.left || time < step_ms {
+                break;
+            }
+            let timestamp = Timestamp::from_milliseconds(time);
+            let label = if time.is_multiple_of(24 * 60 * 60_000) {
+                timestamp.format_utc("%d %b")
+            } else {
+                timestamp.format_utc("%H:%M")
+            };
+            labels.push((x, label));
+            time -= step_ms;
+        }
+
+        labels
+    }
+
+    fn draw_heatmap(
+        &mut self,
+        order_book_history: &OrderBookHistory,
+        last_candle: &Candle,
+        interval: &Interval,
+        candle_width: i32,
+    ) {
+        let area = self.layout.candles_area;
+        let time_to_x = |time: Timestamp| self.time_to_x(time, last_candle, interval, candle_width);
+
+        let mut columns: Vec<HeatmapColumn> = Vec::new();
+        let mut max_quantity = Decimal::ZERO;
+        let snapshots = order_book_history.snapshots();
+        for (i, snapshot) in snapshots.iter().enumerate() {
+            let x = time_to_x(snapshot.time).max(area.left);
+            let next_x = match snapshots.get(i + 1) {
+                Some(next) => time_to_x(next.time),
+                None => x + 1,
+            }
+            .min(area.left + area.width);
+            if next_x <= x {
+                // a later snapshot takes this pixel column
+                continue;
+            }
+
+            let mut buckets: Vec<(i32, Decimal)> = Vec::new();
+            for (price, quantity) in snapshot.levels() {
+                let y = self.price_to_px(*price);
+                if y < area.top || y >= area.top + area.height {
+                    continue;
+                }
+                buckets.push((y, *quantity));
+                max_quantity = max_quantity.max(*quantity);
+            }
+            columns.push((x, next_x - x, buckets));
+        }
+
+        if max_quantity.is_zero() {
+            return;
+        }
+
+        let cell_height = self.px_per_tick.ceil().to_i32().unwrap_or(1).max(1);
+        for (x, width, buckets) in columns {
+            for (y, quantity) in buckets {
+                let alpha = (quantity / max_quantity * Decimal::from(255))
+                    .to_u8()
+                    .unwrap_or(255);
+                if alpha == 0 {
+                    continue;
+                }
+                self.dt.fill_rect(
+                    x as f32,
+                    (y - cell_height / 2) as f32,
+                    width as f32,
+                    cell_height as f32,
+                    &Source::Solid(self.color_schema.heatmap.with_alpha(alpha).into()),
+                    &DrawOptions::new(),
+                );
+            }
+        }
+    }
+
+    fn draw_indicators(
+        &mut self,
+        indicators: &Indicators,
+        last_candle: &Candle,
+        interval: &Interval,
+        candle_width: i32,
+    ) {
+        let area = self.layout.candles_area;
+
+        for indicator in indicators.iter() {
+            let lines_count = match indicator.points().back() {
+                Some(point) => point.lines.len(),
+                None => continue,
+            };
+            for line in 0..lines_count {
+                let mut pb = PathBuilder::new();
+                let mut started = false;
+                for point in indicator.points() {
+                    let x = self.time_to_x(point.open_time, last_candle, interval, candle_width)
+                        + candle_width / 2;
+                    if x < area.left {
+                        continue;
+                    }
+                    if x > area.left + area.width {
+                        break;
+                    }
+                    let y = match point.lines.get(line).and_then(|v| Decimal::from_f64(*v)) {
+                        Some(value) => self.price_to_px(value),
+                        None => continue,
+                    };
+                    if started {
+                        pb.line_to(x as f32, y as f32);
+                    } else {
+                        pb.move_to(x as f32, y as f32);
+                        started = true;
+                    }
+                }
+                let path = pb.finish();
+
+                let color = indicator.config.color();
+                // bands are drawn semi-transparent
+                let color = if line == 0 {
+                    color
+                } else {
+                    color.with_alpha(color.alpha() / 2)
+                };
+                self.dt.stroke(
+                    &path,
+                    &Source::Solid(color.into()),
+                    &StrokeStyle {
+                        width: 1.0,
+                        cap: LineCap::Round,
+                        join: LineJoin::Round,
+                        ..Default::default()
+                    },
+                    &DrawOptions::new(),
+                );
+            }
+        }
+    }
+
+    fn draw_big_trade_bubbles(
+        &mut self,
+        big_trades: &BigTrades,
+        last_candle: &Candle,
+        interval: &Interval,
+        candle_width: i32,
+    ) {
+        let area = self.layout.candles_area;
+        let threshold = big_trades.threshold().to_f32().unwrap_or(1.0).max(1.0);
+
+        for trade in big_trades.trades() {
+            let x = self.time_to_x(trade.time, last_candle, interval, candle_width);
+            let y = self.price_to_px(trade.price);
+            if x < area.left || x >= area.left + area.width || y < area.top || y >= area.height {
+                continue;
+            }
+
+            let radius = ((trade.notional().to_f32().unwrap_or(0.0) / threshold).sqrt() * 4.0)
+                .clamp(3.0, 20.0);
+            let color = match trade.side {
+                OrderSide::Buy => self.color_schema.volume_buy,
+                OrderSide::Sell => self.color_schema.volume_sell,
+            };
+
+            let mut pb = PathBuilder::new();
+            pb.arc(x as f32, y as f32, radius, 0., 2. * std::f32::consts::PI);
+            let path = pb.finish();
+            self.dt
+                .fill(&path, &Source::Solid(color.into()), &DrawOptions::new());
+        }
+    }
+
+    fn draw_big_trades(&mut self, big_trades: &BigTrades) {
+        let area = self.layout.big_trades_area;
+
+        self.dt.fill_rect(
+            area.left as f32,
+            area.top as f32,
+            area.width as f32,
+            area.height as f32,
+            &Source::Solid(self.color_schema.background.into()),
+            &DrawOptions::new(),
+        );
+        self.dt.fill_rect(
+            area.left as f32,
+            area.top as f32,
+            1.,
+            area.height as f32,
+            &Source::Solid(self.color_schema.border.into()),
+            &DrawOptions::new(),
+        );
+
+        let line_height = 14;
+        let limit = (area.height / line_height).max(0) as usize;
+        for (i, trade) in big_trades.trades().iter().rev().take(limit).enumerate() {
+            let color = match trade.side {
+                OrderSide::Buy => self.color_schema.text_positive_pnl,
+                OrderSide::Sell => self.color_schema.text_negative_pnl,
+            };
+            self.draw_text(
+                self.font_size(),
+                &format!(
+                    "{} {}",
+                    format_notional(trade.notional()),
+                    to_fixed_string(trade.price.to_f64().unwrap(), 8)
+                ),
+                Point::new(
+                    (area.left + 4) as f32,
+                    (area.top + (i as i32 + 1) * line_height) as f32,
+                ),
+                &Source::Solid(color.into()),
+                &DrawOptions::new(),
+            );
+        }
+    }
+
+    fn draw_drawings(&mut self, last_candle: &Candle, interval: &Interval) {
+        if self.drawings.is_empty() {
+            return;
+        }
+        let area = self.layout.candles_area;
+        let color = self.color_schema.drawing;
+        let style = StrokeStyle {
+            width: 1.0,
+            cap: LineCap::Round,
+            join: LineJoin::Round,
+            ..Default::default()
+        };
+
+        let mut pb = PathBuilder::new();
+        pb.rect(
+            area.left as f32,
+            area.top as f32,
+            area.width as f32,
+            area.height as f32,
+        );
+        self.dt.push_clip(&pb.finish());
+
+        for drawing in self.drawings.clone() {
+            let mut pb = PathBuilder::new();
+            match drawing {
+                Drawing::Level { price } => {
+                    let y = self.price_to_px(price) as f32;
+                    pb.move_to(area.left as f32, y);
+                    pb.line_to((area.left + area.width) as f32, y);
+                }
+                Drawing::TrendLine { start, end } => {
+                    let (x1, y1) = self.anchor_to_px(start, last_candle, interval);
+                    let (x2, y2) = self.anchor_to_px(end, last_candle, interval);
+                    pb.move_to(x1 as f32, y1 as f32);
+                    pb.line_to(x2 as f32, y2 as f32);
+                }
+                Drawing::Zone { start, end } => {
+                    let (x1, y1) = self.anchor_to_px(start, last_candle, interval);
+                    let (x2, y2) = self.anchor_to_px(end, last_candle, interval);
+                    pb.rect(
+                        x1.min(x2) as f32,
+                        y1.min(y2) as f32,
+                        (x1 - x2).abs() as f32,
+                        (y1 - y2).abs() as f32,
+                    );
+                    let path = pb.finish();
+                    self.dt.fill(
+                        &path,
+                        &Source::Solid(color.with_alpha(color.alpha() / 6).into()),
+                        &DrawOptions::new(),
+                    );
+                    self.dt.stroke(
+                        &path,
+                        &Source::Solid(color.into()),
+                        &style,
+                        &DrawOptions::new(),
+                    );
+                    continue;
+                }
+            }
+            self.dt.stroke(
+                &pb.finish(),
+                &Source::Solid(color.into()),
+                &style,
+                &DrawOptions::new(),
+            );
+        }
+
+        self.dt.pop_clip();
+    }
+
+    /// Anchors point to the middle of the candle.
+    fn anchor_to_px(
+        &self,
+        anchor: Anchor,
+        last_candle: &Candle,
+        interval: &Interval,
+    ) -> (i32, i32) {
+        let x = self.time_to_x(anchor.time, last_candle, interval, self.candle_width)
+            + self.candle_width / 2;
+        (x, self.price_to_px(anchor.price))
+    }
+
+    /// Maps time to x within the candles area, aligned with the candle slots.
+    fn time_to_x(
+        &self,
+        time: Timestamp,
+        last_candle: &Candle,
+        interval: &Interval,
+        candle_width: i32,
+    ) -> i32 {
+        let area = self.layout.candles_area;
+        let interval_ms = interval.seconds() as i64 * 1000;
+        // left edge of the last candle slot
+        let last_x = area.left + area.width - 15 - candle_width / 2;
+        let dt = time.milliseconds() as i64 - last_candle.open_time.milliseconds() as i64;
+        last_x + (dt * candle_width as i64 / interval_ms) as i32
+    }
+
     fn adjust_center(&mut self, price: Decimal) {
         if (price - self.center_price).abs() / self.tick_size * self.px_per_tick
             >= Decimal::from(self.layout.height / 4)
@@ -1148,3 +2347,48 @@ impl Renderer {
         self.book_entry_range = Decimal::ZERO;
     }
 }
+
+fn format_notional(notional: Decimal) -> String {
+    let value = notional.to_f64().unwrap_or(0.0);
+    if value >= 1_000_000.0 {
+        format!("{:.2}M", value / 1_000_000.0)
+    } else if value >= 1_000.0 {
+        format!("{:.0}K", value / 1_000.0)
+    } else {
+        format!("{:.0}", value)
+    }
+}
+
+fn logical_px(px: i32, ui_scale: f32) -> i32 {
+    (px as f32 / ui_scale) as i32
+}
+
+/// `45s`, `3m05s`, `2h07m`
+fn format_duration(seconds: u64) -> String {
+    match seconds {
+        0..60 => format!("{}s", seconds),
+        60..3600 => format!("{}m{:02}s", seconds / 60, seconds % 60),
+        _ => format!("{}h{:02}m", seconds / 3600, seconds % 3600 / 60),
+    }
+}
+
+fn distance_to_segment(p: (f32, f32), a: (f32, f32), b: (f32, f32)) -> f32 {
+    let (dx, dy) = (b.0 - a.0, b.1 - a.1);
+    let length_sq = dx * dx + dy * dy;
+    let t = if length_sq == 0.0 {
+        0.0
+    } else {
+        (((p.0 - a.0) * dx + (p.1 - a.1) * dy) / length_sq).clamp(0.0, 1.0)
+    };
+    let (cx, cy) = (a.0 + t * dx, a.1 + t * dy);
+    ((p.0 - cx).powi(2) + (p.1 - cy).powi(2)).sqrt()
+}
+
+// the identity transform keeps the fast path of `fill_rect`
+fn scaled_draw_target(width: i32, height: i32, ui_scale: f32) -> DrawTarget {
+    let mut dt = DrawTarget::new(width, height);
+    if ui_scale != 1.0 {
+        dt.set_transform(&Transform::scale(ui_scale, ui_scale));
+    }
+    dt
+}
@@ -1,4 +1,4 @@
-use crate::models::{NewOrder, Order, OrderSide, OrderType, Orders, Symbol};
+use crate::models::{Capabilities, NewOrder, Order, OrderSide, OrderType, Orders, Symbol};
 use rust_decimal::Decimal;
 use rust_decimal::prelude::ToPrimitive;
 
@@ -12,6 +12,7 @@ pub struct Trader {
     pub bid: Option<Decimal>,
     pub ask: Option<Decimal>,
     sl_pnl: Option<Decimal>,
+    capabilities: Capabilities,
 }
 
 impl Trader {
@@ -21,6 +22,7 @@ impl Trader {
         size_multiplier_options: [usize; 4],
         size_quote: Decimal,
         sl_pnl: Option<Decimal>,
+        capabilities: Capabilities,
     ) -> Self {
         Trader {
             symbol,
@@ -32,9 +34,22 @@ impl Trader {
             bid: None,
             ask: None,
             sl_pnl,
+            capabilities,
         }
     }
 
+    /// Drops the orders the exchange does not support: all orders on data-only exchanges,
+    /// stop orders and, without shorting, sells larger than the position.
+    fn supported(&self, order: NewOrder) -> Option<NewOrder> {
+        let capabilities = &self.capabilities;
+        let supported = capabilities.trading
+            && (order.order_type != OrderType::Stop || capabilities.stop_orders)
+            && (order.order_side == OrderSide::Buy
+                || capabilities.shorting
+                || order.quantity <= self.orders.base_balance());
+        supported.then_some(order)
+    }
+
     fn get_single_size(&mut self) -> Option<Decimal> {
         if let Some(size_base) = self.size_base {
             return Some(size_base);
@@ -50,84 +65,67 @@ impl Trader {
     }
 
     fn get_work_size(&mut self) -> Option<Decimal> {
-        if let Some(size) = self.get_single_size() {
-            Some(size * Decimal::from(self.get_size_multiplier()))
-        } else {
-            None
-        }
+        self.get_single_size()
+            .map(|size| size * Decimal::from(self.get_size_multiplier()))
     }
 
     pub fn market_buy(&mut self) -> Option<NewOrder> {
-        if let Some(size) = self.get_work_size() {
-            Some(NewOrder {
+        self.get_work_size()
+            .map(|size| NewOrder {
                 order_type: OrderType::Market,
                 order_side: OrderSide::Buy,
                 quantity: size,
                 price: None,
             })
-        } else {
-            None
-        }
+            .and_then(|order| self.supported(order))
     }
 
     pub fn market_sell(&mut self) -> Option<NewOrder> {
-        if let Some(size) = self.get_work_size() {
-            Some(NewOrder {
+        self.get_work_size()
+            .map(|size| NewOrder {
                 order_type: OrderType::Market,
                 order_side: OrderSide::Sell,
                 quantity: size,
                 price: None,
             })
-        } else {
-            None
-        }
+            .and_then(|order| self.supported(order))
     }
 
     pub fn limit(&mut self, price: Decimal) -> Option<NewOrder> {
-        if let Some(bid) = self.bid {
-            if let Some(size) = self.get_work_size() {
-                Some(NewOrder {
-                    order_type: OrderType::Limit,
-                    order_side: if price < bid {
-                        OrderSide::Buy
-                    } else {
-                        OrderSide::Sell
-                    },
-                    quantity: size,
-                    price: Some(price),
-                })
-            } else {
-                None
-            }
-        } else {
-            None
-        }
+        let bid = self.bid?;
+        self.get_work_size()
+            .map(|size| NewOrder {
+                order_type: OrderType::Limit,
+                order_side: if price < bid {
+                    OrderSide::Buy
+                } else {
+                    OrderSide::Sell
+                },
+                quantity: size,
+                price: Some(price),
+            })
+            .and_then(|order| self.supported(order))
     }
 
     pub fn stop(&mut self, price: Decimal) -> Option<NewOrder> {
-        if let Some(bid) = self.bid {
-            if let Some(size) = self.get_work_size() {
-                Some(NewOrder {
-                    order_type: OrderType::Stop,
-                    order_side: if price < bid {
-                        OrderSide::Sell
-                    } else {
-                        OrderSide::Buy
-                    },
-                    quantity: size,
-                    price: Some(price),
-                })
-            } else {
-                None
-            }
-        } else {
-            None
-        }
+        let bid = self.bid?;
+        self.get_work_size()
+            .map(|size| NewOrder {
+                order_type: OrderType::Stop,
+                order_side: if price < bid {
+                    OrderSide::Sell
+                } else {
+                    OrderSide::Buy
+                },
+                quantity: size,
+                price: Some(price),
+            })
+            .and_then(|order| self.supported(order))
     }
 
     pub fn flat(&self) -> Option<NewOrder> {
         let balance = self.orders.base_balance();
-        if balance != Decimal::ZERO {
+        let order = if balance != Decimal::ZERO {
             if balance > Decimal::ZERO {
                 Some(NewOrder {
                     order_type: OrderType::Market,
@@ -145,12 +143,13 @@ impl Trader {
             }
         } else {
             None
-        }
+        };
+        order.and_then(|order| self.supported(order))
     }
 
     pub fn reverse(&self) -> Option<NewOrder> {
         let balance = self.orders.base_balance();
-        if balance != Decimal::ZERO {
+        let order = if balance != Decimal::ZERO {
             if balance > Decimal::ZERO {
                 Some(NewOrder {
                     order_type: OrderType::Market,
@@ -168,7 +167,8 @@ impl Trader {
             }
         } else {
             None
-        }
+        };
+        order.and_then(|order| self.supported(order))
     }
 
     pub fn consume_order(&mut self, order: Order) -> bool {
@@ -198,6 +198,11 @@ impl Trader {
         }
     }
 
+    /// Returns true if there is an open position or open orders.
+    pub fn has_exposure(&self) -> bool {
+        self.orders.base_balance() != Decimal::ZERO || !self.orders.open().is_empty()
+    }
+
     pub fn get_last_closed_order(&self) -> Option<&Order> {
         self.orders.last_closed()
     }
@@ -238,3 +243,62 @@ impl Trader {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::Trader;
+    use crate::models::{
+        Capabilities, Order, OrderSide, OrderStatus, OrderType, Orders, Symbol, Timestamp,
+    };
+    use rust_decimal::Decimal;
+
+    fn trader(capabilities: Capabilities) -> Trader {
+        let symbol = Symbol {
+            slug: "BTCUSDT".to_string(),
+            tick_size: Decimal::new(1, 1),
+            step_size: Decimal::new(1, 3),
+            min_notional: Decimal::from(5),
+        };
+        let mut trader = Trader::new(
+            symbol,
+            Orders::new(),
+            [1, 2, 3, 4],
+            Decimal::from(100),
+            None,
+            capabilities,
+        );
+        trader.set_bid_ask(Some(Decimal::from(100)), Some(Decimal::from(100)));
+        trader
+    }
+
+    #[test]
+    fn test_capabilities() {
+        assert!(trader(Capabilities::DATA_ONLY).market_buy().is_none());
+
+        let spot = Capabilities {
+            trading: true,
+            ..Capabilities::DATA_ONLY
+        };
+        let mut trader = trader(spot);
+        assert!(trader.market_buy().is_some());
+        // no shorting and no stop orders
+        assert!(trader.market_sell().is_none());
+        assert!(trader.stop(Decimal::from(90)).is_none());
+
+        trader.consume_order(Order::new(
+            "1".to_string(),
+            OrderType::Market,
+            OrderSide::Buy,
+            OrderStatus::Filled,
+            Decimal::ONE,
+            Decimal::ONE,
+            Decimal::from(100),
+            Decimal::from(100),
+            Decimal::ZERO,
+            Timestamp::now(),
+            false,
+        ));
+        assert!(trader.flat().is_some());
+        assert!(trader.reverse().is_none());
+    }
+}
@@ -1,3 +1,9 @@
+mod font;
+mod notifier;
 mod sleep;
+mod ui_scale;
 
+pub use font::load_font;
+pub use notifier::Notifier;
 pub use sleep::{allow_sleep, prevent_sleep};
+pub use ui_scale::detect_ui_scale;
@@ -0,0 +1,62 @@
+use font_kit::family_name::FamilyName;
+use font_kit::font::Font;
+use font_kit::properties::Properties;
+use font_kit::source::SystemSource;
+use std::path::Path;
+use std::sync::Arc;
+
+// DejaVu Sans Mono, see assets/fonts/LICENSE-DejaVu.txt
+const BUNDLED_FONT: &[u8] = include_bytes!("../../assets/fonts/DejaVuSansMono.ttf");
+
+/// Loads the font from `font_path`, the `font_family` system font or the system monospace font.
+/// Falls back to the bundled font, the message explains why a configured font was not used.
+pub fn load_font(font_family: Option<&str>, font_path: Option<&Path>) -> (Font, Option<String>) {
+    let configured = if let Some(path) = font_path {
+        Some(
+            Font::from_path(path, 0)
+                .map_err(|e| format!("Failed to load font {}: {}", path.display(), e)),
+        )
+    } else {
+        font_family.map(|family| {
+            system_font(FamilyName::Title(family.to_string()))
+                .ok_or_else(|| format!("Font family not found: {}", family))
+        })
+    };
+
+    match configured {
+        Some(Ok(font)) => (font, None),
+        Some(Err(message)) => (
+            bundled_font(),
+            Some(format!("{}, using the bundled font", message)),
+        ),
+        None => (
+            system_font(FamilyName::Monospace).unwrap_or_else(bundled_font),
+            None,
+        ),
+    }
+}
+
+fn system_font(family: FamilyName) -> Option<Font> {
+    SystemSource::new()
+        .select_best_match(&[family], &Properties::new())
+        .ok()?
+        .load()
+        .ok()
+}
+
+fn bundled_font() -> Font {
+    Font::from_bytes(Arc::new(BUNDLED_FONT.to_vec()), 0).expect("The bundled font is invalid")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::load_font;
+    use std::path::Path;
+
+    #[test]
+    fn test_load_font_fallback() {
+        let (font, message) = load_font(None, Some(Path::new("/nonexistent/font.ttf")));
+        assert_eq!(font.family_name(), "DejaVu Sans Mono");
+        assert!(message.unwrap().starts_with("Failed to load font"));
+    }
+}
@@ -0,0 +1,188 @@
+use crate::models::{
+    Log, LogLevel, NotificationConfig, NotificationEvent, NotificationSink, Timestamp,
+    WebhookFormat,
+};
+use serde_json::{Value, json};
+use std::process::Command;
+use std::sync::mpsc::{self, Sender};
+use std::time::Duration;
+
+/// Delivers events to the configured sinks in a background thread, so slow webhooks do not block the UI.
+pub struct Notifier {
+    sender: Sender<(NotificationEvent, String)>,
+}
+
+impl Notifier {
+    /// Returns None if there are no sinks. Delivery errors are logged as warnings.
+    pub fn start(configs: Vec<NotificationConfig>, logs_sender: Sender<Log>) -> Option<Self> {
+        if configs.is_empty() {
+            return None;
+        }
+
+        let (sender, receiver) = mpsc::channel::<(NotificationEvent, String)>();
+        std::thread::spawn(move || {
+            let client = reqwest::blocking::Client::builder()
+                .timeout(Duration::from_secs(10))
+                .build()
+                .unwrap_or_default();
+
+            for (event, message) in receiver {
+                for config in configs.iter().filter(|c| c.accepts(event)) {
+                    if let Err(e) = deliver(&client, &config.sink, event, &message) {
+                        logs_sender
+                            .send(Log::new(
+                                LogLevel::Warning("NOTIFY".to_string(), Some(5)),
+                                format!("Failed to deliver {} notification: {}", event.slug(), e),
+                                None,
+                            ))
+                            .ok();
+                    }
+                }
+            }
+        });
+
+        Some(Self { sender })
+    }
+
+    pub fn notify(&self, event: NotificationEvent, message: &str) {
+        self.sender.send((event, message.to_string())).ok();
+    }
+}
+
+fn deliver(
+    client: &reqwest::blocking::Client,
+    sink: &NotificationSink,
+    event: NotificationEvent,
+    message: &str,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let title = format!("scalper-rs: {}", event);
+    match sink {
+        NotificationSink::Desktop => desktop_notification(&title, message),
+        NotificationSink::Webhook {
+            url,
+            format,
+            chat_id,
+        } => {
+            let body = webhook_body(*format, chat_id.as_deref(), event, &title, message);
+            client.post(url).json(&body).send()?.error_for_status()?;
+            Ok(())
+        }
+        NotificationSink::Command { command } => {
+            let (program, args) = command.split_first().ok_or("Empty command")?;
+            let status = Command::new(program)
+                .args(args)
+                .env("SCALPER_EVENT", event.slug())
+                .env("SCALPER_TITLE", &title)
+                .env("SCALPER_MESSAGE", message)
+                .status()?;
+            if !status.success() {
+                return Err(format!("{} exited with {}", program, status).into());
+            }
+            Ok(())
+        }
+    }
+}
+
+fn webhook_body(
+    format: WebhookFormat,
+    chat_id: Option<&str>,
+    event: NotificationEvent,
+    title: &str,
+    message: &str,
+) -> Value {
+    let text = format!("{}\n{}", title, message);
+    match format {
+        WebhookFormat::Generic => json!({
+            "event": event.slug(),
+            "title": title,
+            "message": message,
+            "time": Timestamp::now().to_utc_string(),
+        }),
+        WebhookFormat::Slack => json!({ "text": text }),
+        WebhookFormat::Discord => json!({ "content": text }),
+        WebhookFormat::Telegram => json!({ "chat_id": chat_id, "text": text }),
+    }
+}
+
+fn desktop_notification(title: &str, message: &str) -> Result<(), Box<dyn std::error::Error>> {
+    #[cfg(target_os = "macos")]
+    {
+        let script = format!(
+            "display notification {:?} with title {:?}",
+            message.replace('"', "'"),
+            title
+        );
+        Command::new("osascript").arg("-e").arg(script).status()?;
+        Ok(())
+    }
+
+    #[cfg(not(target_os = "macos"))]
+    {
+        // notify-send talks to the freedesktop notification service over D-Bus
+        let status = Command::new("notify-send")
+            .arg("--app-name=scalper-rs")
+            .arg(title)
+            .arg(message)
+            .status()?;
+        if !status.success() {
+            return Err(format!("notify-send exited with {}", status).into());
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Notifier;
+    use crate::models::{NotificationConfig, NotificationEvent, NotificationSink, WebhookFormat};
+    use std::io::{Read, Write};
+    use std::net::TcpListener;
+    use std::sync::mpsc;
+    use std::time::Duration;
+
+    #[test]
+    fn test_webhook() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}/hook", listener.local_addr().unwrap());
+        let (logs_sender, logs_receiver) = mpsc::channel();
+
+        let notifier = Notifier::start(
+            vec![NotificationConfig {
+                sink: NotificationSink::Webhook {
+                    url,
+                    format: WebhookFormat::Slack,
+                    chat_id: None,
+                },
+                events: vec![NotificationEvent::OrderFilled],
+            }],
+            logs_sender,
+        )
+        .unwrap();
+        // not routed to the webhook
+        notifier.notify(
+            NotificationEvent::AlertTriggered,
+            "BTCUSDT: price crossed 100",
+        );
+        notifier.notify(NotificationEvent::OrderFilled, "BTCUSDT: filled");
+
+        let (mut stream, _) = listener.accept().unwrap();
+        stream
+            .set_read_timeout(Some(Duration::from_secs(5)))
+            .unwrap();
+        let mut request = Vec::new();
+        let mut buf = [0; 1024];
+        while !String::from_utf8_lossy(&request).contains("filled\"}") {
+            let n = stream.read(&mut buf).unwrap();
+            assert!(n > 0);
+            request.extend_from_slice(&buf[..n]);
+        }
+        stream
+            .write_all(b"HTTP/1.1 200 OK\r\nContent-Length: 0\r\n\r\n")
+            .unwrap();
+
+        let request = String::from_utf8_lossy(&request);
+        assert!(request.starts_with("POST /hook"));
+        assert!(request.contains(r#"{"text":"scalper-rs: Order filled\nBTCUSDT: filled"}"#));
+        assert!(logs_receiver.try_recv().is_err());
+    }
+}
@@ -0,0 +1,12 @@
+use std::env;
+
+/// minifb does not expose the monitor scale factor,
+/// so it is taken from the toolkit variables HiDPI desktops set.
+pub fn detect_ui_scale() -> f32 {
+    ["GDK_SCALE", "QT_SCALE_FACTOR"]
+        .iter()
+        .filter_map(|name| env::var(name).ok())
+        .filter_map(|value| value.trim().parse::<f32>().ok())
+        .find(|scale| *scale >= 1.0)
+        .unwrap_or(1.0)
+}